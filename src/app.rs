@@ -13,7 +13,7 @@ fn main() {
    eframe::run_native(
       "Video player",
       native_options, Box::new(|_| Ok(Box::new(TestApp {
-         player: VidioPlayer::new(SavedSettings::default()),
+         player: VidioPlayer::new(SavedSettings::load()),
       }))),
    ).unwrap();
 }
@@ -29,4 +29,10 @@ impl App for TestApp {
              self.player.show(ui, frame.wgpu_render_state().unwrap()).unwrap();
           });
    }
+
+   fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+      if let Err(err) = self.player.saved_settings().save() {
+         println!("Couldn't save settings {err}");
+      }
+   }
 }
\ No newline at end of file
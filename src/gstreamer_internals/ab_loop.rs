@@ -0,0 +1,82 @@
+use anyhow::Result;
+use gstreamer::prelude::ElementExtManual;
+use gstreamer::{ClockTime, Pipeline, SeekFlags, SeekType};
+
+/// A-B repeat state, shared between the backend and the bus thread so
+/// ``SegmentDone`` can be answered straight away without waiting on the ui
+#[derive(Debug, Clone, Copy)]
+pub struct AbLoop {
+   pub a: ClockTime,
+   pub b: ClockTime,
+   /// remaining jumps back to ``a``, ``None`` loops forever
+   pub repeats_left: Option<u32>,
+   pub rate: f64,
+}
+
+impl AbLoop {
+   /// flushing segment seek that starts the loop
+   pub fn start(&self, pipeline: &Pipeline) -> Result<()> {
+      self.segment_seek(pipeline, SeekFlags::FLUSH)
+   }
+
+   pub fn contains(&self, time: ClockTime) -> bool {
+      time >= self.a && time < self.b
+   }
+
+   /// segment seek to ``seek_to`` that keeps ``b`` as the stop point,
+   /// used when the user seeks while a loop is active
+   pub fn seek_inside(&self, pipeline: &Pipeline, flags: SeekFlags, seek_to: ClockTime) -> Result<()> {
+      pipeline.seek(
+         self.rate,
+         flags | SeekFlags::SEGMENT,
+         SeekType::Set,
+         seek_to,
+         SeekType::Set,
+         self.b,
+      )?;
+      Ok(())
+   }
+
+   /// answers a ``SegmentDone`` message,
+   /// non flushing so the jump back to ``a`` is seamless
+   pub fn on_segment_done(state: &mut Option<AbLoop>, pipeline: &Pipeline) -> Result<()> {
+      let Some(ab) = state.as_mut() else {
+         return Ok(());
+      };
+
+      match ab.repeats_left {
+         // loop finished, carry on playing past b
+         Some(0) => {
+            pipeline.seek(
+               ab.rate,
+               SeekFlags::ACCURATE,
+               SeekType::Set,
+               ab.b,
+               SeekType::None,
+               ClockTime::NONE,
+            )?;
+            *state = None;
+         }
+         _ => {
+            if let Some(left) = ab.repeats_left.as_mut() {
+               *left -= 1;
+            }
+            ab.segment_seek(pipeline, SeekFlags::empty())?;
+         }
+      }
+
+      Ok(())
+   }
+
+   fn segment_seek(&self, pipeline: &Pipeline, flags: SeekFlags) -> Result<()> {
+      pipeline.seek(
+         self.rate,
+         flags | SeekFlags::SEGMENT | SeekFlags::ACCURATE,
+         SeekType::Set,
+         self.a,
+         SeekType::Set,
+         self.b,
+      )?;
+      Ok(())
+   }
+}
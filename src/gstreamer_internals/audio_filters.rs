@@ -0,0 +1,343 @@
+use anyhow::Result;
+use gstreamer::prelude::{Cast, ElementExt, ObjectExt, PadExt, ToSendValue};
+use gstreamer::{Bin, Element, ElementFactory, Object, StructureRef};
+use serde::{Deserialize, Serialize};
+use crate::gstreamer_internals::loudness::{LoudnessMode, LoudnessSettings};
+use crate::gstreamer_internals::visualisation::{relink_when_idle, LEVEL_FLOOR};
+use crate::gstreamer_internals::volume::db_to_gain;
+
+/// centre frequencies of the ``equalizer-10bands`` bands in hz
+pub const EQ_BANDS_HZ: [f64; 10] = [29.0, 59.0, 119.0, 237.0, 474.0, 947.0, 1889.0, 3770.0, 7523.0, 15011.0];
+
+/// gain range ``equalizer-10bands`` accepts per band in dB
+pub const EQ_RANGE_DB: std::ops::RangeInclusive<f64> = -24.0..=12.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EqPreset {
+   #[default]
+   Flat,
+   BassBoost,
+   TrebleBoost,
+   /// cuts rumble and lifts the 1-4khz range dialogue sits in
+   Voice,
+   Loudness,
+   /// whatever the sliders were last set to
+   Custom,
+}
+
+impl EqPreset {
+   pub const ALL: &'static [EqPreset] = &[Self::Flat, Self::BassBoost, Self::TrebleBoost, Self::Voice, Self::Loudness, Self::Custom];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Flat => "Flat",
+         Self::BassBoost => "Bass boost",
+         Self::TrebleBoost => "Treble boost",
+         Self::Voice => "Voice",
+         Self::Loudness => "Loudness",
+         Self::Custom => "Custom",
+      }
+   }
+
+   /// band gains for the preset, ``None`` for ``Custom``
+   pub fn bands(self) -> Option<[f64; 10]> {
+      match self {
+         Self::Flat => Some([0.0; 10]),
+         Self::BassBoost => Some([6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+         Self::TrebleBoost => Some([0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 3.0, 5.0, 6.0]),
+         Self::Voice => Some([-12.0, -8.0, -4.0, -1.0, 0.0, 2.0, 4.0, 4.0, 1.0, -2.0]),
+         Self::Loudness => Some([5.0, 4.0, 2.0, 0.0, -1.0, 0.0, 0.0, 1.0, 3.0, 4.0]),
+         Self::Custom => None,
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum StereoMode {
+   #[default]
+   Normal,
+   /// mid side widening, 0.0 is unchanged and 1.0 doubles the side signal
+   Wide(f32),
+   /// every channel gets the average of all of them
+   Mono,
+}
+
+/// the configurable part of the audio bin, part of the saved settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioFilterSettings {
+   pub eq_enabled: bool,
+   pub eq_preset: EqPreset,
+   /// dB per band, see ``EQ_BANDS_HZ``
+   pub eq_bands: [f64; 10],
+   /// compresses loud parts so quiet dialogue can be turned up without explosions waking anyone
+   pub night_mode: bool,
+   /// linear amplitude the compressor starts at
+   pub night_threshold: f32,
+   /// compressor ratio, 0.25 is 4:1
+   pub night_ratio: f32,
+   pub stereo: StereoMode,
+   pub loudness: LoudnessSettings,
+}
+
+impl Default for AudioFilterSettings {
+   fn default() -> Self {
+      Self {
+         eq_enabled: false,
+         eq_preset: EqPreset::Flat,
+         eq_bands: [0.0; 10],
+         night_mode: false,
+         night_threshold: 0.125,
+         night_ratio: 0.25,
+         stereo: StereoMode::Normal,
+         loudness: LoudnessSettings::default(),
+      }
+   }
+}
+
+impl AudioFilterSettings {
+   /// voice eq plus night mode, for dialogue buried under music and effects
+   pub fn voice_enhancement() -> Self {
+      Self {
+         eq_enabled: true,
+         eq_preset: EqPreset::Voice,
+         eq_bands: EqPreset::Voice.bands().unwrap(),
+         night_mode: true,
+         ..Self::default()
+      }
+   }
+
+   pub fn set_preset(&mut self, preset: EqPreset) {
+      self.eq_preset = preset;
+      if let Some(bands) = preset.bands() {
+         self.eq_bands = bands;
+      }
+   }
+
+   /// ``channels`` by ``channels`` matrix for ``audioconvert``s ``mix-matrix``, empty leaves mixing alone
+   fn mix_matrix(&self, channels: usize) -> Vec<Vec<f32>> {
+      match (self.stereo, channels) {
+         (StereoMode::Normal, _) | (_, 0 | 1) => vec![],
+         (StereoMode::Mono, n) => vec![vec![1.0 / n as f32; n]; n],
+         // l' = l + w/2 (l - r), r' = r + w/2 (r - l)
+         (StereoMode::Wide(width), 2) => {
+            let side = width / 2.0;
+            vec![vec![1.0 + side, -side], vec![-side, 1.0 + side]]
+         }
+         (StereoMode::Wide(_), _) => vec![],
+      }
+   }
+}
+
+/// how much of each new ``level`` reading goes into the averages around ``audioloudnorm``,
+/// a few seconds worth since its output lags the input by its lookahead
+const GAIN_SMOOTHING: f64 = 0.1;
+
+/// the filter elements inside the audio bin,
+/// everything is set through properties so changes apply live, only real time normalization gets relinked
+pub struct AudioFilters {
+   /// ``audioloudnorm`` and the converters after it since it only outputs 192khz f64,
+   /// between a ``level`` on either side to tell the gain it applies, linked in while real time normalization is on
+   loudnorm: Vec<Element>,
+   /// bin and the element the loudnorm elements follow, once the bin is linked
+   loudnorm_slot: Option<(Bin, Element)>,
+   loudnorm_linked: bool,
+   /// smoothed rms dB going into and coming out of ``audioloudnorm``
+   loudnorm_levels: (Option<f64>, Option<f64>),
+   /// pre-scan normalization gain
+   normalize: Element,
+   equalizer: Element,
+   dynamic: Element,
+   downmix: Element,
+   settings: AudioFilterSettings,
+   /// channel count the current ``mix-matrix`` was built for
+   matrix_channels: usize,
+   /// integrated loudness from the pre-scan once it finishes
+   measured_lufs: Option<f64>,
+}
+
+impl AudioFilters {
+   pub fn new(settings: &AudioFilterSettings) -> Result<Self> {
+      let meter = || ElementFactory::make("level").property("interval", 500_000_000u64).property("post-messages", true).build();
+      let loudnorm = vec![
+         meter()?,
+         ElementFactory::make("audioloudnorm").build()?,
+         ElementFactory::make("audioconvert").build()?,
+         ElementFactory::make("audioresample").build()?,
+         meter()?,
+      ];
+
+      let mut filters = Self {
+         loudnorm,
+         loudnorm_slot: None,
+         loudnorm_linked: false,
+         loudnorm_levels: (None, None),
+         normalize: ElementFactory::make("volume").name("normalize").build()?,
+         equalizer: ElementFactory::make("equalizer-10bands").build()?,
+         dynamic: ElementFactory::make("audiodynamic").build()?,
+         downmix: ElementFactory::make("audioconvert").name("downmix").build()?,
+         settings: settings.clone(),
+         matrix_channels: 0,
+         measured_lufs: None,
+      };
+      filters.apply(settings);
+      Ok(filters)
+   }
+
+   /// in link order, sits between the converters and the volume element
+   pub fn elements(&self) -> Vec<&Element> {
+      vec![&self.normalize, &self.equalizer, &self.dynamic, &self.downmix]
+   }
+
+   /// once ``elements`` are linked after ``before`` in ``bin``, lets real time normalization be put in front of them
+   pub fn attach(&mut self, bin: &Bin, before: &Element) -> Result<()> {
+      self.loudnorm_slot = Some((bin.clone(), before.clone()));
+      self.sync_loudnorm()
+   }
+
+   /// links ``audioloudnorm`` in or out to match the mode
+   fn sync_loudnorm(&mut self) -> Result<()> {
+      let wanted = self.settings.loudness.mode == LoudnessMode::RealTime;
+      let Some((bin, before)) = &self.loudnorm_slot else {
+         return Ok(());
+      };
+      if wanted == self.loudnorm_linked {
+         return Ok(());
+      }
+
+      relink_when_idle(bin, before, &self.normalize, &self.loudnorm, wanted)?;
+      self.loudnorm_linked = wanted;
+      self.loudnorm_levels = (None, None);
+      Ok(())
+   }
+
+   /// takes ``level`` messages from the meters around ``audioloudnorm``, false for anyone else's
+   pub fn handle_message(&mut self, src: &Object, structure: &StructureRef) -> bool {
+      let (input, output) = (&self.loudnorm[0], &self.loudnorm[self.loudnorm.len() - 1]);
+      let average = if src == input.upcast_ref::<Object>() {
+         &mut self.loudnorm_levels.0
+      } else if src == output.upcast_ref::<Object>() {
+         &mut self.loudnorm_levels.1
+      } else {
+         return false;
+      };
+
+      let rms = structure
+          .get::<gstreamer::glib::ValueArray>("rms")
+          .map(|values| values.iter().filter_map(|v| v.get::<f64>().ok()).map(|db| db.max(LEVEL_FLOOR)).collect::<Vec<_>>())
+          .unwrap_or_default();
+      if !rms.is_empty() {
+         let db = rms.iter().sum::<f64>() / rms.len() as f64;
+         *average = Some(average.map_or(db, |average| average + (db - average) * GAIN_SMOOTHING));
+      }
+      true
+   }
+
+   pub fn settings(&self) -> &AudioFilterSettings {
+      &self.settings
+   }
+
+   pub fn apply(&mut self, settings: &AudioFilterSettings) {
+      self.settings = settings.clone();
+
+      for (i, gain) in settings.eq_bands.iter().enumerate() {
+         let gain = match settings.eq_enabled {
+            true => gain.clamp(*EQ_RANGE_DB.start(), *EQ_RANGE_DB.end()),
+            false => 0.0,
+         };
+         // all bands at zero puts the equalizer in passthrough
+         self.equalizer.set_property(&format!("band{i}"), gain);
+      }
+
+      self.dynamic.set_property_from_str("mode", "compressor");
+      self.dynamic.set_property_from_str("characteristics", "soft-knee");
+      self.dynamic.set_property("threshold", settings.night_threshold.clamp(0.0, 1.0));
+      self.dynamic.set_property("ratio", match settings.night_mode {
+         true => settings.night_ratio.clamp(0.0, 1.0),
+         false => 1.0,
+      });
+
+      self.loudnorm[1].set_property("loudness-target", settings.loudness.target_lufs);
+      if let Err(err) = self.sync_loudnorm() {
+         println!("Couldn't switch real time normalization {err}");
+      }
+      self.set_measured_loudness(self.measured_lufs);
+
+      self.matrix_channels = 0;
+      self.sync_channels();
+   }
+
+   pub fn set_measured_loudness(&mut self, lufs: Option<f64>) {
+      self.measured_lufs = lufs;
+      self.normalize.set_property("volume", self.applied_gain_db().map_or(1.0, db_to_gain));
+   }
+
+   pub fn measured_loudness(&self) -> Option<f64> {
+      self.measured_lufs
+   }
+
+   /// gain currently applied, ``None`` while off, scanning or before audio has gone through ``audioloudnorm``,
+   /// which doesn't say so itself so it's the difference of the levels either side
+   pub fn applied_gain_db(&self) -> Option<f64> {
+      match self.settings.loudness.mode {
+         LoudnessMode::Off => None,
+         LoudnessMode::RealTime => match self.loudnorm_levels {
+            // silence tells nothing about the gain
+            (Some(input), Some(output)) if input > LEVEL_FLOOR + 10.0 => Some(output - input),
+            _ => None,
+         },
+         LoudnessMode::PreScan => self.measured_lufs.map(|lufs| self.settings.loudness.gain_db(lufs)),
+      }
+   }
+
+   /// rebuilds the mix matrix when the stream's channel count changes,
+   /// called from the backend update since caps only show up once audio flows
+   pub fn sync_channels(&mut self) {
+      let channels = self.downmix
+          .static_pad("sink")
+          .and_then(|pad| pad.current_caps())
+          .and_then(|caps| caps.structure(0).and_then(|s| s.get::<i32>("channels").ok()))
+          .unwrap_or(0) as usize;
+
+      if channels == self.matrix_channels {
+         return;
+      }
+      self.matrix_channels = channels;
+
+      let matrix = self.settings.mix_matrix(channels);
+      let rows = matrix.iter().map(|row| {
+         gstreamer::Array::new(row.iter().map(|v| v.to_send_value())).to_send_value()
+      });
+      self.downmix.set_property("mix-matrix", gstreamer::Array::new(rows));
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn presets_stay_in_range() {
+      for preset in EqPreset::ALL {
+         for gain in preset.bands().unwrap_or([0.0; 10]) {
+            assert!(EQ_RANGE_DB.contains(&gain), "{preset:?} {gain}");
+         }
+      }
+   }
+
+   #[test]
+   fn mix_matrix_shapes() {
+      let mut settings = AudioFilterSettings::default();
+      assert!(settings.mix_matrix(2).is_empty());
+
+      settings.stereo = StereoMode::Mono;
+      let matrix = settings.mix_matrix(6);
+      assert_eq!(matrix.len(), 6);
+      assert!(matrix.iter().all(|row| row.len() == 6));
+
+      settings.stereo = StereoMode::Wide(0.5);
+      assert_eq!(settings.mix_matrix(2).len(), 2);
+      // widening only makes sense for stereo
+      assert!(settings.mix_matrix(6).is_empty());
+   }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use gstreamer::prelude::{ElementExt, GstBinExt, GstBinExtManual, ObjectExt, PadExt};
+use gstreamer::{Caps, Element, ElementFactory, Pipeline};
+
+/// pipeline decoding only the first audio stream of ``uri`` into ``chain``,
+/// the last element of ``chain`` should be a sink with ``sync`` off so it runs as fast as it can
+///
+/// used by the background passes over whole files, loudness and waveform
+pub fn first_audio_stream_pipeline(uri: &str, chain: &[&Element]) -> Result<Pipeline> {
+   gstreamer::init()?;
+
+   let pipeline = Pipeline::new();
+   let decode = ElementFactory::make("uridecodebin")
+       .property("uri", uri)
+       .property("caps", Caps::builder("audio/x-raw").build())
+       .property("expose-all-streams", false)
+       .build()?;
+   let convert = ElementFactory::make("audioconvert").build()?;
+
+   pipeline.add_many([&decode, &convert])?;
+   pipeline.add_many(chain.iter().copied())?;
+   Element::link_many(std::iter::once(&convert).chain(chain.iter().copied()))?;
+
+   // the first audio pad is used, any others are drained so they don't stall the decoder
+   let weak_pipeline = pipeline.downgrade();
+   let weak_convert = convert.downgrade();
+   decode.connect_pad_added(move |_, pad| {
+      let (Some(pipeline), Some(convert)) = (weak_pipeline.upgrade(), weak_convert.upgrade()) else {
+         return;
+      };
+
+      let convert_pad = convert.static_pad("sink").unwrap();
+      if !convert_pad.is_linked() {
+         let _ = pad.link(&convert_pad);
+         return;
+      }
+
+      if let Ok(drain) = ElementFactory::make("fakesink").property("sync", false).build() {
+         let _ = pipeline.add(&drain);
+         let _ = drain.sync_state_with_parent();
+         let _ = pad.link(&drain.static_pad("sink").unwrap());
+      }
+   });
+
+   Ok(pipeline)
+}
@@ -1,12 +1,24 @@
 use std::ops::RangeInclusive;
-use anyhow::Result;
-use gstreamer::{ClockTime, SeekFlags, State};
+use std::path::Path;
+use anyhow::{Context, Result};
+use gstreamer::{ClockTime, Sample, SeekFlags, State};
 use gstreamer_video::VideoInfo;
-use crate::gstreamer_internals::prober::Probe;
+use serde::Serialize;
+use crate::gstreamer_internals::ab_loop::AbLoop;
+use crate::gstreamer_internals::audio_filters::AudioFilterSettings;
+use crate::gstreamer_internals::color_balance::ColorAdjustments;
+use crate::gstreamer_internals::deinterlace::DeinterlaceSettings;
+use crate::gstreamer_internals::prober::{Chapter, Probe};
 use crate::gstreamer_internals::update::FrameUpdate;
+use crate::gstreamer_internals::visualisation::ChannelLevel;
+use crate::gstreamer_internals::waveform::Waveform;
 
 pub trait GstreamerBackendFramework: Sized {
-   fn init(uri: &str) -> Result<Self>;
+   fn init(uri: &str) -> Result<Self> {
+      Self::init_with_options(uri, BackendOptions::default())
+   }
+
+   fn init_with_options(uri: &str, options: BackendOptions) -> Result<Self>;
 
    fn update(&mut self) -> Result<FrameUpdate>;
 
@@ -23,6 +35,13 @@ pub trait GstreamerBackendFramework: Sized {
 
    fn change_playback_speed(&mut self, speed: f64) -> Result<()>;
 
+   /// runs on ``leader``'s clock with a base time matching its own, seeked to where it is now,
+   /// so both show the same frame at the same moment without further seeks until either seeks again
+   fn lock_to(&mut self, leader: &Self) -> Result<()>;
+
+   /// copies ``leader``'s base time, which it picks again every time it resumes, does nothing until ``lock_to``
+   fn follow_base_time(&mut self, leader: &Self);
+
    /////////////////////
    // Seeking Methods //
    /////////////////////
@@ -33,6 +52,18 @@ pub trait GstreamerBackendFramework: Sized {
 
    fn seek_frames(&mut self, frames: i32) -> Result<()>;
 
+   //////////////////
+   // Loop Methods //
+   //////////////////
+
+   /// loops between ``a`` and ``b`` using segment seeks,
+   /// ``repeats`` is how many times to jump back before playing on, ``None`` for forever
+   fn set_ab_loop(&mut self, a: ClockTime, b: ClockTime, repeats: Option<u32>) -> Result<()>;
+
+   fn clear_ab_loop(&mut self) -> Result<()>;
+
+   fn get_ab_loop(&self) -> Option<AbLoop>;
+
    //////////////////////
    // DataInfo Methods //
    //////////////////////
@@ -40,13 +71,11 @@ pub trait GstreamerBackendFramework: Sized {
    fn get_frametime(&self) -> f64;
 
    fn is_playing(&self) -> bool {
-      let _st = self.get_predicted_state();
-      matches!(State::Playing, _st)
+      matches!(self.get_predicted_state(), State::Playing)
    }
 
    fn is_paused(&self) -> bool {
-      let _st = self.get_predicted_state();
-      matches!(State::Paused, _st)
+      matches!(self.get_predicted_state(), State::Paused)
    }
 
    fn get_probe(&self) -> Result<&Probe>;
@@ -61,6 +90,49 @@ pub trait GstreamerBackendFramework: Sized {
 
    fn get_duration(&self) -> Result<ClockTime>;
 
+   fn get_chapters(&self) -> &[Chapter];
+
+   /// polled straight from the pipeline, cheap enough for once a frame but not free
+   fn get_live_stats(&self) -> LiveStats;
+
+   /// writes the frame currently on screen to ``path`` as a png
+   fn screenshot(&self, path: &Path) -> Result<()>;
+
+   fn current_chapter(&self) -> Option<usize> {
+      Chapter::index_at(self.get_chapters(), self.timecode())
+   }
+
+   /////////////////////
+   // Chapter Methods //
+   /////////////////////
+
+   fn seek_chapter(&mut self, index: usize) -> Result<()> {
+      let start = self.get_chapters().get(index).context("No chapter at index")?.start;
+      self.seek_time(SeekFlags::FLUSH | SeekFlags::ACCURATE, start)?;
+      self.queue_frame_update();
+      Ok(())
+   }
+
+   /// goes to the start of the next chapter, does nothing on the last one or without chapters
+   fn next_chapter(&mut self) -> Result<()> {
+      let next = self.current_chapter().map_or(0, |i| i + 1);
+      if next >= self.get_chapters().len() {
+         return Ok(());
+      }
+      self.seek_chapter(next)
+   }
+
+   /// goes to the start of the current chapter, or the one before if already near its start
+   fn previous_chapter(&mut self) -> Result<()> {
+      let Some(current) = self.current_chapter() else { return Ok(()) };
+      let into_chapter = self.timecode().saturating_sub(self.get_chapters()[current].start);
+
+      match into_chapter < ClockTime::from_seconds(3) {
+         true => self.seek_chapter(current.saturating_sub(1)),
+         false => self.seek_chapter(current),
+      }
+   }
+
    ////////////////////
    // Stream Methods //
    ////////////////////
@@ -78,9 +150,71 @@ pub trait GstreamerBackendFramework: Sized {
    fn list_audio_devices(&self) -> Result<Vec<(String, String)>>;
    fn get_current_audio_device(&self) -> Option<String>;
 
+   /// linear gain, 1.0 leaves the audio as it is
    fn get_current_volume(&self) -> f64;
+   /// ``0.0..=max`` where max is the boost given to ``set_max_volume``
    fn get_volume_range(&self) -> RangeInclusive<f64>;
+   /// clamped to ``get_volume_range``
    fn set_volume(&mut self, to: f64) -> Result<()>;
+   fn set_max_volume(&mut self, max: f64) -> Result<()>;
+
+   fn is_muted(&self) -> bool;
+   /// mutes without touching the volume so unmuting restores it
+   fn set_muted(&mut self, muted: bool) -> Result<()>;
+
+   fn get_audio_filters(&self) -> &AudioFilterSettings;
+   /// applies live, the pipeline isn't rebuilt
+   fn set_audio_filters(&mut self, settings: &AudioFilterSettings) -> Result<()>;
+
+   fn get_loudness_state(&self) -> LoudnessState;
+
+   ///////////////////////////
+   // Visualisation Methods //
+   ///////////////////////////
+
+   /// latest ``spectrum`` magnitudes in dB, lowest band first
+   fn get_spectrum(&self) -> &[f32];
+
+   /// the most recent mono samples, oldest first
+   fn get_audio_samples(&self) -> Vec<f32>;
+
+   /// latest peak and rms of each channel, empty until audio plays
+   fn get_levels(&self) -> &[ChannelLevel];
+
+   /// links in the analysis behind ``get_spectrum``, ``get_audio_samples`` and ``get_levels``,
+   /// off to start with as it costs every file, they stay empty while it's off
+   fn set_audio_analysis(&mut self, enabled: bool) -> Result<()>;
+
+   /// starts the background waveform pass for this file unless it's cached or already running
+   fn request_waveform(&mut self);
+
+   /// ``None`` until ``request_waveform`` has finished
+   fn get_waveform(&self) -> Option<&Waveform>;
+
+   /// ``(factory name, long name)`` of every installed visualisation element
+   fn list_vis_plugins(&self) -> Vec<(String, String)>;
+
+   /// renders ``factory`` into the video sink when there's no video, ``None`` turns visualisation off
+   fn set_vis_plugin(&mut self, factory: Option<&str>) -> Result<()>;
+
+   /// embedded cover art of the current audio track, an encoded image
+   fn get_cover_art(&self) -> Option<Sample>;
+
+   ///////////////////
+   // Video Methods //
+   ///////////////////
+
+   /// adjusts the decoded frames with playbin's ``GstColorBalance``, for when they aren't post processed on the gpu,
+   /// it has no gamma channel so that's ignored, applied once the video chain exists if it doesn't yet
+   fn set_color_balance(&mut self, adjustments: &ColorAdjustments) -> Result<()>;
+
+   /// changes the mode and method of the ``deinterlace`` element, which only exists if ``BackendOptions::deinterlace``
+   /// needed it, errors if it doesn't as playbin only takes a new video filter when the file is reopened
+   fn set_deinterlace(&mut self, settings: &DeinterlaceSettings) -> Result<()>;
+
+   /// frames bigger than ``max_size`` are scaled down before they're handed over, saving conversion and upload
+   /// for small views, ``None`` takes them at their own size
+   fn set_max_video_size(&mut self, max_size: Option<(u32, u32)>) -> Result<()>;
 
    //////////////////////
    // Subtitle Methods //
@@ -92,6 +226,65 @@ pub trait GstreamerBackendFramework: Sized {
 
 }
 
+#[derive(Debug, Clone)]
+pub struct BackendOptions {
+   /// how long the discoverer gets before giving up, slow network sources may need more
+   pub probe_timeout: ClockTime,
+   /// reuse probe results from the on disk cache while a fresh one runs in the background
+   pub use_probe_cache: bool,
+   /// linear gain applied once the pipeline is built
+   pub volume: f64,
+   pub muted: bool,
+   /// highest gain ``set_volume`` allows, above 1.0 is boost
+   pub max_volume: f64,
+   /// uses the sinks own volume through ``StreamVolume`` when it has one instead of the ``volume`` element
+   pub hardware_volume: bool,
+   pub audio_filters: AudioFilterSettings,
+   /// the ``deinterlace`` element goes in as playbin's video filter when this uses a cpu method
+   pub deinterlace: DeinterlaceSettings,
+}
+
+impl Default for BackendOptions {
+   fn default() -> Self {
+      Self {
+         probe_timeout: ClockTime::from_seconds(5),
+         use_probe_cache: true,
+         volume: 1.0,
+         muted: false,
+         max_volume: 2.0,
+         hardware_volume: false,
+         audio_filters: AudioFilterSettings::default(),
+         deinterlace: DeinterlaceSettings::default(),
+      }
+   }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LiveStats {
+   /// ``(element name, factory name)`` of every decoder inside the playbin
+   pub decoders: Vec<(String, String)>,
+   pub video_caps: Option<String>,
+   pub audio_caps: Option<String>,
+   pub rendered_frames: Option<u64>,
+   pub dropped_frames: Option<u64>,
+   /// buffering percentage, only meaningful for network sources
+   pub buffer_percent: Option<i32>,
+   /// of the last frame, ``progressive`` once deinterlaced on the cpu
+   pub interlace_mode: Option<String>,
+   /// ``(mode, method)`` of the ``deinterlace`` element, ``None`` without one
+   pub deinterlacer: Option<(String, String)>,
+}
+
+/// progress of loudness normalization for the ui
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoudnessState {
+   /// pre-scan still decoding the file
+   pub scanning: bool,
+   pub measured_lufs: Option<f64>,
+   /// gain being applied, from the pre-scan or measured around ``audioloudnorm`` in real time mode
+   pub gain_db: Option<f64>,
+}
+
 pub struct PlayFlags;
 impl PlayFlags {
    pub const VIDEO: u32 = 1 << 0;
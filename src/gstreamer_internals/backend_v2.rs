@@ -1,26 +1,40 @@
 use crate::fraction_to_f64;
-use crate::gstreamer_internals::backend_framework::GstreamerBackendFramework;
-use crate::gstreamer_internals::prober::Probe;
+use crate::gstreamer_internals::ab_loop::AbLoop;
+use crate::gstreamer_internals::audio_filters::{AudioFilterSettings, AudioFilters};
+use crate::gstreamer_internals::backend_framework::{BackendOptions, GstreamerBackendFramework, LiveStats, LoudnessState, PlayFlags};
+use crate::gstreamer_internals::color_balance::ColorAdjustments;
+use crate::gstreamer_internals::deinterlace::DeinterlaceSettings;
+use crate::gstreamer_internals::loudness::{LoudnessCache, LoudnessMode};
+use crate::gstreamer_internals::probe_cache::ProbeCache;
+use crate::gstreamer_internals::prober::{Chapter, Probe};
 use crate::gstreamer_internals::update::FrameUpdate;
+use crate::gstreamer_internals::visualisation::{AudioAnalysis, ChannelLevel};
+use crate::gstreamer_internals::waveform::{Waveform, WaveformCache};
 use anyhow::{bail, Context, Result};
 use crossbeam_channel::Receiver;
 use gstreamer::ffi::GstObject;
 use gstreamer::glib::gobject_ffi::{g_object_get, g_object_set, GObject};
 use gstreamer::glib::translate::ToGlibPtr;
 use gstreamer::glib::ParamFlags;
-use gstreamer::prelude::{Cast, ElementExt, ElementExtManual, GstBinExtManual, GstObjectExt, IsA, ObjectExt};
-use gstreamer::{Bin, Caps, ClockTime, Element, ElementFactory, FlowSuccess, Object, Pipeline, SeekFlags, SeekType, State};
+use gstreamer::prelude::{Cast, ElementExt, ElementExtManual, GstBinExt, GstBinExtManual, GstObjectExt, IsA, ObjectExt, PadExt, PipelineExt, ClockExt};
+use gstreamer::{Bin, Caps, ClockTime, Element, ElementFactory, ElementFactoryType, FlowSuccess, Format, Message, MessageView, Object, Pipeline, Rank, Sample, SeekFlags, SeekType, State, Structure, TagList};
 use gstreamer_app::AppSink;
+use gstreamer_audio::prelude::StreamVolumeExt;
+use gstreamer_audio::{StreamVolume, StreamVolumeFormat};
 use gstreamer_video::glib::Value;
-use gstreamer_video::VideoInfo;
+use gstreamer_video::prelude::ColorBalanceExt;
+use gstreamer_video::{ColorBalance, VideoInfo};
 use std::ffi::CString;
 use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 use std::time::Duration;
 
 pub struct BackendV2 {
    pipeline: Pipeline,
    update_receiver: Receiver<(FrameUpdate, VideoInfo)>,
+   bus_receiver: Receiver<Message>,
 
    probe: Result<Probe>,
    probe_future: Option<JoinHandle<Result<Probe>>>,
@@ -36,8 +50,38 @@ pub struct BackendV2 {
 
    volume: Element,
    current_volume: f64,
+   max_volume: f64,
+   muted: bool,
+   hardware_volume: bool,
    audio_sink: Element,
    current_audio_device: Option<String>,
+   audio_filters: AudioFilters,
+   loudness_scan: Option<JoinHandle<Result<f64>>>,
+   audio_analysis: AudioAnalysis,
+   waveform: Option<Waveform>,
+   waveform_scan: Option<JoinHandle<Result<Waveform>>>,
+
+   /// waits in here until playbin has built its colour balance
+   pending_color_balance: Option<ColorAdjustments>,
+
+   /// playbin's video filter, only made when the options asked for cpu deinterlacing
+   deinterlacer: Option<Element>,
+   deinterlace: DeinterlaceSettings,
+
+   /// frames are scaled down to fit this before they reach the appsink
+   max_video_size: Option<(u32, u32)>,
+   /// the appsink also takes deep colour formats
+   deep_video: bool,
+
+   /// embedded image of the current audio track, looked for whenever tags arrive until found
+   cover_art: Option<Sample>,
+
+   /// running time of the leader where this pipeline's segment starts, once locked to one
+   clock_offset: Option<ClockTime>,
+
+   ab_loop: Arc<Mutex<Option<AbLoop>>>,
+
+   chapters: Vec<Chapter>,
 }
 
 impl Drop for BackendV2 {
@@ -53,7 +97,58 @@ impl BackendV2 {
       inny.0
    }
 
-   fn make_audio_sink(device: Option<&str>) -> Result<(Bin, Element, Element)> {
+   /// 8 bit srgb, and for ``deep`` sources whichever of the formats the display texture uploads directly is closest,
+   /// the colorimetry is left open there so videoconvert keeps the stream's own and pq or hlg reach the display path intact,
+   /// 8 bit sources aren't offered those or videoconvert widens them to P010 for nothing,
+   /// ``max_size`` has playbin's videoscale shrink the frames to fit inside it
+   fn video_caps(max_size: Option<(u32, u32)>, deep: bool) -> Caps {
+      let mut sdr = Structure::builder("video/x-raw")
+          .field("format", "RGBA")
+          .field("colorimetry", "sRGB")
+          .build();
+      let mut deep = deep.then(|| {
+         Structure::builder("video/x-raw")
+             .field("format", gstreamer::List::new(["P010_10LE", "P016_LE", "RGB10A2_LE", "RGBA64_LE"]))
+             .build()
+      });
+
+      if let Some((width, height)) = max_size {
+         for structure in std::iter::once(&mut sdr).chain(deep.as_mut()) {
+            structure.set("width", gstreamer::IntRange::new(1, width.max(1) as i32));
+            structure.set("height", gstreamer::IntRange::new(1, height.max(1) as i32));
+         }
+      }
+
+      let mut caps = Caps::builder_full().structure(sdr);
+      if let Some(deep) = deep {
+         caps = caps.structure(deep);
+      }
+      caps.build()
+   }
+
+   /// hands the appsink caps for the current size limit and depth and has upstream renegotiate
+   fn apply_video_caps(&self) -> Result<()> {
+      let sink = self.pipeline.property::<Option<Element>>("video-sink").context("No video sink")?;
+      sink.set_property("caps", &Self::video_caps(self.max_video_size, self.deep_video));
+
+      // upstream only looks at the new caps once asked to renegotiate
+      if let Some(pad) = sink.static_pad("sink") {
+         pad.push_event(gstreamer::event::Reconfigure::new());
+      }
+      Ok(())
+   }
+
+   /// deep colour caps once the probe says the source needs them
+   fn sync_deep_video(&mut self) -> Result<()> {
+      let deep = self.probe.as_ref().is_ok_and(Probe::has_deep_video);
+      if deep != self.deep_video {
+         self.deep_video = deep;
+         self.apply_video_caps()?;
+      }
+      Ok(())
+   }
+
+   fn make_audio_sink(device: Option<&str>, filter_settings: &AudioFilterSettings) -> Result<(Bin, Element, Element, AudioFilters, AudioAnalysis)> {
       // Create a new Bin
       let bin = Bin::new();
 
@@ -61,6 +156,9 @@ impl BackendV2 {
       let audio_convert = ElementFactory::make("audioconvert").build()?;
       let audio_resample = ElementFactory::make("audioresample").build()?;
       let volume = ElementFactory::make("volume").build()?;
+      let mut filters = AudioFilters::new(filter_settings)?;
+      // the analysis pins the format to f32, this lets the sink pick again
+      let sink_convert = ElementFactory::make("audioconvert").build()?;
 
       #[cfg(target_os = "windows")]
       let audio_sink = ElementFactory::make("wasapisink")
@@ -80,11 +178,22 @@ impl BackendV2 {
       probe_props(&audio_sink);
       probe_props(&volume);
 
+      let chain = [&audio_convert, &audio_resample]
+          .into_iter()
+          .chain(filters.elements())
+          .chain([&volume, &sink_convert, &audio_sink])
+          .collect::<Vec<_>>();
+
       // Add elements to the Bin
-      bin.add_many(&[&audio_convert, &audio_resample, &volume, &audio_sink])?;
+      bin.add_many(&chain)?;
 
       // Link elements together
-      Element::link_many(&[&audio_convert, &audio_resample, &volume, &audio_sink])?;
+      Element::link_many(&chain)?;
+      filters.attach(&bin, &audio_resample)?;
+
+      // goes after the filters so the visuals show what's heard, linked in once something shows it
+      let before_volume = chain[chain.len() - 4];
+      let analysis = AudioAnalysis::new(&bin, before_volume, &volume)?;
 
       // Add a ghost pad to the Bin to expose the audio_convert's sink pad
       let ghost_pad = gstreamer::GhostPad::with_target(
@@ -92,12 +201,207 @@ impl BackendV2 {
       )?;
       bin.add_pad(&ghost_pad)?;
 
-      Ok((bin, volume, audio_sink))
+      Ok((bin, volume, audio_sink, filters, analysis))
+   }
+
+   /// the appsink and the ``deinterlace`` filter when the settings need one, set on ``pipeline``,
+   /// playbin's own deinterlacer is turned off so the settings pick the only one frames go through
+   fn make_video_sink(pipeline: &Pipeline, deinterlace: &DeinterlaceSettings) -> Result<(AppSink, Option<Element>)> {
+      let appsink = ElementFactory::make("appsink")
+          .name("videosink")
+          .build()?
+          .dynamic_cast::<AppSink>()
+          .unwrap();
+
+      // deep colour is added once the probe has looked at the source
+      appsink.set_property("caps", &Self::video_caps(None, false));
+      pipeline.set_property("video-sink", &appsink);
+
+      // on by default, it would deinterlace ahead of the appsink whatever the settings say
+      set_playflag(pipeline, false, PlayFlags::DEINTERLACE)?;
+
+      // playbin converts to something the filter takes first, which is 8 bit, so it's left out unless needed
+      let deinterlacer = match deinterlace.needs_element() {
+         true => match ElementFactory::make("deinterlace").build() {
+            Ok(element) => {
+               pipeline.set_property("video-filter", &element);
+               Some(element)
+            }
+            Err(err) => {
+               println!("No deinterlace element {err}");
+               None
+            }
+         },
+         false => None,
+      };
+
+      Ok((appsink, deinterlacer))
+   }
+
+   /// pushes volume and mute to the sinks own volume when asked for and it has one,
+   /// otherwise to the ``volume`` element
+   fn apply_volume(&self) {
+      match self.hardware_volume.then(|| self.stream_volume()).flatten() {
+         // sinks clamp at unity, any boost past it is left to the software element
+         Some(stream_volume) => {
+            stream_volume.set_volume(StreamVolumeFormat::Linear, self.current_volume.min(1.0));
+            stream_volume.set_mute(self.muted);
+            self.volume.set_property("volume", self.current_volume.max(1.0));
+            self.volume.set_property("mute", false);
+         }
+         None => {
+            self.volume.set_property("volume", self.current_volume);
+            self.volume.set_property("mute", self.muted);
+         }
+      }
+   }
+
+   /// the real sink if it implements ``StreamVolume``,
+   /// ``autoaudiosink`` only creates its child once the pipeline leaves null
+   fn stream_volume(&self) -> Option<StreamVolume> {
+      if let Ok(stream_volume) = self.audio_sink.clone().dynamic_cast::<StreamVolume>() {
+         return Some(stream_volume);
+      }
+
+      self.audio_sink
+          .downcast_ref::<Bin>()?
+          .iterate_recurse()
+          .into_iter()
+          .flatten()
+          .find_map(|element| element.dynamic_cast::<StreamVolume>().ok())
+   }
+
+   /// starts the pre-scan when it's wanted and hasn't run yet for this file
+   fn start_loudness_scan(&mut self) {
+      let wanted = self.audio_filters.settings().loudness.mode == LoudnessMode::PreScan;
+      if wanted && self.loudness_scan.is_none() && self.audio_filters.measured_loudness().is_none() {
+         let uri = self.pipeline.property::<String>("uri");
+         self.loudness_scan = Some(LoudnessCache::measure(&uri));
+      }
+   }
+
+   fn poll_loudness_scan(&mut self) {
+      if !self.loudness_scan.as_ref().is_some_and(|scan| scan.is_finished()) {
+         return;
+      }
+
+      match self.loudness_scan.take().unwrap().join().unwrap() {
+         Ok(lufs) => {
+            self.audio_filters.set_measured_loudness(Some(lufs));
+            println!("Measured {lufs:.1} LUFS, normalization gain {:?} dB", self.audio_filters.applied_gain_db());
+         }
+         Err(err) => println!("Loudness scan failed {err}"),
+      }
+   }
+
+   /// pushes ``pending_color_balance`` to playbin, the channels only show up once the video chain is built
+   fn apply_color_balance(&mut self) {
+      let Some(adjustments) = self.pending_color_balance else {
+         return;
+      };
+
+      let Some(balance) = self.pipeline.dynamic_cast_ref::<ColorBalance>() else {
+         self.pending_color_balance = None;
+         return;
+      };
+
+      let channels = balance.list_channels();
+      for channel in &channels {
+         if let Some(value) = adjustments.channel_value(&channel.label(), channel.min_value(), channel.max_value()) {
+            balance.set_value(channel, value);
+         }
+      }
+
+      if !channels.is_empty() {
+         self.pending_color_balance = None;
+      }
+   }
+
+   fn poll_waveform_scan(&mut self) {
+      if !self.waveform_scan.as_ref().is_some_and(|scan| scan.is_finished()) {
+         return;
+      }
+
+      match self.waveform_scan.take().unwrap().join().unwrap() {
+         Ok(waveform) => self.waveform = Some(waveform),
+         Err(err) => println!("Waveform scan failed {err}"),
+      }
+   }
+
+   /// handles messages forwarded from the bus thread
+   fn handle_bus_messages(&mut self) {
+      while let Ok(msg) = self.bus_receiver.try_recv() {
+         match msg.view() {
+            MessageView::Toc(toc_msg) => {
+               let (toc, _updated) = toc_msg.toc();
+               let chapters = Chapter::from_toc(&toc);
+               if !chapters.is_empty() {
+                  self.chapters = chapters;
+               }
+            }
+            MessageView::Element(element) => {
+               if let Some(structure) = element.structure() {
+                  let from_filters = element.src().is_some_and(|src| self.audio_filters.handle_message(src, structure));
+                  if !from_filters {
+                     self.audio_analysis.handle_message(structure);
+                  }
+               }
+            }
+            MessageView::Tag(..) if self.cover_art.is_none() => {
+               self.cover_art = self.query_cover_art();
+            }
+            _ => (),
+         }
+      }
+   }
+
+   /// the current audio track's image tag, asked for again only when new tags arrive
+   fn query_cover_art(&self) -> Option<Sample> {
+      let track = self.pipeline.property::<i32>("current-audio").max(0);
+      let tags = self.pipeline.emit_by_name::<Option<TagList>>("get-audio-tags", &[&track])?;
+
+      tags.get::<gstreamer::tags::Image>()
+          .or_else(|| tags.get::<gstreamer::tags::PreviewImage>())
+          .map(|image| image.get())
+   }
+
+   fn query_chapters(&self) -> Vec<Chapter> {
+      let mut query = gstreamer::query::Toc::new();
+      if self.pipeline.query(&mut query) {
+         if let (Some(toc), _) = query.result() {
+            return Chapter::from_toc(&toc);
+         }
+      }
+      vec![]
+   }
+
+   /// seeks while keeping an active A-B loop alive,
+   /// seeking outside the loop cancels it
+   fn seek_keeping_loop(&mut self, seek_flags: SeekFlags, seek_to: ClockTime) -> Result<()> {
+      let mut ab_loop = self.ab_loop.lock().unwrap();
+
+      if let Some(ab) = *ab_loop {
+         if ab.contains(seek_to) {
+            return ab.seek_inside(&self.pipeline, seek_flags, seek_to);
+         }
+         *ab_loop = None;
+      }
+
+      self.pipeline.seek(
+         self.playback_speed,
+         seek_flags,
+         SeekType::Set,
+         seek_to,
+         SeekType::None,
+         ClockTime::NONE,
+      )?;
+
+      Ok(())
    }
 }
 
 impl GstreamerBackendFramework for BackendV2 {
-   fn init(uri: &str) -> Result<Self> {
+   fn init_with_options(uri: &str, options: BackendOptions) -> Result<Self> {
       gstreamer::init()?;
 
       let pipeline: Pipeline = ElementFactory::make("playbin").build()?.dynamic_cast().unwrap();
@@ -108,25 +412,11 @@ impl GstreamerBackendFramework for BackendV2 {
       pipeline.set_property("uri", uri);
 
       // video sink TODO hardware acc
-      let appsink = ElementFactory::make("appsink")
-          .name("videosink")
-          .build()?
-          .dynamic_cast::<AppSink>()
-          .unwrap();
-
-
-      let caps = Caps::builder("video/x-raw")
-          .field("format", &"RGBA")
-          .field("colorimetry", &"sRGB")
-          .build();
-
-      appsink.set_property("caps", &caps);
-      pipeline.set_property("video-sink", &appsink);
-
+      let (appsink, deinterlacer) = Self::make_video_sink(&pipeline, &options.deinterlace)?;
 
       // audio sink
 
-      let (audio_bin, volume, audio_sink) = Self::make_audio_sink(None)?;
+      let (audio_bin, volume, audio_sink, audio_filters, audio_analysis) = Self::make_audio_sink(None, &options.audio_filters)?;
       pipeline.set_property("audio-sink", &audio_bin);
 
       // updater
@@ -154,13 +444,32 @@ impl GstreamerBackendFramework for BackendV2 {
       );
 
       // debug info
+      let ab_loop = Arc::new(Mutex::new(None::<AbLoop>));
+
+      let (bus_sender, bus_receiver) = crossbeam_channel::unbounded::<Message>();
+
       let bus = pipeline.bus().unwrap();
+      let weak_pipeline = pipeline.downgrade();
+      let bus_ab_loop = Arc::clone(&ab_loop);
       std::thread::spawn(move || {
          for msg in bus.iter_timed(ClockTime::NONE) {
-            use gstreamer::MessageView;
-
             match msg.view() {
                MessageView::Eos(..) => break,
+               MessageView::SegmentDone(..) => {
+                  // answered here rather than in update so the loop point doesn't wait on the ui
+                  if let Some(pipeline) = weak_pipeline.upgrade() {
+                     let mut state = bus_ab_loop.lock().unwrap();
+                     if let Err(err) = AbLoop::on_segment_done(&mut state, &pipeline) {
+                        println!("Failed to loop segment {err}");
+                     }
+                  }
+               }
+               MessageView::Toc(..) | MessageView::Tag(..) => {
+                  let _ = bus_sender.send(msg.clone());
+               }
+               MessageView::Element(element) if element.structure().is_some_and(|s| ["spectrum", "level"].contains(&s.name().as_str())) => {
+                  let _ = bus_sender.send(msg.clone());
+               }
                MessageView::Error(err) => {
                   println!(
                      "Error from {:?}: {} ({:?})",
@@ -176,14 +485,22 @@ impl GstreamerBackendFramework for BackendV2 {
          println!("Closing message bus for gstreamer backend");
       });
 
-      let probe_future = Some(Probe::from_uri_future(uri));
+      let (cached_probe, probe_future) = match options.use_probe_cache {
+         true => ProbeCache::probe(uri, options.probe_timeout),
+         false => {
+            let (uri, timeout) = (uri.to_string(), options.probe_timeout);
+            (None, std::thread::spawn(move || Probe::from_uri_with_timeout(&uri, timeout)))
+         }
+      };
+      let chapters = cached_probe.as_ref().map(|p| p.chapters.clone()).unwrap_or_default();
 
 
       let mut this = Self {
          pipeline,
          update_receiver,
-         probe: Err(anyhow::format_err!("Not initialized yet")),
-         probe_future,
+         bus_receiver,
+         probe: cached_probe.ok_or_else(|| anyhow::format_err!("Not initialized yet")),
+         probe_future: Some(probe_future),
          latest_info: None,
          latest_timecode: ClockTime::ZERO,
          target_state: State::Null,
@@ -194,13 +511,39 @@ impl GstreamerBackendFramework for BackendV2 {
          },
          playback_speed: 1.0,
          volume,
-         current_volume: 2.5,
+         current_volume: options.volume,
+         max_volume: options.max_volume,
+         muted: options.muted,
+         hardware_volume: options.hardware_volume,
          audio_sink,
          current_audio_device: None,
+         audio_filters,
+         loudness_scan: None,
+         audio_analysis,
+         waveform: None,
+         waveform_scan: None,
+         pending_color_balance: None,
+         deinterlacer,
+         deinterlace: options.deinterlace,
+         max_video_size: None,
+         deep_video: false,
+         cover_art: None,
+         clock_offset: None,
+         ab_loop,
+         chapters,
       };
 
+      // a cached probe settles the caps before the first negotiation
+      this.sync_deep_video()?;
+
       // ensures it starts in paused state
       this.stop()?;
+      this.set_max_volume(options.max_volume)?;
+      this.apply_volume();
+      this.start_loudness_scan();
+      if this.deinterlacer.is_some() {
+         this.set_deinterlace(&options.deinterlace)?;
+      }
 
       Ok(this)
    }
@@ -213,11 +556,30 @@ impl GstreamerBackendFramework for BackendV2 {
          }
          if check {
             let fut = self.probe_future.take().unwrap();
-            let probe_res = fut.join().unwrap();
-            self.probe = probe_res;
+            match fut.join().unwrap() {
+               Ok(probe) => self.probe = Ok(probe),
+               // keeps the cached probe if the refresh failed
+               Err(err) if self.probe.is_ok() => println!("Probe refresh failed {err}"),
+               Err(err) => self.probe = Err(err),
+            }
+
+            // the toc message can arrive before anyone listens, so ask again and fall back on the probe
+            if self.chapters.is_empty() {
+               self.chapters = self.query_chapters();
+            }
+            if let (true, Ok(probe)) = (self.chapters.is_empty(), &self.probe) {
+               self.chapters = probe.chapters.clone();
+            }
+            self.sync_deep_video()?;
          }
       }
 
+      self.handle_bus_messages();
+      self.audio_filters.sync_channels();
+      self.poll_loudness_scan();
+      self.poll_waveform_scan();
+      self.apply_color_balance();
+
       match self.frame_queue_info.queued {
          true => {
             match self.frame_queue_info.in_progress {
@@ -298,6 +660,16 @@ impl GstreamerBackendFramework for BackendV2 {
    fn change_playback_speed(&mut self, speed: f64) -> Result<()> {
       let cp = self.latest_timecode;
       self.playback_speed = speed;
+
+      let mut ab_loop = self.ab_loop.lock().unwrap();
+      if let Some(ab) = ab_loop.as_mut() {
+         ab.rate = speed;
+         if ab.contains(cp) {
+            return ab.seek_inside(&self.pipeline, SeekFlags::FLUSH, cp);
+         }
+      }
+      drop(ab_loop);
+
       self.pipeline.seek(
          speed,
          SeekFlags::FLUSH,
@@ -309,33 +681,52 @@ impl GstreamerBackendFramework for BackendV2 {
       Ok(())
    }
 
+   fn lock_to(&mut self, leader: &Self) -> Result<()> {
+      let clock = leader.pipeline.pipeline_clock();
+      if self.pipeline.clock().as_ref() != Some(&clock) {
+         self.pipeline.use_clock(Some(&clock));
+      }
+      // the base time is handed over from the leader instead of picked on every state change
+      self.pipeline.set_start_time(ClockTime::NONE);
+
+      // where the leader is and its running time there, taken together so the pair agrees
+      let running_time = match leader.pipeline.current_state() {
+         State::Playing => {
+            let now = clock.time().context("Leader clock has no time")?;
+            now.saturating_sub(leader.pipeline.base_time().unwrap_or(now))
+         }
+         _ => leader.pipeline.start_time().unwrap_or(ClockTime::ZERO),
+      };
+      let position = leader.pipeline.query_position::<ClockTime>().context("Leader has no position")?;
+
+      // after a flushing seek this pipeline's running time starts from 0 at ``position``
+      self.seek_time(SeekFlags::FLUSH | SeekFlags::ACCURATE, position)?;
+      self.clock_offset = Some(running_time);
+      self.follow_base_time(leader);
+      Ok(())
+   }
+
+   fn follow_base_time(&mut self, leader: &Self) {
+      let (Some(offset), Some(base_time)) = (self.clock_offset, leader.pipeline.base_time()) else { return };
+      if self.pipeline.base_time() != Some(base_time + offset) {
+         self.pipeline.set_base_time(base_time + offset);
+      }
+   }
+
    /////////////////////
    // Seeking Methods //
    /////////////////////
 
    fn seek_time(&mut self, seek_flags: SeekFlags, seek_to: ClockTime) -> Result<()> {
-      self.pipeline.seek(
-         self.playback_speed,
-         seek_flags,
-         SeekType::Set,
-         seek_to,
-         SeekType::None,
-         ClockTime::NONE,
-      )?;
-
-      Ok(())
+      self.seek_keeping_loop(seek_flags, seek_to)
    }
 
    fn seek_timeline(&mut self, seek_to: ClockTime, accurate: bool) -> Result<()> {
       // self.pipeline.seek_simple(seek_flags, seek_to)?;
       if !self.frame_queue_info.in_progress {
-         self.pipeline.seek(
-            self.playback_speed,
+         self.seek_keeping_loop(
             if accurate { SeekFlags::FLUSH } else { SeekFlags::FLUSH | SeekFlags::KEY_UNIT },
-            SeekType::Set,
             seek_to,
-            SeekType::None,
-            ClockTime::NONE,
          )?;
       }
 
@@ -378,6 +769,40 @@ impl GstreamerBackendFramework for BackendV2 {
       }
    }
 
+   //////////////////
+   // Loop Methods //
+   //////////////////
+
+   fn set_ab_loop(&mut self, a: ClockTime, b: ClockTime, repeats: Option<u32>) -> Result<()> {
+      if a >= b {
+         bail!("Loop point A must come before B");
+      }
+
+      let ab = AbLoop {
+         a,
+         b,
+         repeats_left: repeats,
+         rate: self.playback_speed,
+      };
+      ab.start(&self.pipeline)?;
+      *self.ab_loop.lock().unwrap() = Some(ab);
+
+      self.queue_frame_update();
+      Ok(())
+   }
+
+   fn clear_ab_loop(&mut self) -> Result<()> {
+      if self.ab_loop.lock().unwrap().take().is_some() {
+         // drops the segment stop point, otherwise playback halts at b
+         self.seek_time(SeekFlags::FLUSH | SeekFlags::ACCURATE, self.latest_timecode)?;
+      }
+      Ok(())
+   }
+
+   fn get_ab_loop(&self) -> Option<AbLoop> {
+      *self.ab_loop.lock().unwrap()
+   }
+
    //////////////////////
    // DataInfo Methods //
    //////////////////////
@@ -427,6 +852,61 @@ impl GstreamerBackendFramework for BackendV2 {
       Ok(duration)
    }
 
+   fn get_chapters(&self) -> &[Chapter] {
+      &self.chapters
+   }
+
+   fn get_live_stats(&self) -> LiveStats {
+      let decoders = self.pipeline
+          .iterate_recurse()
+          .into_iter()
+          .flatten()
+          .filter_map(|element| {
+             let factory = element.factory()?;
+             factory.klass().contains("Decoder").then(|| (element.name().to_string(), factory.name().to_string()))
+          })
+          .collect();
+
+      let sink_caps = |sink: &Element| {
+         sink.static_pad("sink")
+             .and_then(|pad| pad.current_caps())
+             .map(|caps| caps.to_string())
+      };
+
+      let video_sink = self.pipeline.property::<Option<Element>>("video-sink");
+      let stats = video_sink.as_ref().map(|sink| sink.property::<Structure>("stats"));
+
+      let mut buffering = gstreamer::query::Buffering::new(Format::Percent);
+      let buffer_percent = self.pipeline.query(&mut buffering).then(|| buffering.percent().1);
+
+      LiveStats {
+         decoders,
+         video_caps: video_sink.as_ref().and_then(sink_caps),
+         audio_caps: sink_caps(&self.audio_sink),
+         rendered_frames: stats.as_ref().and_then(|s| s.get::<u64>("rendered").ok()),
+         dropped_frames: stats.as_ref().and_then(|s| s.get::<u64>("dropped").ok()),
+         buffer_percent,
+         interlace_mode: self.latest_info.as_ref().map(|info| format!("{:?}", info.interlace_mode())),
+         deinterlacer: self.deinterlacer.as_ref().map(|_| {
+            let (mode, method) = self.deinterlace.element_properties();
+            (mode.to_string(), method.unwrap_or("none").to_string())
+         }),
+      }
+   }
+
+   fn screenshot(&self, path: &Path) -> Result<()> {
+      // playbin re encodes the last frame the video sink got
+      let caps = Caps::builder("image/png").build();
+      let sample = self.pipeline
+          .emit_by_name::<Option<Sample>>("convert-sample", &[&caps])
+          .context("No frame to screenshot")?;
+
+      let buffer = sample.buffer().context("Screenshot sample has no buffer")?;
+      let map = buffer.map_readable()?;
+      std::fs::write(path, map.as_slice())?;
+      Ok(())
+   }
+
    ////////////////////
    // Stream Methods //
    ////////////////////
@@ -444,6 +924,7 @@ impl GstreamerBackendFramework for BackendV2 {
    }
    fn set_audio_track(&mut self, track: u32) -> Result<()> {
       self.pipeline.set_property("current-audio", track as i32);
+      self.cover_art = self.query_cover_art();
       Ok(())
    }
 
@@ -465,7 +946,7 @@ impl GstreamerBackendFramework for BackendV2 {
          self.pipeline.set_property("audio-sink", None::<&Element>);
 
          // Create a new audio-sink
-         let (new_audio_bin, new_volume, new_audio_sink) = Self::make_audio_sink(Some(device))?;
+         let (new_audio_bin, new_volume, new_audio_sink, mut new_filters, new_analysis) = Self::make_audio_sink(Some(device), self.audio_filters.settings())?;
 
          // Set the new audio-sink to the pipeline
          self.pipeline.set_property("audio-sink", &new_audio_bin);
@@ -473,6 +954,10 @@ impl GstreamerBackendFramework for BackendV2 {
          // Update the audio_sink and volume fields
          self.audio_sink = new_audio_sink;
          self.volume = new_volume;
+         new_filters.set_measured_loudness(self.audio_filters.measured_loudness());
+         self.audio_filters = new_filters;
+         self.audio_analysis = new_analysis;
+         self.apply_volume();
 
          // Set the pipeline state back to PLAYING or the desired state
          self.pipeline.set_state(self.target_state)?;
@@ -532,40 +1017,147 @@ impl GstreamerBackendFramework for BackendV2 {
    }
 
    fn get_volume_range(&self) -> RangeInclusive<f64> {
-      0.0..=5.0
+      0.0..=self.max_volume
    }
 
    fn set_volume(&mut self, to: f64) -> Result<()> {
-      self.current_volume = to;
-      self.volume.set_property("volume", to);
+      self.current_volume = to.clamp(0.0, self.max_volume);
+      self.apply_volume();
       Ok(())
    }
 
-   //////////////////////
-   // Subtitle Methods //
-   //////////////////////
+   fn set_max_volume(&mut self, max: f64) -> Result<()> {
+      // the volume element tops out at 10x
+      self.max_volume = max.clamp(1.0, 10.0);
+      self.set_volume(self.current_volume)
+   }
 
-   fn toggle_playflag(&mut self, set_to: bool, flag: u32) -> Result<()> {
-      let gobject_ptr = to_g_obj_pointer(self.pipeline.clone())?;
+   fn is_muted(&self) -> bool {
+      self.muted
+   }
 
-      let property_name = CString::new("flags")?;
-      let mut flags: u32 = 0;
+   fn set_muted(&mut self, muted: bool) -> Result<()> {
+      self.muted = muted;
+      self.apply_volume();
+      Ok(())
+   }
 
-      unsafe {
-         g_object_get(gobject_ptr, property_name.as_ptr(), &mut flags as *mut u32 as *mut _, std::ptr::null::<i32>());
+   fn get_audio_filters(&self) -> &AudioFilterSettings {
+      self.audio_filters.settings()
+   }
 
-         if set_to {
-            flags |= flag;
-         } else {
-            flags &= !flag;
-         }
+   fn set_audio_filters(&mut self, settings: &AudioFilterSettings) -> Result<()> {
+      self.audio_filters.apply(settings);
+      self.start_loudness_scan();
+      Ok(())
+   }
 
-         g_object_set(gobject_ptr, property_name.as_ptr(), flags, std::ptr::null::<i32>());
+   fn get_loudness_state(&self) -> LoudnessState {
+      LoudnessState {
+         scanning: self.loudness_scan.is_some(),
+         measured_lufs: self.audio_filters.measured_loudness(),
+         gain_db: self.audio_filters.applied_gain_db(),
       }
+   }
+
+   ///////////////////////////
+   // Visualisation Methods //
+   ///////////////////////////
+
+   fn get_spectrum(&self) -> &[f32] {
+      self.audio_analysis.magnitudes()
+   }
+
+   fn get_audio_samples(&self) -> Vec<f32> {
+      self.audio_analysis.samples()
+   }
 
+   fn get_levels(&self) -> &[ChannelLevel] {
+      self.audio_analysis.levels()
+   }
+
+   fn set_audio_analysis(&mut self, enabled: bool) -> Result<()> {
+      self.audio_analysis.set_linked(enabled)
+   }
+
+   fn request_waveform(&mut self) {
+      if self.waveform.is_none() && self.waveform_scan.is_none() {
+         let uri = self.pipeline.property::<String>("uri");
+         self.waveform_scan = Some(WaveformCache::load_or_scan(&uri));
+      }
+   }
+
+   fn get_waveform(&self) -> Option<&Waveform> {
+      self.waveform.as_ref()
+   }
+
+   fn list_vis_plugins(&self) -> Vec<(String, String)> {
+      ElementFactory::factories_with_type(ElementFactoryType::ANY, Rank::NONE)
+          .into_iter()
+          .filter(|factory| factory.klass().contains("Visualization"))
+          .map(|factory| (factory.name().to_string(), factory.longname().to_string()))
+          .collect()
+   }
+
+   fn set_vis_plugin(&mut self, factory: Option<&str>) -> Result<()> {
+      match factory {
+         Some(factory) => {
+            let vis = ElementFactory::make(factory).build()?;
+            self.pipeline.set_property("vis-plugin", &vis);
+            self.toggle_playflag(true, PlayFlags::VIS)
+         }
+         None => self.toggle_playflag(false, PlayFlags::VIS),
+      }
+   }
+
+   fn get_cover_art(&self) -> Option<Sample> {
+      self.cover_art.clone()
+   }
+
+   ///////////////////
+   // Video Methods //
+   ///////////////////
+
+   fn set_color_balance(&mut self, adjustments: &ColorAdjustments) -> Result<()> {
+      self.pending_color_balance = Some(*adjustments);
+      self.apply_color_balance();
+      Ok(())
+   }
+
+   fn set_deinterlace(&mut self, settings: &DeinterlaceSettings) -> Result<()> {
+      self.deinterlace = *settings;
+      let Some(deinterlacer) = &self.deinterlacer else {
+         return match settings.needs_element() {
+            true => Err(anyhow::format_err!("No deinterlace element, reopen the file")),
+            false => Ok(()),
+         };
+      };
+
+      let (mode, method) = settings.element_properties();
+      deinterlacer.set_property_from_str("mode", mode);
+      if let Some(method) = method {
+         deinterlacer.set_property_from_str("method", method);
+      }
       Ok(())
    }
 
+   fn set_max_video_size(&mut self, max_size: Option<(u32, u32)>) -> Result<()> {
+      if self.max_video_size == max_size {
+         return Ok(());
+      }
+      self.max_video_size = max_size;
+
+      self.apply_video_caps()
+   }
+
+   //////////////////////
+   // Subtitle Methods //
+   //////////////////////
+
+   fn toggle_playflag(&mut self, set_to: bool, flag: u32) -> Result<()> {
+      set_playflag(&self.pipeline, set_to, flag)
+   }
+
    fn get_playflag_state(&self, flag: u32) -> Result<bool> {
       let gobject_ptr = to_g_obj_pointer(self.pipeline.clone())?;
 
@@ -585,6 +1177,28 @@ impl GstreamerBackendFramework for BackendV2 {
    }
 }
 
+/// sets or clears one of playbin's ``flags``
+fn set_playflag(pipeline: &Pipeline, set_to: bool, flag: u32) -> Result<()> {
+   let gobject_ptr = to_g_obj_pointer(pipeline.clone())?;
+
+   let property_name = CString::new("flags")?;
+   let mut flags: u32 = 0;
+
+   unsafe {
+      g_object_get(gobject_ptr, property_name.as_ptr(), &mut flags as *mut u32 as *mut _, std::ptr::null::<i32>());
+
+      if set_to {
+         flags |= flag;
+      } else {
+         flags &= !flag;
+      }
+
+      g_object_set(gobject_ptr, property_name.as_ptr(), flags, std::ptr::null::<i32>());
+   }
+
+   Ok(())
+}
+
 #[allow(dead_code)]
 fn probe_props(element: &Element) {
    let props = element.list_properties();
@@ -617,3 +1231,55 @@ struct FrameQueueInfo {
    start_state: State,
    in_progress: bool,
 }
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use gstreamer::Fraction;
+   use gstreamer_app::AppSrc;
+   use gstreamer_video::{VideoFormat, VideoInterlaceMode};
+   use crate::gstreamer_internals::deinterlace::{DeinterlaceMethod, DeinterlaceMode};
+
+   /// prerolls interlaced frames from an ``appsrc`` through the player's video sink, the caps that arrive
+   fn sink_caps(settings: &DeinterlaceSettings) -> Caps {
+      gstreamer::init().unwrap();
+      let pipeline: Pipeline = ElementFactory::make("playbin").property("uri", "appsrc://").build().unwrap().dynamic_cast().unwrap();
+
+      let info = VideoInfo::builder(VideoFormat::Rgba, 64, 64)
+          .interlace_mode(VideoInterlaceMode::Interleaved)
+          .fps(Fraction::new(25, 1))
+          .build()
+          .unwrap();
+      pipeline.connect("source-setup", false, move |args| {
+         let src = args[1].get::<Element>().unwrap().dynamic_cast::<AppSrc>().unwrap();
+         src.set_caps(Some(&info.to_caps().unwrap()));
+         src.set_format(Format::Time);
+         for i in 0..3 {
+            let mut buffer = gstreamer::Buffer::with_size(info.size()).unwrap();
+            buffer.get_mut().unwrap().set_pts(ClockTime::from_mseconds(40 * i));
+            src.push_buffer(buffer).unwrap();
+         }
+         src.end_of_stream().unwrap();
+         None
+      });
+
+      let (appsink, _deinterlacer) = BackendV2::make_video_sink(&pipeline, settings).unwrap();
+      pipeline.set_state(State::Paused).unwrap();
+      let sample = appsink.try_pull_preroll(ClockTime::from_seconds(10)).expect("No frame prerolled");
+      pipeline.set_state(State::Null).unwrap();
+
+      sample.caps().unwrap().to_owned()
+   }
+
+   #[test]
+   fn only_the_chosen_deinterlacer_runs() {
+      let off = DeinterlaceSettings { mode: DeinterlaceMode::Off, method: DeinterlaceMethod::GpuBob };
+      let gpu_bob = DeinterlaceSettings { mode: DeinterlaceMode::Auto, method: DeinterlaceMethod::GpuBob };
+
+      for settings in [off, gpu_bob] {
+         let info = VideoInfo::from_caps(&sink_caps(&settings)).unwrap();
+         assert_eq!(info.interlace_mode(), VideoInterlaceMode::Interleaved, "{settings:?}");
+      }
+      assert!(gpu_bob.bob_on_gpu(VideoInterlaceMode::Interleaved));
+   }
+}
@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use gstreamer::Buffer;
+use gstreamer_video::video_frame::Readable;
+use gstreamer_video::{VideoFormat, VideoFrame, VideoFrameExt, VideoInfo};
+
+/// luma under this is black, a little above limited range black
+const BLACK_LEVEL: f32 = 0.1;
+
+/// frames that have to be seen before a crop is reported, the crop is the smallest border among them
+const WINDOW: usize = 12;
+
+/// time between analysed frames
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// pixels checked along each row or column
+const SAMPLES_PER_LINE: u32 = 64;
+
+/// borders thinner than this fraction of the frame are left alone
+const MIN_BORDER: f32 = 0.01;
+
+/// black border on each side as a fraction of the frame
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Borders {
+   pub left: f32,
+   pub top: f32,
+   pub right: f32,
+   pub bottom: f32,
+}
+
+impl Borders {
+   pub fn is_empty(&self) -> bool {
+      self.left == 0.0 && self.top == 0.0 && self.right == 0.0 && self.bottom == 0.0
+   }
+
+   fn min(self, other: Self) -> Self {
+      Self {
+         left: self.left.min(other.left),
+         top: self.top.min(other.top),
+         right: self.right.min(other.right),
+         bottom: self.bottom.min(other.bottom),
+      }
+   }
+}
+
+/// borders of one frame, ``None`` when all of it is black, ``luma(x, y)`` is 0..1
+fn frame_borders(width: u32, height: u32, luma: impl Fn(u32, u32) -> f32) -> Option<Borders> {
+   if width < 2 || height < 2 {
+      return None;
+   }
+
+   let row_black = |y| (0..SAMPLES_PER_LINE).all(|i| luma(i * (width - 1) / (SAMPLES_PER_LINE - 1), y) < BLACK_LEVEL);
+   let column_black = |x| (0..SAMPLES_PER_LINE).all(|i| luma(x, i * (height - 1) / (SAMPLES_PER_LINE - 1)) < BLACK_LEVEL);
+
+   let top = (0..height).position(|y| !row_black(y))?;
+   let bottom = (0..height).rev().position(|y| !row_black(y)).unwrap_or(0);
+   let left = (0..width).position(|x| !column_black(x)).unwrap_or(0);
+   let right = (0..width).rev().position(|x| !column_black(x)).unwrap_or(0);
+
+   let fraction = |pixels: usize, of: u32| {
+      let fraction = pixels as f32 / of as f32;
+      if fraction < MIN_BORDER { 0.0 } else { fraction }
+   };
+
+   Some(Borders {
+      left: fraction(left, width),
+      top: fraction(top, height),
+      right: fraction(right, width),
+      bottom: fraction(bottom, height),
+   })
+}
+
+/// luma of ``frame`` at a pixel, for the formats the player negotiates
+fn luma_reader<'a>(frame: &'a VideoFrame<Readable>) -> Option<Box<dyn Fn(u32, u32) -> f32 + 'a>> {
+   let data = frame.plane_data(0).ok()?;
+   let stride = frame.plane_stride()[0] as usize;
+   let u16_at = move |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]) as f32 / 65535.0;
+   let luma = |r: f32, g: f32, b: f32| 0.2126 * r + 0.7152 * g + 0.0722 * b;
+
+   let reader: Box<dyn Fn(u32, u32) -> f32 + 'a> = match frame.format() {
+      VideoFormat::Rgba => Box::new(move |x, y| {
+         let at = y as usize * stride + x as usize * 4;
+         luma(data[at] as f32, data[at + 1] as f32, data[at + 2] as f32) / 255.0
+      }),
+      VideoFormat::Rgba64Le => Box::new(move |x, y| {
+         let at = y as usize * stride + x as usize * 8;
+         luma(u16_at(at), u16_at(at + 2), u16_at(at + 4))
+      }),
+      VideoFormat::Rgb10a2Le => Box::new(move |x, y| {
+         let at = y as usize * stride + x as usize * 4;
+         let packed = u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+         let channel = |shift: u32| ((packed >> shift) & 0x3ff) as f32 / 1023.0;
+         luma(channel(0), channel(10), channel(20))
+      }),
+      // the luma plane is all that's needed
+      VideoFormat::P01010le | VideoFormat::P016Le => Box::new(move |x, y| u16_at(y as usize * stride + x as usize * 2)),
+      _ => return None,
+   };
+   Some(reader)
+}
+
+/// the last ``WINDOW`` frames that weren't entirely black, cleared when the resolution changes
+#[derive(Default)]
+struct BorderWindow {
+   size: (u32, u32),
+   frames: VecDeque<Borders>,
+   /// the crop so far at this resolution, it only ever shrinks
+   settled: Option<Borders>,
+}
+
+impl BorderWindow {
+   /// adds a frame, returns the crop once the window has been full,
+   /// a dark scene outlasting the window can't grow it again, picture seen outside it is always picture
+   fn push(&mut self, size: (u32, u32), borders: Option<Borders>) -> Option<Borders> {
+      if size != self.size {
+         self.size = size;
+         self.frames.clear();
+         self.settled = None;
+      }
+
+      // black frames say nothing about where the picture ends, fades and dark scenes are skipped
+      if let Some(borders) = borders {
+         self.frames.push_back(borders);
+         if self.frames.len() > WINDOW {
+            self.frames.pop_front();
+         }
+      }
+
+      if self.frames.len() == WINDOW {
+         let window = self.frames.iter().copied().reduce(Borders::min);
+         self.settled = match (self.settled, window) {
+            (Some(settled), Some(window)) => Some(settled.min(window)),
+            (settled, window) => settled.or(window),
+         };
+      }
+      self.settled
+   }
+}
+
+/// looks for letterbox and pillarbox bars on a background thread,
+/// fed a frame every ``SAMPLE_INTERVAL`` and never blocking the caller
+pub struct BlackBarDetector {
+   sender: crossbeam_channel::Sender<(Buffer, VideoInfo)>,
+   detected: Arc<Mutex<Option<Borders>>>,
+   last_sample: Option<Instant>,
+}
+
+impl BlackBarDetector {
+   pub fn new() -> Self {
+      let (sender, receiver) = crossbeam_channel::bounded::<(Buffer, VideoInfo)>(1);
+      let detected = Arc::new(Mutex::new(None));
+
+      let thread_detected = Arc::clone(&detected);
+      std::thread::spawn(move || {
+         let mut window = BorderWindow::default();
+
+         // ends when the detector and its sender are dropped
+         for (buffer, info) in receiver {
+            let Ok(frame) = VideoFrame::from_buffer_readable(buffer, &info) else { continue };
+            let Some(luma) = luma_reader(&frame) else { continue };
+
+            let borders = frame_borders(frame.width(), frame.height(), luma);
+            *thread_detected.lock().unwrap() = window.push((frame.width(), frame.height()), borders);
+         }
+      });
+
+      Self {
+         sender,
+         detected,
+         last_sample: None,
+      }
+   }
+
+   /// hands over ``frame`` when another sample is due and the thread isn't still busy
+   pub fn offer(&mut self, frame: &VideoFrame<Readable>) {
+      if self.last_sample.is_some_and(|last| last.elapsed() < SAMPLE_INTERVAL) {
+         return;
+      }
+
+      if self.sender.try_send((frame.buffer().to_owned(), frame.info().clone())).is_ok() {
+         self.last_sample = Some(Instant::now());
+      }
+   }
+
+   /// the bars found over the last few seconds, ``None`` until enough frames were seen
+   pub fn detected(&self) -> Option<Borders> {
+      *self.detected.lock().unwrap()
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   /// 2.39:1 picture letterboxed in 1920x1080
+   fn letterboxed(_x: u32, y: u32, brightness: f32) -> f32 {
+      if (138..942).contains(&y) { brightness } else { 0.0 }
+   }
+
+   #[test]
+   fn finds_letterbox() {
+      let borders = frame_borders(1920, 1080, |x, y| letterboxed(x, y, 0.5)).unwrap();
+      assert!((borders.top - 138.0 / 1080.0).abs() < 1e-4);
+      assert!((borders.bottom - 138.0 / 1080.0).abs() < 1e-4);
+      assert_eq!(borders.left, 0.0);
+      assert_eq!(borders.right, 0.0);
+   }
+
+   #[test]
+   fn black_frames_are_skipped() {
+      assert_eq!(frame_borders(1920, 1080, |_, _| 0.0), None);
+
+      let mut window = BorderWindow::default();
+      let bars = frame_borders(1920, 1080, |x, y| letterboxed(x, y, 0.5));
+      for _ in 0..WINDOW - 1 {
+         assert_eq!(window.push((1920, 1080), bars), None);
+      }
+      assert_eq!(window.push((1920, 1080), None), None);
+      assert_eq!(window.push((1920, 1080), bars), bars);
+   }
+
+   #[test]
+   fn dark_scenes_dont_grow_the_crop() {
+      let mut window = BorderWindow::default();
+      let bars = frame_borders(1920, 1080, |x, y| letterboxed(x, y, 0.5));
+      for _ in 0..WINDOW {
+         window.push((1920, 1080), bars);
+      }
+
+      // only the middle of a dark scene is above black
+      let dark = frame_borders(1920, 1080, |x, y| if (400..680).contains(&y) { letterboxed(x, y, 0.5) } else { 0.0 });
+      for _ in 0..WINDOW * 2 {
+         assert_eq!(window.push((1920, 1080), dark), bars);
+      }
+   }
+
+   #[test]
+   fn resolution_change_resets() {
+      let mut window = BorderWindow::default();
+      let bars = frame_borders(1920, 1080, |x, y| letterboxed(x, y, 0.5));
+      for _ in 0..WINDOW {
+         window.push((1920, 1080), bars);
+      }
+      assert_eq!(window.push((1280, 720), bars), None);
+   }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+/// picture adjustments, the ranges match ``videobalance`` so both paths look the same
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorAdjustments {
+   /// added to every channel, ``-1.0..=1.0``
+   pub brightness: f32,
+   /// ``0.0..=2.0`` around mid grey
+   pub contrast: f32,
+   /// ``0.0..=2.0``, zero is greyscale
+   pub saturation: f32,
+   /// rotation of the colour wheel, ``-1.0..=1.0`` is a full turn
+   pub hue: f32,
+   /// ``0.1..=4.0``, only the gpu path can do gamma
+   pub gamma: f32,
+}
+
+impl Default for ColorAdjustments {
+   fn default() -> Self {
+      Self {
+         brightness: 0.0,
+         contrast: 1.0,
+         saturation: 1.0,
+         hue: 0.0,
+         gamma: 1.0,
+      }
+   }
+}
+
+impl ColorAdjustments {
+   pub fn is_identity(&self) -> bool {
+      *self == Self::default()
+   }
+
+   /// value for the ``GstColorBalance`` channel with ``label`` scaled into ``min..=max``,
+   /// ``None`` for channels there's no adjustment for
+   pub fn channel_value(&self, label: &str, min: i32, max: i32) -> Option<i32> {
+      // every channel is centred on its default, -1..1 either side
+      let centred = match label.to_ascii_uppercase().as_str() {
+         "BRIGHTNESS" => self.brightness,
+         "CONTRAST" => self.contrast - 1.0,
+         "SATURATION" => self.saturation - 1.0,
+         "HUE" => self.hue,
+         _ => return None,
+      };
+
+      let fraction = (centred.clamp(-1.0, 1.0) + 1.0) / 2.0;
+      Some(min + ((max - min) as f32 * fraction).round() as i32)
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn defaults_sit_mid_channel() {
+      let adjustments = ColorAdjustments::default();
+      for label in ["BRIGHTNESS", "CONTRAST", "SATURATION", "HUE"] {
+         assert_eq!(adjustments.channel_value(label, -1000, 1000), Some(0));
+      }
+      assert_eq!(adjustments.channel_value("GAMMA", -1000, 1000), None);
+   }
+
+   #[test]
+   fn channels_reach_their_ends() {
+      let adjustments = ColorAdjustments { contrast: 2.0, saturation: 0.0, ..Default::default() };
+      assert_eq!(adjustments.channel_value("CONTRAST", -1000, 1000), Some(1000));
+      assert_eq!(adjustments.channel_value("SATURATION", -1000, 1000), Some(-1000));
+   }
+}
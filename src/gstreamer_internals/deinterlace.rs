@@ -0,0 +1,113 @@
+use gstreamer_video::VideoInterlaceMode;
+use serde::{Deserialize, Serialize};
+
+/// when frames get deinterlaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DeinterlaceMode {
+   Off,
+   /// only frames whose caps say they're interlaced
+   #[default]
+   Auto,
+   /// every frame, for interlaced video flagged as progressive
+   Always,
+}
+
+impl DeinterlaceMode {
+   pub const ALL: &'static [DeinterlaceMode] = &[Self::Off, Self::Auto, Self::Always];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Off => "Off",
+         Self::Auto => "Auto",
+         Self::Always => "Always",
+      }
+   }
+
+   /// value of the ``deinterlace`` element's ``mode`` property
+   fn element_mode(self) -> &'static str {
+      match self {
+         Self::Off => "disabled",
+         Self::Auto => "auto",
+         Self::Always => "interlaced",
+      }
+   }
+}
+
+/// how frames are deinterlaced, all but ``GpuBob`` run in gstreamer's ``deinterlace`` element,
+/// which playbin feeds 8 bit frames so hdr is lost with those
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DeinterlaceMethod {
+   Linear,
+   Yadif,
+   GreedyH,
+   /// line doubles one field in the post process chain, keeps deep colour
+   #[default]
+   GpuBob,
+}
+
+impl DeinterlaceMethod {
+   pub const ALL: &'static [DeinterlaceMethod] = &[Self::Linear, Self::Yadif, Self::GreedyH, Self::GpuBob];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Linear => "Linear",
+         Self::Yadif => "Yadif",
+         Self::GreedyH => "Greedy (high motion)",
+         Self::GpuBob => "Bob (gpu)",
+      }
+   }
+
+   /// value of the ``deinterlace`` element's ``method`` property, ``None`` for the gpu
+   pub fn element_method(self) -> Option<&'static str> {
+      match self {
+         Self::Linear => Some("linear"),
+         Self::Yadif => Some("yadif"),
+         Self::GreedyH => Some("greedyh"),
+         Self::GpuBob => None,
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeinterlaceSettings {
+   pub mode: DeinterlaceMode,
+   pub method: DeinterlaceMethod,
+}
+
+impl DeinterlaceSettings {
+   /// the ``deinterlace`` element is only put in the pipeline for these
+   pub fn needs_element(&self) -> bool {
+      self.mode != DeinterlaceMode::Off && self.method.element_method().is_some()
+   }
+
+   /// ``(mode, method)`` for the ``deinterlace`` element, disabled when the gpu does it
+   pub fn element_properties(&self) -> (&'static str, Option<&'static str>) {
+      match self.method.element_method() {
+         Some(method) => (self.mode.element_mode(), Some(method)),
+         None => (DeinterlaceMode::Off.element_mode(), None),
+      }
+   }
+
+   /// whether the gpu bob runs for frames with ``interlace_mode``
+   pub fn bob_on_gpu(&self, interlace_mode: VideoInterlaceMode) -> bool {
+      self.method == DeinterlaceMethod::GpuBob && match self.mode {
+         DeinterlaceMode::Off => false,
+         DeinterlaceMode::Auto => interlace_mode != VideoInterlaceMode::Progressive,
+         DeinterlaceMode::Always => true,
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn auto_only_bobs_interlaced() {
+      let settings = DeinterlaceSettings::default();
+      assert!(!settings.bob_on_gpu(VideoInterlaceMode::Progressive));
+      assert!(settings.bob_on_gpu(VideoInterlaceMode::Interleaved));
+      assert!(!settings.needs_element());
+   }
+}
@@ -0,0 +1,146 @@
+use std::thread::JoinHandle;
+use anyhow::{bail, Context, Result};
+use gstreamer::prelude::ElementExt;
+use gstreamer::{ClockTime, ElementFactory, MessageView, State};
+use serde::{Deserialize, Serialize};
+use crate::gstreamer_internals::audio_scan::first_audio_stream_pipeline;
+use crate::gstreamer_internals::probe_cache::CacheIndex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LoudnessMode {
+   #[default]
+   Off,
+   /// ``audioloudnorm`` linked into the audio bin, follows the loudness as it goes with a few seconds of lookahead
+   RealTime,
+   /// measures the whole file once with ``ebur128level`` and applies a fixed gain
+   PreScan,
+}
+
+impl LoudnessMode {
+   pub const ALL: &'static [LoudnessMode] = &[Self::Off, Self::RealTime, Self::PreScan];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Off => "Off",
+         Self::RealTime => "Real time",
+         Self::PreScan => "Pre-scan",
+      }
+   }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnessSettings {
+   pub mode: LoudnessMode,
+   /// integrated loudness everything is brought to, -23 is EBU R128 and -14 is roughly streaming sites
+   pub target_lufs: f64,
+   /// most boost a pre-scan gain can add, quiet files with loud peaks would clip past this
+   pub max_gain_db: f64,
+}
+
+impl Default for LoudnessSettings {
+   fn default() -> Self {
+      Self {
+         mode: LoudnessMode::Off,
+         target_lufs: -18.0,
+         max_gain_db: 12.0,
+      }
+   }
+}
+
+impl LoudnessSettings {
+   /// dB needed to bring ``measured_lufs`` to the target
+   pub fn gain_db(&self, measured_lufs: f64) -> f64 {
+      (self.target_lufs - measured_lufs).min(self.max_gain_db)
+   }
+}
+
+static INDEX: CacheIndex = CacheIndex::new("loudness_cache.json", 5000);
+
+/// integrated loudness per uri on disk, invalidated the same way as the probe cache
+pub struct LoudnessCache;
+
+impl LoudnessCache {
+   pub fn lookup(uri: &str) -> Option<f64> {
+      INDEX.lookup(uri)
+   }
+
+   pub fn store(uri: &str, integrated_lufs: f64) -> Result<()> {
+      INDEX.store(uri, integrated_lufs)
+   }
+
+   /// cached loudness if there is one, otherwise a background scan that stores its result
+   pub fn measure(uri: &str) -> JoinHandle<Result<f64>> {
+      let uri = uri.to_string();
+      std::thread::spawn(move || {
+         if let Some(lufs) = Self::lookup(&uri) {
+            return Ok(lufs);
+         }
+
+         let lufs = measure_integrated_loudness(&uri)?;
+         if let Err(err) = Self::store(&uri, lufs) {
+            println!("Couldn't write loudness cache {err}");
+         }
+         Ok(lufs)
+      })
+   }
+}
+
+/// decodes the first audio stream of ``uri`` as fast as possible through ``ebur128level``,
+/// blocking, takes a few seconds for a typical episode
+pub fn measure_integrated_loudness(uri: &str) -> Result<f64> {
+   let level = ElementFactory::make("ebur128level")
+       .property_from_str("mode", "global")
+       .property("post-messages", true)
+       .build()?;
+   let sink = ElementFactory::make("fakesink").property("sync", false).build()?;
+
+   let pipeline = first_audio_stream_pipeline(uri, &[&level, &sink])?;
+   pipeline.set_state(State::Playing)?;
+
+   let bus = pipeline.bus().context("Scan pipeline has no bus")?;
+   let mut integrated = None;
+   let mut failure = None;
+
+   for msg in bus.iter_timed(ClockTime::NONE) {
+      match msg.view() {
+         MessageView::Element(element) => {
+            if let Some(s) = element.structure().filter(|s| s.name() == "ebur128-level") {
+               if let Ok(lufs) = s.get::<f64>("global-loudness") {
+                  integrated = Some(lufs);
+               }
+            }
+         }
+         MessageView::Eos(..) => break,
+         MessageView::Error(err) => {
+            failure = Some(format!("{} ({:?})", err.error(), err.debug()));
+            break;
+         }
+         _ => (),
+      }
+   }
+
+   pipeline.set_state(State::Null)?;
+
+   if let Some(failure) = failure {
+      bail!("Loudness scan failed {failure}");
+   }
+
+   match integrated {
+      Some(lufs) if lufs.is_finite() => Ok(lufs),
+      Some(_) => bail!("Audio is silent"),
+      None => bail!("No loudness measured, is there an audio stream"),
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn gain_is_capped() {
+      let settings = LoudnessSettings { target_lufs: -18.0, max_gain_db: 6.0, ..Default::default() };
+      assert_eq!(settings.gain_db(-14.0), -4.0);
+      assert_eq!(settings.gain_db(-40.0), 6.0);
+   }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::{Context, Result};
+use gstreamer::ClockTime;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::gstreamer_internals::prober::Probe;
+
+/// size and mtime of a local file, both ``None`` for network uris which are cached on uri alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+   size: Option<u64>,
+   mtime: Option<u64>,
+}
+
+impl FileStamp {
+   fn of(uri: &str) -> Self {
+      let metadata = Url::parse(uri)
+          .ok()
+          .filter(|url| url.scheme() == "file")
+          .and_then(|url| url.to_file_path().ok())
+          .and_then(|path| std::fs::metadata(path).ok());
+
+      Self {
+         size: metadata.as_ref().map(|m| m.len()),
+         mtime: metadata
+             .and_then(|m| m.modified().ok())
+             .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+             .map(|d| d.as_secs()),
+      }
+   }
+}
+
+/// ``vid_v2`` inside the users cache dir, created if missing
+fn cache_dir() -> Result<PathBuf> {
+   let dir = dirs::cache_dir().context("No cache directory")?.join("vid_v2");
+   std::fs::create_dir_all(&dir)?;
+   Ok(dir)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+   stamp: FileStamp,
+   cached_at: u64,
+   value: T,
+}
+
+/// a json index in ``cache_dir`` of values keyed by uri, each invalidated when its files size or mtime changes,
+/// shared by the probe, loudness and waveform caches
+pub(crate) struct CacheIndex {
+   file_name: &'static str,
+   /// entries past this are dropped oldest first
+   max_entries: usize,
+   /// serializes read modify write of the index between backends and scan threads
+   lock: Mutex<()>,
+}
+
+impl CacheIndex {
+   pub(crate) const fn new(file_name: &'static str, max_entries: usize) -> Self {
+      Self {
+         file_name,
+         max_entries,
+         lock: Mutex::new(()),
+      }
+   }
+
+   fn path(&self) -> Result<PathBuf> {
+      Ok(cache_dir()?.join(self.file_name))
+   }
+
+   fn load<T: DeserializeOwned>(&self) -> HashMap<String, CacheEntry<T>> {
+      self.path()
+          .ok()
+          .and_then(|path| std::fs::read(path).ok())
+          .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+          .unwrap_or_default()
+   }
+
+   fn save<T: Serialize>(&self, entries: &HashMap<String, CacheEntry<T>>) -> Result<()> {
+      let path = self.path()?;
+      // written next to the index then renamed so a crash can't leave half a file
+      let tmp = path.with_extension("json.tmp");
+      std::fs::write(&tmp, serde_json::to_vec(entries)?)?;
+      std::fs::rename(tmp, path)?;
+      Ok(())
+   }
+
+   /// cached value for ``uri``, ``None`` if missing or the file changed since
+   pub(crate) fn lookup<T: DeserializeOwned>(&self, uri: &str) -> Option<T> {
+      let _lock = self.lock.lock().unwrap();
+      let entry = self.load::<T>().remove(uri)?;
+      (entry.stamp == FileStamp::of(uri)).then_some(entry.value)
+   }
+
+   pub(crate) fn store<T: Serialize + DeserializeOwned>(&self, uri: &str, value: T) -> Result<()> {
+      let _lock = self.lock.lock().unwrap();
+      let mut entries = self.load::<T>();
+
+      let cached_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+      entries.insert(uri.to_string(), CacheEntry {
+         stamp: FileStamp::of(uri),
+         cached_at,
+         value,
+      });
+
+      if entries.len() > self.max_entries {
+         let mut by_age = entries.iter().map(|(uri, e)| (e.cached_at, uri.clone())).collect::<Vec<_>>();
+         by_age.sort();
+         for (_, uri) in by_age.into_iter().take(entries.len() - self.max_entries) {
+            entries.remove(&uri);
+         }
+      }
+
+      self.save(&entries)
+   }
+
+   pub(crate) fn clear(&self) -> Result<()> {
+      let _lock = self.lock.lock().unwrap();
+      self.save::<()>(&HashMap::new())
+   }
+}
+
+static INDEX: CacheIndex = CacheIndex::new("probe_cache.json", 2000);
+
+/// on disk json index of probe results keyed by uri, invalidated when the files size or mtime changes
+pub struct ProbeCache;
+
+impl ProbeCache {
+   /// cached probe for ``uri``, ``None`` if missing or the file changed since
+   pub fn lookup(uri: &str) -> Option<Probe> {
+      INDEX.lookup(uri)
+   }
+
+   pub fn store(probe: &Probe) -> Result<()> {
+      INDEX.store(&probe.uri, probe.clone())
+   }
+
+   pub fn clear() -> Result<()> {
+      INDEX.clear()
+   }
+
+   /// the cached probe straight away if there is a valid one,
+   /// plus a background discovery that refreshes the cache either way
+   pub fn probe(uri: &str, timeout: ClockTime) -> (Option<Probe>, JoinHandle<Result<Probe>>) {
+      let cached = Self::lookup(uri);
+
+      let uri = uri.to_string();
+      let handle = std::thread::spawn(move || {
+         let probe = Probe::from_uri_with_timeout(&uri, timeout)?;
+         if let Err(err) = Self::store(&probe) {
+            println!("Couldn't write probe cache {err}");
+         }
+         Ok(probe)
+      });
+
+      (cached, handle)
+   }
+}
@@ -1,12 +1,96 @@
 use anyhow::Result;
-use gstreamer::tags::{AudioCodec, Bitrate, Title, VideoCodec};
-use gstreamer::ClockTime;
+use gstreamer::tags::{AudioCodec, Bitrate, ContainerFormat, ContainerSpecificTrackId, ImageOrientation, LanguageCode, SubtitleCodec, Title, VideoCodec};
+use gstreamer::{Caps, ClockTime, TagList, Toc, TocEntry, TocEntryType};
 use gstreamer_pbutils::prelude::DiscovererStreamInfoExt;
 use gstreamer_pbutils::Discoverer;
+use gstreamer_video::{VideoColorimetry, VideoContentLightLevel, VideoMasteringDisplayInfo, VideoTransferFunction};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::thread::JoinHandle;
 
 // helper functions
-#[derive(Debug, Clone)]
+/// every tag as ``(name, value)``, values that can't be shown as text are debug printed
+fn tag_pairs(tags: Option<TagList>) -> Vec<(String, String)> {
+   let Some(tags) = tags else { return vec![] };
+
+   tags.iter()
+       .map(|(name, value)| {
+          let text = value
+              .transform::<String>()
+              .ok()
+              .and_then(|v| v.get::<String>().ok())
+              .unwrap_or_else(|| format!("{value:?}"));
+          (name.to_string(), text.chars().take(200).collect())
+       })
+       .collect()
+}
+
+fn caps_field(caps: &Option<Caps>, field: &str) -> Option<String> {
+   caps.as_ref()?.structure(0)?.get::<String>(field).ok()
+}
+
+fn caps_name(caps: &Option<Caps>) -> Option<String> {
+   Some(caps.as_ref()?.structure(0)?.name().to_string())
+}
+
+fn track_id(tags: &Option<TagList>) -> Option<u32> {
+   tags.as_ref()?.get::<ContainerSpecificTrackId>()?.get().parse::<u32>().ok()
+}
+
+fn language(tags: &Option<TagList>) -> Option<String> {
+   Some(tags.as_ref()?.get::<LanguageCode>()?.get().to_string())
+}
+
+fn title(tags: &Option<TagList>) -> Option<String> {
+   Some(tags.as_ref()?.get::<Title>()?.get().to_string())
+}
+
+fn channel_layout_name(channels: u32) -> String {
+   match channels {
+      1 => "mono".to_string(),
+      2 => "stereo".to_string(),
+      3 => "2.1".to_string(),
+      6 => "5.1".to_string(),
+      8 => "7.1".to_string(),
+      n => format!("{n} channels"),
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HdrMetadata {
+   /// ``Smpte2084`` for HDR10 / PQ, ``AribStdB67`` for HLG
+   pub transfer: String,
+   /// in cd/m²
+   pub max_mastering_luminance: Option<f64>,
+   pub min_mastering_luminance: Option<f64>,
+   pub max_content_light_level: Option<u32>,
+   pub max_frame_average_light_level: Option<u32>,
+}
+
+impl HdrMetadata {
+   /// ``None`` for anything that isn't PQ or HLG
+   fn from_caps(caps: &Option<Caps>) -> Option<Self> {
+      let colorimetry = VideoColorimetry::from_str(&caps_field(caps, "colorimetry")?).ok()?;
+      if !matches!(colorimetry.transfer(), VideoTransferFunction::Smpte2084 | VideoTransferFunction::AribStdB67) {
+         return None;
+      }
+
+      let caps = caps.as_ref()?;
+      let mastering = VideoMasteringDisplayInfo::from_caps(caps).ok();
+      let light_level = VideoContentLightLevel::from_caps(caps).ok();
+
+      Some(Self {
+         transfer: format!("{:?}", colorimetry.transfer()),
+         // mastering luminance is stored in units of 0.0001 cd/m²
+         max_mastering_luminance: mastering.as_ref().map(|m| m.max_display_mastering_luminance() as f64 / 10000.0),
+         min_mastering_luminance: mastering.as_ref().map(|m| m.min_display_mastering_luminance() as f64 / 10000.0),
+         max_content_light_level: light_level.as_ref().map(|l| l.max_content_light_level() as u32),
+         max_frame_average_light_level: light_level.as_ref().map(|l| l.max_frame_average_light_level() as u32),
+      })
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoStream {
    pub name: Option<String>,
    pub fps: Option<f64>,
@@ -15,94 +99,232 @@ pub struct VideoStream {
    pub resolution: Option<(u32, u32)>,
    pub codec: Option<String>,
    pub index: Option<u32>,
+   pub language: Option<String>,
+   pub pixel_format: Option<String>,
+   pub colorimetry: Option<String>,
+   pub hdr: Option<HdrMetadata>,
+   pub pixel_aspect_ratio: (i32, i32),
+   /// raw ``image-orientation`` tag, e.g. ``rotate-90`` or ``flip-rotate-0``
+   pub rotation: Option<String>,
+   pub interlaced: bool,
+   pub depth: u32,
+   pub tags: Vec<(String, String)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioStream {
    pub name: Option<String>,
    pub codec: Option<String>,
    pub bitrate: Option<u32>,
+   pub max_bitrate: Option<u32>,
+   pub index: Option<u32>,
+   pub language: Option<String>,
+   pub channels: u32,
+   pub channel_layout: String,
+   pub channel_mask: u64,
+   pub sample_rate: u32,
+   pub depth: u32,
+   pub tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleStream {
+   pub name: Option<String>,
+   pub codec: Option<String>,
    pub index: Option<u32>,
+   pub language: Option<String>,
+   pub tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+   pub title: Option<String>,
+   pub start: ClockTime,
+   /// ``None`` when the container doesn't say, usually the last chapter
+   pub end: Option<ClockTime>,
+}
+
+impl Chapter {
+   /// flattens the editions of a toc into its chapters ordered by start time,
+   /// missing ends are filled in from the next chapters start
+   pub fn from_toc(toc: &Toc) -> Vec<Chapter> {
+      fn collect(entries: Vec<TocEntry>, out: &mut Vec<Chapter>) {
+         for entry in entries {
+            match entry.entry_type() {
+               TocEntryType::Chapter => {
+                  let Some((start, end)) = entry.start_stop_times() else { continue };
+                  if start < 0 { continue; }
+
+                  let title = entry
+                      .tags()
+                      .and_then(|t| t.get::<Title>().map(|f| f.get().to_string()));
+
+                  out.push(Chapter {
+                     title,
+                     start: ClockTime::from_nseconds(start as u64),
+                     end: (end >= 0).then(|| ClockTime::from_nseconds(end as u64)),
+                  });
+               }
+               _ => collect(entry.sub_entries(), out),
+            }
+         }
+      }
+
+      let mut out = vec![];
+      collect(toc.entries(), &mut out);
+      out.sort_by_key(|c| c.start);
+
+      for i in 1..out.len() {
+         if out[i - 1].end.is_none() {
+            out[i - 1].end = Some(out[i].start);
+         }
+      }
+
+      out
+   }
+
+   /// index of the chapter playing at ``time``
+   pub fn index_at(chapters: &[Chapter], time: ClockTime) -> Option<usize> {
+      chapters.iter().rposition(|c| c.start <= time)
+   }
+
+   pub fn display_title(&self, index: usize) -> String {
+      match &self.title {
+         None => format!("Chapter {}", index + 1),
+         Some(title) => title.clone(),
+      }
+   }
+
+   /// matches chapters named like an opening or ending, for auto skipping
+   pub fn is_opening_or_ending(&self) -> bool {
+      let Some(title) = &self.title else { return false };
+      let title = title.trim().to_lowercase();
+      ["opening", "ending", "op", "ed"]
+          .iter()
+          .any(|name| title == *name || title.starts_with(&format!("{name} ")))
+   }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Probe {
    pub uri: String,
-   pub captions: Vec<(Option<String>, usize)>,
+   pub container: Option<String>,
+   pub duration: Option<ClockTime>,
+   pub seekable: bool,
+   pub live: bool,
+   pub tags: Vec<(String, String)>,
+   pub captions: Vec<(SubtitleStream, usize)>,
    pub audio_streams: Vec<(AudioStream, usize)>,
    pub video_streams: Vec<(VideoStream, usize)>,
+   pub chapters: Vec<Chapter>,
 }
 impl Probe {
    pub fn from_uri(uri: &str) -> Result<Probe> {
+      Self::from_uri_with_timeout(uri, ClockTime::from_seconds(5))
+   }
+
+   pub fn from_uri_with_timeout(uri: &str, timeout: ClockTime) -> Result<Probe> {
+      log::debug!("Running discoverer on {uri}");
+      let discoverer = Discoverer::new(timeout)?;
+      let info = discoverer.discover_uri(uri)?;
+
+      let global_tags = info.tags();
+      let container = global_tags
+          .as_ref()
+          .and_then(|t| t.get::<ContainerFormat>().map(|f| f.get().to_string()))
+          .or_else(|| info.container_streams().first().and_then(|c| caps_name(&c.caps())));
+
       let mut out = Probe {
          uri: uri.to_string(),
+         container,
+         duration: info.duration(),
+         seekable: info.is_seekable(),
+         live: info.is_live(),
+         tags: tag_pairs(global_tags),
          captions: vec![],
          audio_streams: vec![],
          video_streams: vec![],
+         chapters: vec![],
       };
 
-      println!("Running discoverer");
-      let discoverer = Discoverer::new(ClockTime::from_seconds(5))?;
-      let info = discoverer.discover_uri(uri)?;
-
       for (i, video_stream) in info.video_streams().iter().enumerate() {
          let framerate = video_stream.framerate();
-         let fps = Some(framerate.numer() as f64 / framerate.denom() as f64);
-         let bitrate = Some(video_stream.bitrate());
-         let max_bitrate = Some(video_stream.max_bitrate());
-         let resolution = Some((video_stream.width(), video_stream.height()));
-
-         let name = video_stream
-             .tags()
-             .and_then(|t| t.get::<Title>().map(|f| f.get().to_string()));
+         let fps = (framerate.denom() != 0).then(|| framerate.numer() as f64 / framerate.denom() as f64);
+         let par = video_stream.par();
 
-         let codec = video_stream
-             .tags()
-             .and_then(|t| t.get::<VideoCodec>().map(|f| f.get().to_string()));
+         let tags = video_stream.tags();
+         let caps = video_stream.caps();
 
-         let index = video_stream
-             .tags()
-             .and_then(|t| t.get::<gstreamer::tags::ContainerSpecificTrackId>().map(|f| f.get().to_string().parse::<u32>().ok())).flatten();
+         // encoded caps carry no format field, fall back on the chroma and bit depth
+         let pixel_format = caps_field(&caps, "format").or_else(|| {
+            let chroma = caps_field(&caps, "chroma-format")?;
+            Some(format!("{chroma} {}-bit", video_stream.depth()))
+         });
 
          let s_out = VideoStream {
-            name,
+            name: title(&tags),
             fps,
-            bitrate,
-            max_bitrate,
-            resolution,
-            codec,
-            index,
+            bitrate: Some(video_stream.bitrate()),
+            max_bitrate: Some(video_stream.max_bitrate()),
+            resolution: Some((video_stream.width(), video_stream.height())),
+            codec: tags.as_ref().and_then(|t| t.get::<VideoCodec>().map(|f| f.get().to_string())).or_else(|| caps_name(&caps)),
+            index: track_id(&tags),
+            language: language(&tags),
+            pixel_format,
+            colorimetry: caps_field(&caps, "colorimetry"),
+            hdr: HdrMetadata::from_caps(&caps),
+            pixel_aspect_ratio: (par.numer(), par.denom()),
+            rotation: tags.as_ref().and_then(|t| t.get::<ImageOrientation>().map(|f| f.get().to_string())),
+            interlaced: video_stream.is_interlaced(),
+            depth: video_stream.depth(),
+            tags: tag_pairs(tags),
          };
 
          out.video_streams.push((s_out, i));
       }
 
       for (i, subtitle_stream) in info.subtitle_streams().iter().enumerate() {
-         if let Some(tags) = subtitle_stream.tags() {
-            let language = tags.get::<Title>().map(|t| t.get().to_string());
-            out.captions.push((language, i));
-         }
+         let tags = subtitle_stream.tags();
+         let caps = subtitle_stream.caps();
+
+         let s_out = SubtitleStream {
+            name: title(&tags),
+            codec: tags.as_ref().and_then(|t| t.get::<SubtitleCodec>().map(|f| f.get().to_string())).or_else(|| caps_name(&caps)),
+            index: track_id(&tags),
+            language: language(&tags).or_else(|| subtitle_stream.language().map(|l| l.to_string())),
+            tags: tag_pairs(tags),
+         };
+
+         out.captions.push((s_out, i));
       }
 
       for (i, audio_stream) in info.audio_streams().iter().enumerate() {
-         if let Some(tags) = audio_stream.tags() {
-            let name = tags.get::<Title>().map(|t| t.get().to_string());
-            let codec = tags.get::<AudioCodec>().map(|t| t.get().to_string());
-            let bitrate = tags.get::<Bitrate>().map(|t| t.get());
-            let index = tags.get::<gstreamer::tags::ContainerSpecificTrackId>().map(|t| t.get().to_string().parse::<u32>().ok()).flatten();
-
-            let a_out = AudioStream {
-               name,
-               codec,
-               bitrate,
-               index,
-            };
-
-            out.audio_streams.push((a_out, i));
-         }
+         let tags = audio_stream.tags();
+         let caps = audio_stream.caps();
+
+         let a_out = AudioStream {
+            name: title(&tags),
+            codec: tags.as_ref().and_then(|t| t.get::<AudioCodec>().map(|f| f.get().to_string())).or_else(|| caps_name(&caps)),
+            bitrate: tags.as_ref().and_then(|t| t.get::<Bitrate>().map(|f| f.get())).or(Some(audio_stream.bitrate())),
+            max_bitrate: Some(audio_stream.max_bitrate()),
+            index: track_id(&tags),
+            language: language(&tags).or_else(|| audio_stream.language().map(|l| l.to_string())),
+            channels: audio_stream.channels(),
+            channel_layout: channel_layout_name(audio_stream.channels()),
+            channel_mask: audio_stream.channel_mask(),
+            sample_rate: audio_stream.sample_rate(),
+            depth: audio_stream.depth(),
+            tags: tag_pairs(tags),
+         };
+
+         out.audio_streams.push((a_out, i));
+      }
+
+      if let Some(toc) = info.toc() {
+         out.chapters = Chapter::from_toc(&toc);
       }
 
-      println!("Finished discoverer");
+      log::debug!("Finished discoverer on {uri}");
 
       Ok(out)
    }
@@ -115,8 +337,16 @@ impl Probe {
       });
       handle
    }
-}
 
+   /// whether any video stream has more than 8 bits per channel or an hdr transfer
+   pub fn has_deep_video(&self) -> bool {
+      self.video_streams.iter().any(|(stream, _)| stream.depth > 8 || stream.hdr.is_some())
+   }
+
+   pub fn to_json(&self) -> Result<String> {
+      Ok(serde_json::to_string_pretty(self)?)
+   }
+}
 
 #[cfg(test)]
 mod tests {
@@ -132,6 +362,17 @@ mod tests {
       assert_eq!(probe.is_ok(), true);
    }
 
+   #[test]
+   fn chapter_opening_ending_names() {
+      let chapter = |title: &str| Chapter { title: Some(title.to_string()), start: ClockTime::ZERO, end: None };
+
+      assert!(chapter("Opening").is_opening_or_ending());
+      assert!(chapter("ED").is_opening_or_ending());
+      assert!(chapter("Ending Credits").is_opening_or_ending());
+      assert!(!chapter("Episode").is_opening_or_ending());
+      assert!(!chapter("Edgar").is_opening_or_ending());
+   }
+
    #[test]
    fn probe_test_online() {
       gstreamer::init().unwrap();
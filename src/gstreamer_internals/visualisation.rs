@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use anyhow::{Context, Result};
+use gstreamer::prelude::{Cast, ElementExt, ElementExtManual, GstBinExt, GstBinExtManual, PadExt};
+use gstreamer::{Bin, Caps, ClockTime, Element, ElementFactory, PadProbeData, PadProbeReturn, PadProbeType, Pipeline, Sample, State, StructureRef};
+use gstreamer_app::{AppSink, AppSrc};
+use gstreamer_video::{VideoFrame, VideoFrameExt, VideoInfo};
+
+/// bands ``spectrum`` splits the signal into, spread linearly up to half the sample rate
+pub const SPECTRUM_BANDS: u32 = 256;
+
+/// dB ``spectrum`` reports for anything at or below the noise floor
+pub const SPECTRUM_THRESHOLD: i32 = -80;
+
+/// mono samples kept for the oscilloscope, about 40ms at 48khz
+const SCOPE_SAMPLES: usize = 2048;
+
+/// dB ``level`` reports for a silent channel
+pub const LEVEL_FLOOR: f64 = -70.0;
+
+/// one channel of the latest ``level`` message, both in dBFS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelLevel {
+   pub peak_db: f64,
+   pub rms_db: f64,
+}
+
+/// taps the audio bin for the egui visualisations,
+/// ``spectrum`` and ``level`` post on the bus and a pad probe keeps the latest samples,
+/// it's only linked in while something shows it since it converts and transforms every buffer
+pub struct AudioAnalysis {
+   /// takes whatever the filters put out so linking in doesn't need upstream to renegotiate
+   convert: Element,
+   /// forces f32 so the probe can read samples without caring about the source format
+   format: Element,
+   spectrum: Element,
+   level: Element,
+   /// the analysis goes between ``before`` and ``after`` in ``bin``
+   bin: Bin,
+   before: Element,
+   after: Element,
+   linked: bool,
+   samples: Arc<Mutex<VecDeque<f32>>>,
+   magnitudes: Vec<f32>,
+   levels: Vec<ChannelLevel>,
+}
+
+impl AudioAnalysis {
+   /// made unlinked, ``before`` has to be linked straight to ``after`` already
+   pub fn new(bin: &Bin, before: &Element, after: &Element) -> Result<Self> {
+      let convert = ElementFactory::make("audioconvert").build()?;
+      let format = ElementFactory::make("capsfilter")
+          .property("caps", Caps::builder("audio/x-raw").field("format", "F32LE").build())
+          .build()?;
+
+      let spectrum = ElementFactory::make("spectrum")
+          .property("bands", SPECTRUM_BANDS)
+          .property("threshold", SPECTRUM_THRESHOLD)
+          .property("interval", 33_000_000u64)
+          .property("post-messages", true)
+          .property("message-magnitude", true)
+          .build()?;
+
+      let level = ElementFactory::make("level")
+          .property("interval", 50_000_000u64)
+          .property("post-messages", true)
+          .build()?;
+
+      let samples = Arc::new(Mutex::new(VecDeque::with_capacity(SCOPE_SAMPLES)));
+
+      let probe_samples = Arc::clone(&samples);
+      spectrum.static_pad("sink").context("Spectrum has no sink pad")?.add_probe(PadProbeType::BUFFER, move |pad, info| {
+         let Some(PadProbeData::Buffer(buffer)) = &info.data else {
+            return PadProbeReturn::Ok;
+         };
+
+         let channels = pad
+             .current_caps()
+             .and_then(|caps| caps.structure(0).and_then(|s| s.get::<i32>("channels").ok()))
+             .unwrap_or(1)
+             .max(1) as usize;
+
+         if let Ok(map) = buffer.map_readable() {
+            let floats: &[f32] = bytemuck::try_cast_slice(map.as_slice()).unwrap_or(&[]);
+
+            let mut samples = probe_samples.lock().unwrap();
+            for frame in floats.chunks_exact(channels) {
+               samples.push_back(frame.iter().sum::<f32>() / channels as f32);
+            }
+            let excess = samples.len().saturating_sub(SCOPE_SAMPLES);
+            samples.drain(..excess);
+         }
+
+         PadProbeReturn::Ok
+      });
+
+      Ok(Self {
+         convert,
+         format,
+         spectrum,
+         level,
+         bin: bin.clone(),
+         before: before.clone(),
+         after: after.clone(),
+         linked: false,
+         samples,
+         magnitudes: vec![],
+         levels: vec![],
+      })
+   }
+
+   /// in link order
+   fn elements(&self) -> [Element; 4] {
+      [self.convert.clone(), self.format.clone(), self.spectrum.clone(), self.level.clone()]
+   }
+
+   /// puts the analysis in or takes it out while audio keeps flowing, done once ``before`` is between buffers
+   pub fn set_linked(&mut self, linked: bool) -> Result<()> {
+      if linked == self.linked {
+         return Ok(());
+      }
+      self.linked = linked;
+      if !linked {
+         self.magnitudes.clear();
+         self.levels.clear();
+         self.samples.lock().unwrap().clear();
+      }
+
+      relink_when_idle(&self.bin, &self.before, &self.after, &self.elements(), linked)
+   }
+
+   /// picks ``spectrum`` and ``level`` messages out of forwarded element messages
+   pub fn handle_message(&mut self, structure: &StructureRef) {
+      match structure.name().as_str() {
+         "spectrum" => {
+            if let Ok(magnitudes) = structure.get::<gstreamer::List>("magnitude") {
+               self.magnitudes = magnitudes.iter().filter_map(|v| v.get::<f32>().ok()).collect();
+            }
+         }
+         "level" => {
+            let per_channel = |field| {
+               structure
+                   .get::<gstreamer::glib::ValueArray>(field)
+                   .map(|values| values.iter().filter_map(|v| v.get::<f64>().ok()).collect::<Vec<_>>())
+                   .unwrap_or_default()
+            };
+
+            self.levels = per_channel("peak")
+                .into_iter()
+                .zip(per_channel("rms"))
+                .map(|(peak_db, rms_db)| ChannelLevel {
+                   peak_db: peak_db.max(LEVEL_FLOOR),
+                   rms_db: rms_db.max(LEVEL_FLOOR),
+                })
+                .collect();
+         }
+         _ => (),
+      }
+   }
+
+   /// one entry per channel in the order ``level`` reports them, empty until audio plays
+   pub fn levels(&self) -> &[ChannelLevel] {
+      &self.levels
+   }
+
+   /// dB per band, lowest frequency first, empty until audio plays
+   pub fn magnitudes(&self) -> &[f32] {
+      &self.magnitudes
+   }
+
+   pub fn samples(&self) -> Vec<f32> {
+      self.samples.lock().unwrap().iter().copied().collect()
+   }
+}
+
+/// ``relink`` from a probe once ``before`` is between buffers, so audio keeps flowing
+pub(crate) fn relink_when_idle(bin: &Bin, before: &Element, after: &Element, elements: &[Element], linked: bool) -> Result<()> {
+   let (bin, before, after, elements) = (bin.clone(), before.clone(), after.clone(), elements.to_vec());
+   let src = before.static_pad("src").context("Nothing to link after")?;
+   src.add_probe(PadProbeType::IDLE, move |_, _| {
+      if let Err(err) = relink(&bin, &before, &after, &elements, linked) {
+         println!("Couldn't relink audio elements {err}");
+      }
+      PadProbeReturn::Remove
+   });
+   Ok(())
+}
+
+/// ``before ! elements ! after`` when ``linked``, ``before ! after`` otherwise
+fn relink(bin: &Bin, before: &Element, after: &Element, elements: &[Element], linked: bool) -> Result<()> {
+   match linked {
+      true => {
+         before.unlink(after);
+         bin.add_many(elements)?;
+         let chain = std::iter::once(before).chain(elements).chain(std::iter::once(after)).collect::<Vec<_>>();
+         Element::link_many(&chain)?;
+         for element in elements {
+            element.sync_state_with_parent()?;
+         }
+      }
+      false => {
+         // removing them from the bin unlinks them too
+         bin.remove_many(elements)?;
+         for element in elements {
+            element.set_state(State::Null)?;
+         }
+         before.link(after)?;
+      }
+   }
+   // the elements may add latency, like ``audioloudnorm``'s lookahead
+   let _ = bin.post_message(gstreamer::message::Latency::builder().src(bin).build());
+   Ok(())
+}
+
+/// an image decoded to tightly packed rgba
+pub struct DecodedImage {
+   pub width: usize,
+   pub height: usize,
+   pub rgba: Vec<u8>,
+}
+
+/// decodes an image sample such as embedded cover art with a throwaway pipeline
+pub fn decode_image(sample: &Sample) -> Result<DecodedImage> {
+   let pipeline = gstreamer::parse::launch(
+      "appsrc name=src ! decodebin ! videoconvert ! video/x-raw,format=RGBA ! appsink name=sink"
+   )?.downcast::<Pipeline>().unwrap();
+
+   let src = pipeline.by_name("src").unwrap().downcast::<AppSrc>().unwrap();
+   let sink = pipeline.by_name("sink").unwrap().downcast::<AppSink>().unwrap();
+
+   let caps = sample.caps().map(|caps| caps.to_owned());
+   src.set_caps(caps.as_ref());
+   src.push_buffer(sample.buffer().context("Image has no data")?.to_owned())?;
+   src.end_of_stream()?;
+
+   pipeline.set_state(State::Playing)?;
+   let decoded = sink.try_pull_sample(ClockTime::from_seconds(5));
+   pipeline.set_state(State::Null)?;
+
+   let decoded = decoded.context("Couldn't decode image")?;
+   let info = VideoInfo::from_caps(decoded.caps().context("No caps")?)?;
+   let frame = VideoFrame::from_buffer_readable(decoded.buffer_owned().context("No buffer")?, &info)
+       .ok()
+       .context("Failed to map image")?;
+
+   let (width, height) = (frame.width() as usize, frame.height() as usize);
+   let stride = frame.plane_stride()[0] as usize;
+   let data = frame.plane_data(0)?;
+
+   // drops the row padding
+   let rgba = (0..height)
+       .flat_map(|row| &data[row * stride..row * stride + width * 4])
+       .copied()
+       .collect();
+
+   Ok(DecodedImage { width, height, rgba })
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+/// the quietest a ``Decibel`` slider goes before dropping to silence
+const DB_FLOOR: f64 = -60.0;
+
+/// how a 0..=1 slider position maps to the linear gain given to the ``volume`` element,
+/// linear gain of 1.0 is the file as mastered and anything above it is boost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VolumeCurve {
+   /// gain is the cube of the slider, same as pulseaudio and ``GST_STREAM_VOLUME_FORMAT_CUBIC``
+   #[default]
+   Cubic,
+   /// the slider moves evenly in dB between ``DB_FLOOR`` and the max boost
+   Decibel,
+   Linear,
+}
+
+impl VolumeCurve {
+   pub const ALL: &'static [VolumeCurve] = &[Self::Cubic, Self::Decibel, Self::Linear];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Cubic => "Cubic",
+         Self::Decibel => "Decibel",
+         Self::Linear => "Linear",
+      }
+   }
+
+   /// linear gain for a slider at ``position``, ``max_gain`` being the gain at the far right
+   pub fn to_gain(self, position: f64, max_gain: f64) -> f64 {
+      let position = position.clamp(0.0, 1.0);
+      match self {
+         Self::Cubic => max_gain * position.powi(3),
+         Self::Decibel => match position <= 0.0 {
+            true => 0.0,
+            false => max_gain * db_to_gain(DB_FLOOR * (1.0 - position)),
+         },
+         Self::Linear => max_gain * position,
+      }
+   }
+
+   /// slider position showing ``gain``, the inverse of ``to_gain``
+   pub fn to_position(self, gain: f64, max_gain: f64) -> f64 {
+      if gain <= 0.0 || max_gain <= 0.0 {
+         return 0.0;
+      }
+
+      let ratio = (gain / max_gain).min(1.0);
+      match self {
+         Self::Cubic => ratio.cbrt(),
+         Self::Decibel => (1.0 - gain_to_db(ratio) / DB_FLOOR).max(0.0),
+         Self::Linear => ratio,
+      }
+   }
+}
+
+pub fn db_to_gain(db: f64) -> f64 {
+   10f64.powf(db / 20.0)
+}
+
+/// ``-inf`` for silence
+pub fn gain_to_db(gain: f64) -> f64 {
+   20.0 * gain.log10()
+}
+
+/// ``80% (-1.9 dB)`` style text for the osd and sliders
+pub fn format_gain(gain: f64) -> String {
+   match gain > 0.0 {
+      true => format!("{:.0}% ({:+.1} dB)", gain * 100.0, gain_to_db(gain)),
+      false => "0%".to_string(),
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn curves_round_trip() {
+      for curve in VolumeCurve::ALL {
+         for max in [1.0, 2.0, 4.0] {
+            for step in 0..=20 {
+               let position = step as f64 / 20.0;
+               let back = curve.to_position(curve.to_gain(position, max), max);
+               assert!((back - position).abs() < 1e-9, "{curve:?} max {max} at {position} gave {back}");
+            }
+         }
+      }
+   }
+
+   #[test]
+   fn curve_ends() {
+      for curve in VolumeCurve::ALL {
+         assert_eq!(curve.to_gain(0.0, 2.0), 0.0);
+         assert!((curve.to_gain(1.0, 2.0) - 2.0).abs() < 1e-9);
+      }
+   }
+}
@@ -0,0 +1,126 @@
+use std::thread::JoinHandle;
+use anyhow::{bail, Context, Result};
+use gstreamer::prelude::{Cast, ElementExt, ElementExtManual};
+use gstreamer::{Caps, ClockTime, ElementFactory, State};
+use gstreamer_app::AppSink;
+use serde::{Deserialize, Serialize};
+use crate::gstreamer_internals::audio_scan::first_audio_stream_pipeline;
+use crate::gstreamer_internals::probe_cache::CacheIndex;
+
+/// peaks per file, plenty for a timeline a few thousand pixels wide
+pub const WAVEFORM_BUCKETS: usize = 2000;
+
+/// loudest sample in each of ``WAVEFORM_BUCKETS`` even slices of the file, 255 is full scale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Waveform {
+   pub peaks: Vec<u8>,
+}
+
+impl Waveform {
+   /// decodes the first audio stream of ``uri`` to mono and keeps the peak of every slice,
+   /// blocking, takes a few seconds for a typical episode
+   pub fn scan(uri: &str) -> Result<Self> {
+      let caps = ElementFactory::make("capsfilter")
+          .property("caps", Caps::builder("audio/x-raw").field("format", "F32LE").field("channels", 1).build())
+          .build()?;
+      let sink = ElementFactory::make("appsink")
+          .property("sync", false)
+          .build()?
+          .dynamic_cast::<AppSink>()
+          .unwrap();
+
+      let pipeline = first_audio_stream_pipeline(uri, &[&caps, sink.upcast_ref()])?;
+
+      pipeline.set_state(State::Paused)?;
+      pipeline.state(ClockTime::from_seconds(10)).0?;
+      let duration = pipeline.query_duration::<ClockTime>().context("Unknown duration")?;
+      pipeline.set_state(State::Playing)?;
+
+      let mut peaks = vec![0f32; WAVEFORM_BUCKETS];
+
+      // pull_sample errors at eos
+      while let Ok(sample) = sink.pull_sample() {
+         let rate = sample.caps()
+             .and_then(|caps| caps.structure(0).and_then(|s| s.get::<i32>("rate").ok()))
+             .unwrap_or(48000)
+             .max(1000) as u64;
+         let Some(buffer) = sample.buffer() else { continue };
+         let Some(pts) = buffer.pts() else { continue };
+         let Ok(map) = buffer.map_readable() else { continue };
+
+         let floats: &[f32] = bytemuck::try_cast_slice(map.as_slice()).unwrap_or(&[]);
+         // one bucket lookup per millisecond of samples instead of per sample
+         let per_ms = (rate / 1000) as usize;
+         for (ms, chunk) in floats.chunks(per_ms).enumerate() {
+            let time = pts.nseconds() + ms as u64 * 1_000_000;
+            let peak = chunk.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+            let bucket = &mut peaks[bucket_of(time, duration.nseconds())];
+            *bucket = bucket.max(peak);
+         }
+      }
+
+      let errored = pipeline.bus().and_then(|bus| bus.pop_filtered(&[gstreamer::MessageType::Error]));
+      pipeline.set_state(State::Null)?;
+
+      if let Some(msg) = errored {
+         bail!("Waveform scan failed {msg:?}");
+      }
+
+      Ok(Self {
+         peaks: peaks.iter().map(|p| (p.clamp(0.0, 1.0) * 255.0).round() as u8).collect(),
+      })
+   }
+}
+
+/// which of the ``WAVEFORM_BUCKETS`` slices ``time`` falls in, anything past the end goes in the last
+fn bucket_of(time: u64, duration: u64) -> usize {
+   let fraction = time as f64 / duration.max(1) as f64;
+   ((fraction * WAVEFORM_BUCKETS as f64) as usize).min(WAVEFORM_BUCKETS - 1)
+}
+
+/// a few hundred files worth of waveforms, oldest dropped first
+static INDEX: CacheIndex = CacheIndex::new("waveform_cache.json", 300);
+
+/// waveforms per uri on disk, invalidated the same way as the probe cache
+pub struct WaveformCache;
+
+impl WaveformCache {
+   pub fn lookup(uri: &str) -> Option<Waveform> {
+      INDEX.lookup(uri)
+   }
+
+   pub fn store(uri: &str, waveform: &Waveform) -> Result<()> {
+      INDEX.store(uri, waveform.clone())
+   }
+
+   /// cached waveform if there is one, otherwise a background scan that stores its result
+   pub fn load_or_scan(uri: &str) -> JoinHandle<Result<Waveform>> {
+      let uri = uri.to_string();
+      std::thread::spawn(move || {
+         if let Some(waveform) = Self::lookup(&uri) {
+            return Ok(waveform);
+         }
+
+         let waveform = Waveform::scan(&uri)?;
+         if let Err(err) = Self::store(&uri, &waveform) {
+            println!("Couldn't write waveform cache {err}");
+         }
+         Ok(waveform)
+      })
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn buckets_cover_duration() {
+      assert_eq!(bucket_of(0, 1000), 0);
+      assert_eq!(bucket_of(500, 1000), WAVEFORM_BUCKETS / 2);
+      assert_eq!(bucket_of(999, 1000), WAVEFORM_BUCKETS - 1);
+      // streams often run a little past the reported duration
+      assert_eq!(bucket_of(1200, 1000), WAVEFORM_BUCKETS - 1);
+      assert_eq!(bucket_of(10, 0), WAVEFORM_BUCKETS - 1);
+   }
+}
@@ -0,0 +1,115 @@
+use eframe::egui::{Slider, Ui};
+use crate::gstreamer_internals::audio_filters::{AudioFilterSettings, EqPreset, StereoMode, EQ_BANDS_HZ, EQ_RANGE_DB};
+use crate::gstreamer_internals::backend_framework::LoudnessState;
+use crate::gstreamer_internals::loudness::LoudnessMode;
+
+/// what normalization is doing right now, with the gain it applies once known
+pub fn loudness_text(mode: LoudnessMode, loudness: &LoudnessState) -> String {
+   match (mode, loudness.scanning, loudness.measured_lufs, loudness.gain_db) {
+      (LoudnessMode::Off, ..) => "Normalization off".to_string(),
+      (LoudnessMode::RealTime, _, _, Some(gain)) => format!("Normalizing in real time, about {gain:+.1} dB"),
+      (LoudnessMode::RealTime, ..) => "Normalizing in real time".to_string(),
+      (LoudnessMode::PreScan, true, ..) => "Measuring loudness...".to_string(),
+      (LoudnessMode::PreScan, false, Some(lufs), Some(gain)) => format!("{lufs:.1} LUFS, applying {gain:+.1} dB"),
+      (LoudnessMode::PreScan, false, ..) => "Loudness couldn't be measured".to_string(),
+   }
+}
+
+fn band_label(hz: f64) -> String {
+   match hz >= 1000.0 {
+      true => format!("{:.0}k", hz / 1000.0),
+      false => format!("{hz:.0}"),
+   }
+}
+
+/// equalizer, night mode, stereo and loudness controls, returns true if anything changed
+/// so the caller can push the settings to the backend
+pub fn audio_filters_editor(ui: &mut Ui, settings: &mut AudioFilterSettings, loudness: &LoudnessState) -> bool {
+   let before = settings.clone();
+
+   // presets leave stereo and normalization alone, they're about the output not the mix
+   ui.horizontal(|ui| {
+      if ui.button("Voice enhancement").on_hover_text("Voice eq with night mode").clicked() {
+         *settings = AudioFilterSettings {
+            stereo: settings.stereo,
+            loudness: settings.loudness.clone(),
+            ..AudioFilterSettings::voice_enhancement()
+         };
+      }
+      if ui.button("Reset").clicked() {
+         *settings = AudioFilterSettings {
+            stereo: settings.stereo,
+            loudness: settings.loudness.clone(),
+            ..AudioFilterSettings::default()
+         };
+      }
+   });
+
+   ui.separator();
+
+   ui.checkbox(&mut settings.eq_enabled, "Equalizer");
+   ui.add_enabled_ui(settings.eq_enabled, |ui| {
+      ui.horizontal_wrapped(|ui| {
+         for preset in EqPreset::ALL {
+            if ui.selectable_label(settings.eq_preset == *preset, preset.label()).clicked() {
+               settings.set_preset(*preset);
+            }
+         }
+      });
+
+      ui.horizontal(|ui| {
+         for (gain, hz) in settings.eq_bands.iter_mut().zip(EQ_BANDS_HZ) {
+            ui.vertical(|ui| {
+               let slider = Slider::new(&mut *gain, EQ_RANGE_DB).vertical().step_by(0.5).show_value(false);
+               if ui.add(slider).on_hover_text(format!("{gain:+.1} dB")).changed() {
+                  settings.eq_preset = EqPreset::Custom;
+               }
+               ui.label(band_label(hz));
+            });
+         }
+      });
+   });
+
+   ui.separator();
+
+   ui.checkbox(&mut settings.night_mode, "Night mode").on_hover_text("Compresses loud parts");
+   ui.add_enabled_ui(settings.night_mode, |ui| {
+      ui.add(Slider::new(&mut settings.night_threshold, 0.01..=1.0).logarithmic(true).prefix("Threshold "));
+      ui.add(Slider::new(&mut settings.night_ratio, 0.05..=1.0).prefix("Ratio ").custom_formatter(|r, _| format!("{:.1}:1", 1.0 / r)));
+   });
+
+   ui.separator();
+
+   ui.horizontal(|ui| {
+      ui.label("Stereo");
+      let is_wide = matches!(settings.stereo, StereoMode::Wide(_));
+      if ui.selectable_label(settings.stereo == StereoMode::Normal, "Normal").clicked() {
+         settings.stereo = StereoMode::Normal;
+      }
+      if ui.selectable_label(is_wide, "Wide").clicked() && !is_wide {
+         settings.stereo = StereoMode::Wide(0.5);
+      }
+      if ui.selectable_label(settings.stereo == StereoMode::Mono, "Mono").clicked() {
+         settings.stereo = StereoMode::Mono;
+      }
+   });
+   if let StereoMode::Wide(width) = &mut settings.stereo {
+      ui.add(Slider::new(width, 0.0..=1.0).prefix("Width "));
+   }
+
+   ui.separator();
+
+   ui.horizontal(|ui| {
+      ui.label("Loudness");
+      for mode in LoudnessMode::ALL {
+         ui.radio_value(&mut settings.loudness.mode, *mode, mode.label());
+      }
+   });
+   ui.add_enabled_ui(settings.loudness.mode != LoudnessMode::Off, |ui| {
+      ui.add(Slider::new(&mut settings.loudness.target_lufs, -31.0..=-10.0).prefix("Target ").suffix(" LUFS"));
+      ui.add(Slider::new(&mut settings.loudness.max_gain_db, 0.0..=24.0).prefix("Max boost ").suffix(" dB"));
+   });
+   ui.label(loudness_text(settings.loudness.mode, loudness));
+
+   *settings != before
+}
@@ -0,0 +1,20 @@
+use eframe::egui::{Button, Slider, Ui};
+use crate::gstreamer_internals::color_balance::ColorAdjustments;
+
+/// brightness, contrast, saturation, hue and gamma sliders, returns true if anything changed
+pub fn color_editor(ui: &mut Ui, adjustments: &mut ColorAdjustments, gamma_supported: bool) -> bool {
+   let before = *adjustments;
+
+   ui.add(Slider::new(&mut adjustments.brightness, -1.0..=1.0).prefix("Brightness "));
+   ui.add(Slider::new(&mut adjustments.contrast, 0.0..=2.0).prefix("Contrast "));
+   ui.add(Slider::new(&mut adjustments.saturation, 0.0..=2.0).prefix("Saturation "));
+   ui.add(Slider::new(&mut adjustments.hue, -1.0..=1.0).prefix("Hue ").custom_formatter(|h, _| format!("{:+.0}°", h * 180.0)));
+   ui.add_enabled(gamma_supported, Slider::new(&mut adjustments.gamma, 0.1..=4.0).logarithmic(true).prefix("Gamma "))
+       .on_disabled_hover_text("Gamma needs the gpu path");
+
+   if ui.add_enabled(!adjustments.is_identity(), Button::new("Reset")).clicked() {
+      *adjustments = ColorAdjustments::default();
+   }
+
+   *adjustments != before
+}
@@ -0,0 +1,358 @@
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use eframe::egui::epaint::Vertex;
+use eframe::egui::{pos2, vec2, Align2, Color32, CursorIcon, FontId, Mesh, Rect, Sense, Shape, Slider, Stroke, TextureId, Ui, UiBuilder};
+use gstreamer::{ClockTime, SeekFlags};
+use crate::gstreamer_internals::backend_framework::GstreamerBackendFramework;
+use crate::gui::player::{SavedSettings, VidioPlayer};
+use crate::gui::view_transform::{Orientation, ViewTransform};
+use crate::wgpu::difference::DifferencePass;
+use crate::wgpu::pack::WgpuRenderPack;
+
+/// frames apart while playing that mean the first source seeked and the second has to be locked to it again
+const PLAYING_DRIFT_FRAMES: f64 = 2.0;
+
+/// least time between relocks while playing, a seek takes a moment to land
+const RESYNC_COOLDOWN: Duration = Duration::from_millis(750);
+
+/// how often to redraw while paused
+const IDLE_REPAINT: Duration = Duration::from_millis(250);
+
+/// frame length assumed for variable framerate sources, which report none
+const FALLBACK_FRAMETIME: Duration = Duration::from_nanos(1_000_000_000 / 30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComparisonMode {
+   #[default]
+   SideBySide,
+   TopBottom,
+   /// the second source over the first right of a draggable split
+   Wipe,
+   /// alternates between the two in the same place
+   Flicker,
+   /// absolute difference between the decoded frames, amplified
+   Difference,
+}
+
+impl ComparisonMode {
+   pub const ALL: &'static [ComparisonMode] = &[Self::SideBySide, Self::TopBottom, Self::Wipe, Self::Flicker, Self::Difference];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::SideBySide => "Side by side",
+         Self::TopBottom => "Top / bottom",
+         Self::Wipe => "Wipe",
+         Self::Flicker => "Flicker",
+         Self::Difference => "Difference",
+      }
+   }
+}
+
+/// the view with its user chosen parts only, the detected black bars are each player's own
+fn shared_part(view: ViewTransform) -> ViewTransform {
+   ViewTransform {
+      black_bars: None,
+      ..view
+   }
+}
+
+/// ``backend``'s frame length, ``None`` when its framerate is variable or unknown
+fn frametime<B: GstreamerBackendFramework>(backend: &B) -> Option<Duration> {
+   Duration::try_from_secs_f64(backend.get_frametime()).ok().filter(|frametime| !frametime.is_zero())
+}
+
+/// whether flicker shows the first source ``elapsed`` after it started
+fn flicker_shows_first(elapsed: Duration, interval: Duration) -> bool {
+   (elapsed.as_millis() / interval.as_millis().max(1)) % 2 == 0
+}
+
+/// one player drawn in ``rect``, clipped to ``clip``, still updated when not ``visible``
+fn show_tile<B: GstreamerBackendFramework>(ui: &mut Ui, player: &mut VidioPlayer<B>, pack: &WgpuRenderPack, salt: &str, rect: Rect, clip: Rect, visible: bool, focused: bool) -> Result<()> {
+   let mut builder = UiBuilder::new().max_rect(rect).id_salt(salt);
+   if !visible {
+      builder = builder.invisible();
+   }
+
+   ui.allocate_new_ui(builder, |ui| {
+      ui.set_clip_rect(clip);
+      player.show_tile(ui, pack, focused)
+   }).inner?;
+   Ok(())
+}
+
+/// the video sized difference over ``rect`` where the first player drew ``uv`` of its frame,
+/// corners are mapped back through ``orientation`` since the texture isn't turned
+fn paint_difference(ui: &Ui, texture: TextureId, rect: Rect, uv: Rect, orientation: Orientation) {
+   let mut mesh = Mesh::with_texture(texture);
+   let corners = [(rect.left_top(), uv.left_top()), (rect.right_top(), uv.right_top()), (rect.right_bottom(), uv.right_bottom()), (rect.left_bottom(), uv.left_bottom())];
+   for (pos, uv) in corners {
+      mesh.vertices.push(Vertex {
+         pos,
+         uv: orientation.unapply(uv),
+         color: Color32::WHITE,
+      });
+   }
+   mesh.add_triangle(0, 1, 2);
+   mesh.add_triangle(0, 2, 3);
+   ui.painter().add(Shape::mesh(mesh));
+}
+
+fn paint_name(ui: &Ui, rect: Rect, name: &str) {
+   ui.painter().text(rect.left_top() + vec2(8.0, 8.0), Align2::LEFT_TOP, name, FontId::proportional(16.0), Color32::WHITE);
+}
+
+/// two sources played in step for comparing encodes of the same clip,
+/// the second runs on the first's clock and base time, and is only seeked to the first's position when it pauses or seeks,
+/// seeks, steps, speed, pause and the view all follow the first, which is also the one that's heard
+pub struct ComparisonView<B: GstreamerBackendFramework> {
+   first: VidioPlayer<B>,
+   second: VidioPlayer<B>,
+   pub mode: ComparisonMode,
+   /// where the wipe splits the view, 0..1 from the left
+   pub split: f32,
+   pub flicker_interval: Duration,
+   pub difference_gain: f32,
+   last_view: ViewTransform,
+   /// timecode of the first source the second was last seeked to, and when
+   last_resync: Option<(ClockTime, Instant)>,
+   flicker_started: Instant,
+   difference: Option<DifferencePass>,
+}
+
+impl<Backend: GstreamerBackendFramework> ComparisonView<Backend> {
+   pub fn new(first_uri: &str, second_uri: &str, saved_settings: SavedSettings) -> Result<Self> {
+      Ok(Self {
+         first: VidioPlayer::new_with_uri(first_uri, saved_settings.clone())?,
+         second: VidioPlayer::new_with_uri(second_uri, saved_settings)?,
+         mode: ComparisonMode::default(),
+         split: 0.5,
+         flicker_interval: Duration::from_millis(500),
+         difference_gain: 8.0,
+         last_view: ViewTransform::default(),
+         last_resync: None,
+         flicker_started: Instant::now(),
+         difference: None,
+      })
+   }
+
+   pub fn first(&self) -> &VidioPlayer<Backend> {
+      &self.first
+   }
+
+   pub fn second(&self) -> &VidioPlayer<Backend> {
+      &self.second
+   }
+
+   /// seeks both to ``to``
+   pub fn seek(&mut self, to: ClockTime, accurate: bool) -> Result<()> {
+      for player in [&mut self.first, &mut self.second] {
+         if let Some(backend) = player.backend_mut() {
+            backend.seek_timeline(to, accurate)?;
+         }
+      }
+      self.last_resync = None;
+      Ok(())
+   }
+
+   /// steps both by ``frames``, any difference left after is fixed up by pts
+   pub fn step(&mut self, frames: i32) -> Result<()> {
+      for player in [&mut self.first, &mut self.second] {
+         if let Some(backend) = player.backend_mut() {
+            backend.seek_frames(frames)?;
+         }
+      }
+      self.last_resync = None;
+      Ok(())
+   }
+
+   pub fn set_playing(&mut self, playing: bool) -> Result<()> {
+      if let Some(backend) = self.first.backend_mut() {
+         match playing {
+            true => backend.start()?,
+            false => backend.stop()?,
+         }
+      }
+      Ok(())
+   }
+
+   /// keeps the second source's clock, speed, state and position on the first's
+   fn follow(&mut self) -> Result<()> {
+      let (Some(first), Some(second)) = (self.first.backend(), self.second.backend_mut()) else {
+         return Ok(());
+      };
+
+      let mut relock = false;
+      if second.current_playback_speed() != first.current_playback_speed() {
+         second.change_playback_speed(first.current_playback_speed())?;
+         relock = true;
+      }
+      match (first.is_playing(), second.is_playing()) {
+         (true, false) => second.start()?,
+         (false, true) => second.stop()?,
+         _ => {}
+      }
+      second.follow_base_time(first);
+
+      let target = first.timecode();
+      let drift = (target.seconds_f64() - second.timecode().seconds_f64()).abs();
+      let frame = frametime(first).unwrap_or(FALLBACK_FRAMETIME).as_secs_f64();
+
+      // while playing the shared clock keeps them together, so drift only comes from the first seeking or looping,
+      // paused relocks once per position so stepping and seeking line both up by pts
+      relock |= match first.is_playing() {
+         true => drift > frame * PLAYING_DRIFT_FRAMES && self.last_resync.map_or(true, |(_, at)| at.elapsed() > RESYNC_COOLDOWN),
+         false => self.last_resync.map_or(true, |(last, _)| last != target),
+      };
+      // fails until the first has prerolled, it's tried again next frame
+      if relock {
+         match second.lock_to(first) {
+            Ok(()) => self.last_resync = Some((target, Instant::now())),
+            Err(err) => println!("Couldn't lock the second source {err}"),
+         }
+      }
+      Ok(())
+   }
+
+   /// copies zoom, pan, crop, aspect and orientation from whichever player they were changed on to the other
+   fn sync_views(&mut self) {
+      let (first, second) = (shared_part(self.first.view()), shared_part(self.second.view()));
+      let changed = match (first != self.last_view, second != self.last_view) {
+         (true, _) => first,
+         (false, true) => second,
+         (false, false) => return,
+      };
+
+      for player in [&mut self.first, &mut self.second] {
+         let black_bars = player.view().black_bars;
+         player.set_view(ViewTransform {
+            black_bars,
+            ..changed
+         });
+      }
+      self.last_view = changed;
+   }
+
+   fn toolbar(&mut self, ui: &mut Ui) {
+      ui.horizontal(|ui| {
+         for mode in ComparisonMode::ALL {
+            ui.radio_value(&mut self.mode, *mode, mode.label());
+         }
+
+         ui.separator();
+         let playing = self.first.backend().is_some_and(|backend| backend.is_playing());
+         let mut result = Ok(());
+         if ui.button("<").on_hover_text("Previous frame").clicked() {
+            result = self.step(-1);
+         }
+         if ui.button(if playing { "Pause" } else { "Play" }).clicked() {
+            result = self.set_playing(!playing);
+         }
+         if ui.button(">").on_hover_text("Next frame").clicked() {
+            result = self.step(1);
+         }
+         if let Err(err) = result {
+            println!("Comparison control failed {err}");
+         }
+
+         match self.mode {
+            ComparisonMode::Flicker => {
+               let mut millis = self.flicker_interval.as_millis() as u64;
+               ui.add(Slider::new(&mut millis, 100..=2000).prefix("Every ").suffix(" ms"));
+               self.flicker_interval = Duration::from_millis(millis);
+            }
+            ComparisonMode::Difference => {
+               ui.add(Slider::new(&mut self.difference_gain, 1.0..=64.0).logarithmic(true).prefix("Gain ×"));
+            }
+            _ => {}
+         }
+      });
+   }
+
+   fn wipe_handle(&mut self, ui: &mut Ui, area: Rect) {
+      let x = area.left() + area.width() * self.split;
+      let handle = Rect::from_center_size(pos2(x, area.center().y), vec2(12.0, area.height()));
+      let resp = ui.allocate_rect(handle, Sense::drag()).on_hover_cursor(CursorIcon::ResizeHorizontal);
+      if let (true, Some(pointer)) = (resp.dragged(), resp.interact_pointer_pos()) {
+         self.split = ((pointer.x - area.left()) / area.width()).clamp(0.0, 1.0);
+      }
+      ui.painter().vline(x, area.y_range(), Stroke::new(2.0, Color32::WHITE));
+   }
+
+   /// the toolbar then both sources laid out by ``mode``
+   pub fn show<R: Into<WgpuRenderPack>>(&mut self, ui: &mut Ui, in_pack: R) -> Result<()> {
+      let pack: WgpuRenderPack = in_pack.into();
+      self.toolbar(ui);
+      self.follow()?;
+
+      let area = ui.available_rect_before_wrap();
+      match self.mode {
+         ComparisonMode::SideBySide | ComparisonMode::TopBottom => {
+            let (a, b) = match self.mode {
+               ComparisonMode::SideBySide => area.split_left_right_at_fraction(0.5),
+               _ => area.split_top_bottom_at_fraction(0.5),
+            };
+            show_tile(ui, &mut self.first, &pack, "comparison first", a, a, true, true)?;
+            show_tile(ui, &mut self.second, &pack, "comparison second", b, b, true, false)?;
+            paint_name(ui, a, "A");
+            paint_name(ui, b, "B");
+         }
+         ComparisonMode::Wipe => {
+            let (_, right) = area.split_left_right_at_fraction(self.split);
+            show_tile(ui, &mut self.first, &pack, "comparison first", area, area, true, true)?;
+            show_tile(ui, &mut self.second, &pack, "comparison second", area, right, true, false)?;
+            self.wipe_handle(ui, area);
+            paint_name(ui, area, "A");
+            paint_name(ui, right, "B");
+         }
+         ComparisonMode::Flicker => {
+            let first_shown = flicker_shows_first(self.flicker_started.elapsed(), self.flicker_interval);
+            show_tile(ui, &mut self.first, &pack, "comparison first", area, area, first_shown, true)?;
+            show_tile(ui, &mut self.second, &pack, "comparison second", area, area, !first_shown, false)?;
+            paint_name(ui, area, if first_shown { "A" } else { "B" });
+         }
+         ComparisonMode::Difference => {
+            show_tile(ui, &mut self.first, &pack, "comparison first", area, area, true, true)?;
+            show_tile(ui, &mut self.second, &pack, "comparison second", area, area, false, false)?;
+
+            let difference = self.difference.get_or_insert_with(|| DifferencePass::new(&pack.device));
+            let texture = difference.update(&pack, self.first.display_texture(), self.second.display_texture(), self.difference_gain);
+            if let (Some(texture), Some(rect)) = (texture, self.first.video_rect()) {
+               paint_difference(ui, texture, rect, self.first.video_uv(), self.first.view().orientation);
+            }
+            paint_name(ui, area, "|A - B|");
+         }
+      }
+
+      if self.mode != ComparisonMode::Difference {
+         if let Some(mut difference) = self.difference.take() {
+            difference.free(&pack);
+         }
+      }
+
+      self.sync_views();
+
+      let frametime = self.first
+          .backend()
+          .filter(|backend| backend.is_playing())
+          .and_then(frametime);
+      let repaint = match self.mode {
+         ComparisonMode::Flicker => frametime.unwrap_or(IDLE_REPAINT).min(self.flicker_interval),
+         _ => frametime.unwrap_or(IDLE_REPAINT),
+      };
+      ui.ctx().request_repaint_after(repaint);
+      Ok(())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn flicker_alternates() {
+      let interval = Duration::from_millis(500);
+      assert!(flicker_shows_first(Duration::from_millis(499), interval));
+      assert!(!flicker_shows_first(Duration::from_millis(500), interval));
+      assert!(flicker_shows_first(Duration::from_millis(1200), interval));
+   }
+}
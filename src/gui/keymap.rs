@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{bail, Context};
+use eframe::egui::{Color32, Event, Grid, InputState, Key, Modifiers, Ui};
+use serde::{Deserialize, Serialize};
+
+/// everything the player can be told to do from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlayerAction {
+   TogglePause,
+   SeekForward,
+   SeekBackward,
+   SeekForwardLong,
+   SeekBackwardLong,
+   FrameStep,
+   FrameBackStep,
+   SpeedUp,
+   SpeedDown,
+   SpeedReset,
+   VolumeUp,
+   VolumeDown,
+   Mute,
+   NextAudioTrack,
+   PreviousAudioTrack,
+   CycleSubtitles,
+   ToggleSubtitles,
+   NextChapter,
+   PreviousChapter,
+   ToggleFullscreen,
+   ExitFullscreen,
+   Screenshot,
+   AbLoop,
+   MediaInfo,
+   ZoomIn,
+   ZoomOut,
+   PanLeft,
+   PanRight,
+   PanUp,
+   PanDown,
+   ResetView,
+   CycleAspect,
+   CycleCrop,
+}
+
+impl PlayerAction {
+   pub const ALL: &'static [PlayerAction] = &[
+      Self::TogglePause,
+      Self::SeekForward,
+      Self::SeekBackward,
+      Self::SeekForwardLong,
+      Self::SeekBackwardLong,
+      Self::FrameStep,
+      Self::FrameBackStep,
+      Self::SpeedUp,
+      Self::SpeedDown,
+      Self::SpeedReset,
+      Self::VolumeUp,
+      Self::VolumeDown,
+      Self::Mute,
+      Self::NextAudioTrack,
+      Self::PreviousAudioTrack,
+      Self::CycleSubtitles,
+      Self::ToggleSubtitles,
+      Self::NextChapter,
+      Self::PreviousChapter,
+      Self::ToggleFullscreen,
+      Self::ExitFullscreen,
+      Self::Screenshot,
+      Self::AbLoop,
+      Self::MediaInfo,
+      Self::ZoomIn,
+      Self::ZoomOut,
+      Self::PanLeft,
+      Self::PanRight,
+      Self::PanUp,
+      Self::PanDown,
+      Self::ResetView,
+      Self::CycleAspect,
+      Self::CycleCrop,
+   ];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::TogglePause => "Play / pause",
+         Self::SeekForward => "Seek +5s",
+         Self::SeekBackward => "Seek -5s",
+         Self::SeekForwardLong => "Seek +30s",
+         Self::SeekBackwardLong => "Seek -30s",
+         Self::FrameStep => "Frame step",
+         Self::FrameBackStep => "Frame back step",
+         Self::SpeedUp => "Speed up",
+         Self::SpeedDown => "Speed down",
+         Self::SpeedReset => "Reset speed",
+         Self::VolumeUp => "Volume up",
+         Self::VolumeDown => "Volume down",
+         Self::Mute => "Mute",
+         Self::NextAudioTrack => "Next audio track",
+         Self::PreviousAudioTrack => "Previous audio track",
+         Self::CycleSubtitles => "Cycle subtitles",
+         Self::ToggleSubtitles => "Toggle subtitles",
+         Self::NextChapter => "Next chapter",
+         Self::PreviousChapter => "Previous chapter",
+         Self::ToggleFullscreen => "Toggle fullscreen",
+         Self::ExitFullscreen => "Exit fullscreen",
+         Self::Screenshot => "Screenshot",
+         Self::AbLoop => "A-B loop",
+         Self::MediaInfo => "Media information",
+         Self::ZoomIn => "Zoom in",
+         Self::ZoomOut => "Zoom out",
+         Self::PanLeft => "Pan left",
+         Self::PanRight => "Pan right",
+         Self::PanUp => "Pan up",
+         Self::PanDown => "Pan down",
+         Self::ResetView => "Reset zoom and pan",
+         Self::CycleAspect => "Cycle aspect ratio",
+         Self::CycleCrop => "Cycle crop",
+      }
+   }
+}
+
+/// a key plus the exact modifiers that have to be held,
+/// stored as mpv style text like ``Ctrl+Shift+Right``
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+   pub key: Key,
+   pub modifiers: Modifiers,
+}
+
+impl KeyBinding {
+   pub fn matches(&self, key: Key, modifiers: Modifiers) -> bool {
+      self.key == key && modifiers.matches_exact(self.modifiers)
+   }
+}
+
+impl Display for KeyBinding {
+   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+      let m = self.modifiers;
+      if m.ctrl || m.command { write!(f, "Ctrl+")?; }
+      if m.alt { write!(f, "Alt+")?; }
+      if m.shift { write!(f, "Shift+")?; }
+      write!(f, "{}", self.key.name())
+   }
+}
+
+impl TryFrom<String> for KeyBinding {
+   type Error = anyhow::Error;
+
+   fn try_from(value: String) -> Result<Self, Self::Error> {
+      // split from the right so a bound "+" key still parses
+      let (mods, key) = match value.rsplit_once('+') {
+         Some((mods, "")) => (mods.strip_suffix('+').unwrap_or(""), "+"),
+         Some((mods, key)) => (mods, key),
+         None => ("", value.as_str()),
+      };
+
+      let mut modifiers = Modifiers::NONE;
+      for name in mods.split('+').filter(|m| !m.is_empty()) {
+         match name.to_lowercase().as_str() {
+            "ctrl" => modifiers = modifiers | Modifiers::COMMAND,
+            "alt" => modifiers = modifiers | Modifiers::ALT,
+            "shift" => modifiers = modifiers | Modifiers::SHIFT,
+            other => bail!("Unknown modifier {other}"),
+         }
+      }
+
+      let key = Key::from_name(key).with_context(|| format!("Unknown key {key}"))?;
+      Ok(Self { key, modifiers })
+   }
+}
+
+impl From<KeyBinding> for String {
+   fn from(binding: KeyBinding) -> Self {
+      binding.to_string()
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keymap {
+   pub bindings: HashMap<PlayerAction, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+   fn default() -> Self {
+      Self::mpv_defaults()
+   }
+}
+
+impl Keymap {
+   /// the same keys mpv uses out of the box where egui can see them
+   pub fn mpv_defaults() -> Self {
+      use PlayerAction::*;
+
+      let defaults: &[(PlayerAction, &[&str])] = &[
+         (TogglePause, &["Space", "P"]),
+         (SeekForward, &["Right"]),
+         (SeekBackward, &["Left"]),
+         (SeekForwardLong, &["Up"]),
+         (SeekBackwardLong, &["Down"]),
+         (FrameStep, &["."]),
+         (FrameBackStep, &[","]),
+         (SpeedUp, &["]"]),
+         (SpeedDown, &["["]),
+         (SpeedReset, &["Backspace"]),
+         (VolumeUp, &["0"]),
+         (VolumeDown, &["9"]),
+         (Mute, &["M"]),
+         (NextAudioTrack, &["Shift+3"]),
+         (PreviousAudioTrack, &["Ctrl+Shift+3"]),
+         (CycleSubtitles, &["J"]),
+         (ToggleSubtitles, &["V"]),
+         (NextChapter, &["PageUp"]),
+         (PreviousChapter, &["PageDown"]),
+         (ToggleFullscreen, &["F"]),
+         (ExitFullscreen, &["Escape"]),
+         (Screenshot, &["S"]),
+         (AbLoop, &["L"]),
+         (MediaInfo, &["I"]),
+         (ZoomIn, &["Alt+=", "Alt++"]),
+         (ZoomOut, &["Alt+-"]),
+         (PanLeft, &["Alt+Left"]),
+         (PanRight, &["Alt+Right"]),
+         (PanUp, &["Alt+Up"]),
+         (PanDown, &["Alt+Down"]),
+         (ResetView, &["Alt+Backspace"]),
+         (CycleAspect, &["Shift+A"]),
+         (CycleCrop, &["C"]),
+      ];
+
+      let bindings = defaults
+          .iter()
+          .map(|(action, keys)| {
+             let keys = keys.iter().map(|k| KeyBinding::try_from(k.to_string()).unwrap()).collect();
+             (*action, keys)
+          })
+          .collect();
+
+      Self { bindings }
+   }
+
+   /// gives actions added since the keymap was saved their default keys
+   pub fn fill_missing_defaults(&mut self) {
+      for (action, keys) in Self::mpv_defaults().bindings {
+         self.bindings.entry(action).or_insert(keys);
+      }
+   }
+
+   pub fn bindings_for(&self, action: PlayerAction) -> &[KeyBinding] {
+      self.bindings.get(&action).map_or(&[], |b| b.as_slice())
+   }
+
+   /// actions whose bindings were pressed this frame, in press order
+   pub fn pressed_actions(&self, input: &InputState) -> Vec<PlayerAction> {
+      let mut out = vec![];
+      for event in &input.events {
+         if let Event::Key { key, pressed: true, modifiers, .. } = event {
+            for action in PlayerAction::ALL {
+               if self.bindings_for(*action).iter().any(|b| b.matches(*key, *modifiers)) {
+                  out.push(*action);
+               }
+            }
+         }
+      }
+      out
+   }
+
+   /// every binding shared by more than one action
+   pub fn conflicts(&self) -> HashMap<KeyBinding, Vec<PlayerAction>> {
+      let mut users: HashMap<KeyBinding, Vec<PlayerAction>> = HashMap::new();
+      for action in PlayerAction::ALL {
+         for binding in self.bindings_for(*action) {
+            users.entry(*binding).or_default().push(*action);
+         }
+      }
+      users.retain(|_, actions| actions.len() > 1);
+      users
+   }
+}
+
+/// rebinding table for the settings window,
+/// ``capturing`` holds the action waiting for its next key press
+pub fn keymap_editor(ui: &mut Ui, keymap: &mut Keymap, capturing: &mut Option<PlayerAction>) {
+   if let Some(action) = *capturing {
+      let pressed = ui.ctx().input(|i| {
+         i.events.iter().find_map(|e| match e {
+            Event::Key { key, pressed: true, modifiers, .. } => Some(KeyBinding { key: *key, modifiers: *modifiers }),
+            _ => None,
+         })
+      });
+
+      if let Some(binding) = pressed {
+         // keeps the player from also acting on the key being bound
+         ui.ctx().input_mut(|i| i.consume_key(binding.modifiers, binding.key));
+
+         // round trips through text so ctrl and command compare the same as loaded bindings
+         let binding = KeyBinding::try_from(binding.to_string()).unwrap_or(binding);
+
+         if binding.key != Key::Escape || !binding.modifiers.is_none() {
+            let list = keymap.bindings.entry(action).or_default();
+            if !list.contains(&binding) {
+               list.push(binding);
+            }
+         }
+         *capturing = None;
+      }
+   }
+
+   let conflicts = keymap.conflicts();
+
+   ui.horizontal(|ui| {
+      if ui.button("Reset to mpv defaults").clicked() {
+         *keymap = Keymap::mpv_defaults();
+      }
+      if !conflicts.is_empty() {
+         ui.colored_label(Color32::RED, format!("{} conflicting bindings", conflicts.len()));
+      }
+   });
+
+   Grid::new("keymap editor").striped(true).num_columns(2).show(ui, |ui| {
+      for action in PlayerAction::ALL {
+         ui.label(action.label());
+
+         ui.horizontal(|ui| {
+            let mut remove = None;
+
+            for (i, binding) in keymap.bindings_for(*action).iter().enumerate() {
+               let text = binding.to_string();
+               let button = match conflicts.get(binding) {
+                  Some(users) => {
+                     let others = users
+                         .iter()
+                         .filter(|a| *a != action)
+                         .map(|a| a.label())
+                         .collect::<Vec<_>>()
+                         .join(", ");
+                     ui.button(eframe::egui::RichText::new(text).color(Color32::RED))
+                         .on_hover_text(format!("Also bound to {others}, click to remove"))
+                  }
+                  None => ui.button(text).on_hover_text("Click to remove"),
+               };
+
+               if button.clicked() {
+                  remove = Some(i);
+               }
+            }
+
+            if let Some(i) = remove {
+               keymap.bindings.entry(*action).or_default().remove(i);
+            }
+
+            match *capturing == Some(*action) {
+               true => { ui.label("press a key, esc to cancel"); }
+               false => {
+                  if ui.button("+").clicked() {
+                     *capturing = Some(*action);
+                  }
+               }
+            }
+         });
+
+         ui.end_row();
+      }
+   });
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn binding_text_round_trip() {
+      for text in ["Space", "Ctrl+Shift+Right", "Alt+F", "Shift+3", "]", "Ctrl++"] {
+         let binding = KeyBinding::try_from(text.to_string()).unwrap();
+         let back = KeyBinding::try_from(binding.to_string()).unwrap();
+         assert_eq!(binding, back, "{text}");
+      }
+
+      assert!(KeyBinding::try_from("Hyper+A".to_string()).is_err());
+   }
+
+   #[test]
+   fn defaults_have_no_conflicts() {
+      assert!(Keymap::mpv_defaults().conflicts().is_empty());
+   }
+}
@@ -0,0 +1,163 @@
+use eframe::egui::{CollapsingHeader, Context, Grid, Id, ScrollArea, Ui, Window};
+use serde::Serialize;
+use crate::gstreamer_internals::backend_framework::LiveStats;
+use crate::gstreamer_internals::prober::{AudioStream, Probe, SubtitleStream, VideoStream};
+use crate::gui::timeline::format_timecode;
+
+type Rows = Vec<(&'static str, String)>;
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+   match value {
+      None => "-".to_string(),
+      Some(v) => v.to_string(),
+   }
+}
+
+fn kbps(bitrate: &Option<u32>) -> String {
+   match bitrate {
+      None | Some(0) => "-".to_string(),
+      Some(b) => format!("{} kb/s", b / 1000),
+   }
+}
+
+fn general_rows(probe: &Probe) -> Rows {
+   vec![
+      ("uri", probe.uri.clone()),
+      ("container", opt(&probe.container)),
+      ("duration", opt(&probe.duration.map(|d| format_timecode(d.seconds_f64())))),
+      ("seekable", probe.seekable.to_string()),
+      ("live", probe.live.to_string()),
+      ("chapters", probe.chapters.len().to_string()),
+   ]
+}
+
+fn video_rows(stream: &VideoStream) -> Rows {
+   vec![
+      ("title", opt(&stream.name)),
+      ("codec", opt(&stream.codec)),
+      ("bitrate", kbps(&stream.bitrate)),
+      ("resolution", opt(&stream.resolution.map(|(w, h)| format!("{w}x{h}")))),
+      ("fps", opt(&stream.fps.map(|f| format!("{f:.3}")))),
+      ("language", opt(&stream.language)),
+      ("pixel format", opt(&stream.pixel_format)),
+      ("colorimetry", opt(&stream.colorimetry)),
+      ("hdr", opt(&stream.hdr.as_ref().map(|h| h.transfer.clone()))),
+      ("pixel aspect", format!("{}:{}", stream.pixel_aspect_ratio.0, stream.pixel_aspect_ratio.1)),
+      ("rotation", opt(&stream.rotation)),
+      ("interlaced", stream.interlaced.to_string()),
+   ]
+}
+
+fn audio_rows(stream: &AudioStream) -> Rows {
+   vec![
+      ("title", opt(&stream.name)),
+      ("codec", opt(&stream.codec)),
+      ("bitrate", kbps(&stream.bitrate)),
+      ("language", opt(&stream.language)),
+      ("channels", format!("{} ({})", stream.channels, stream.channel_layout)),
+      ("sample rate", format!("{} Hz", stream.sample_rate)),
+      ("depth", format!("{} bit", stream.depth)),
+   ]
+}
+
+fn subtitle_rows(stream: &SubtitleStream) -> Rows {
+   vec![
+      ("title", opt(&stream.name)),
+      ("codec", opt(&stream.codec)),
+      ("language", opt(&stream.language)),
+   ]
+}
+
+fn stats_rows(stats: &LiveStats) -> Rows {
+   let decoders = stats.decoders
+       .iter()
+       .map(|(name, factory)| format!("{name} ({factory})"))
+       .collect::<Vec<_>>()
+       .join(", ");
+
+   vec![
+      ("decoders", decoders),
+      ("video caps", opt(&stats.video_caps)),
+      ("audio caps", opt(&stats.audio_caps)),
+      ("rendered frames", opt(&stats.rendered_frames)),
+      ("dropped frames", opt(&stats.dropped_frames)),
+      ("buffering", opt(&stats.buffer_percent.map(|p| format!("{p}%")))),
+      ("interlace mode", opt(&stats.interlace_mode)),
+      ("deinterlacer", opt(&stats.deinterlacer.as_ref().map(|(mode, method)| format!("{method} ({mode})")))),
+   ]
+}
+
+fn rows_to_text(rows: &Rows, tags: &[(String, String)]) -> String {
+   rows.iter()
+       .map(|(label, value)| format!("{label}: {value}"))
+       .chain(tags.iter().map(|(name, value)| format!("{name}: {value}")))
+       .collect::<Vec<_>>()
+       .join("\n")
+}
+
+/// one collapsible block with a key value grid, its tags and copy buttons
+fn section<T: Serialize>(ui: &mut Ui, title: String, rows: Rows, tags: &[(String, String)], json_source: &T) {
+   CollapsingHeader::new(&title).default_open(true).show(ui, |ui| {
+      ui.horizontal(|ui| {
+         if ui.button("Copy text").clicked() {
+            ui.ctx().copy_text(rows_to_text(&rows, tags));
+         }
+         if ui.button("Copy json").clicked() {
+            match serde_json::to_string_pretty(json_source) {
+               Ok(json) => ui.ctx().copy_text(json),
+               Err(err) => println!("Couldn't serialize {title} {err}"),
+            }
+         }
+      });
+
+      Grid::new(&title).striped(true).num_columns(2).show(ui, |ui| {
+         for (label, value) in &rows {
+            ui.label(*label);
+            ui.label(value);
+            ui.end_row();
+         }
+      });
+
+      if !tags.is_empty() {
+         CollapsingHeader::new("tags").id_salt(format!("{title} tags")).show(ui, |ui| {
+            Grid::new(format!("{title} tag grid")).striped(true).num_columns(2).show(ui, |ui| {
+               for (name, value) in tags {
+                  ui.label(name);
+                  ui.label(value);
+                  ui.end_row();
+               }
+            });
+         });
+      }
+   });
+}
+
+/// the "Media information" window, everything the probe found plus live pipeline stats
+pub fn media_info_window(ctx: &Context, id: Id, open: &mut bool, probe: Option<&Probe>, stats: &LiveStats) {
+   Window::new("Media information")
+       .id(id)
+       .open(open)
+       .default_width(420.0)
+       .show(ctx, |ui| {
+          ScrollArea::vertical().show(ui, |ui| {
+             match probe {
+                None => { ui.label("Probe not finished"); }
+                Some(probe) => {
+                   section(ui, "General".to_string(), general_rows(probe), &probe.tags, probe);
+
+                   for (stream, i) in &probe.video_streams {
+                      section(ui, format!("Video {i}"), video_rows(stream), &stream.tags, stream);
+                   }
+                   for (stream, i) in &probe.audio_streams {
+                      section(ui, format!("Audio {i}"), audio_rows(stream), &stream.tags, stream);
+                   }
+                   for (stream, i) in &probe.captions {
+                      section(ui, format!("Subtitle {i}"), subtitle_rows(stream), &stream.tags, stream);
+                   }
+                }
+             }
+
+             section(ui, "Live stats".to_string(), stats_rows(stats), &[], stats);
+          });
+       });
+}
@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+use eframe::egui::{pos2, vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Slider, Stroke, Ui};
+use serde::{Deserialize, Serialize};
+use crate::gui::timeline::format_timecode;
+
+/// how the on screen display looks, part of the saved settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OsdStyle {
+   pub enabled: bool,
+   pub font_size: f32,
+   /// seconds a message stays up before fading
+   pub timeout_secs: f32,
+   pub text_color: [u8; 3],
+   pub background_opacity: u8,
+   /// shows the title and playback clock while the mouse moves
+   pub show_info_on_mouse_move: bool,
+}
+
+impl Default for OsdStyle {
+   fn default() -> Self {
+      Self {
+         enabled: true,
+         font_size: 24.0,
+         timeout_secs: 1.5,
+         text_color: [255, 255, 255],
+         background_opacity: 160,
+         show_info_on_mouse_move: true,
+      }
+   }
+}
+
+impl OsdStyle {
+   pub fn settings_ui(&mut self, ui: &mut Ui) {
+      ui.checkbox(&mut self.enabled, "Enabled");
+      ui.add(Slider::new(&mut self.font_size, 10.0..=64.0).prefix("Font size "));
+      ui.add(Slider::new(&mut self.timeout_secs, 0.3..=10.0).prefix("Timeout ").suffix("s"));
+      ui.add(Slider::new(&mut self.background_opacity, 0..=255).prefix("Background "));
+      ui.horizontal(|ui| {
+         ui.color_edit_button_srgb(&mut self.text_color);
+         ui.label("Text colour");
+      });
+      ui.checkbox(&mut self.show_info_on_mouse_move, "Show title and time when the mouse moves");
+   }
+}
+
+/// what the osd needs to know about playback when painting
+pub struct OsdInfo {
+   pub title: String,
+   pub position: f64,
+   pub duration: f64,
+}
+
+/// transient feedback painted over the video, messages fade out after ``OsdStyle::timeout_secs``
+#[derive(Default)]
+pub struct Osd {
+   message: Option<(String, Instant)>,
+   seek_bar_shown: Option<Instant>,
+   info_shown: Option<Instant>,
+}
+
+impl Osd {
+   pub fn show_message(&mut self, text: impl Into<String>) {
+      self.message = Some((text.into(), Instant::now()));
+   }
+
+   /// shows the position bar, used for keyboard seeks where the timeline may be hidden
+   pub fn show_seek_bar(&mut self) {
+      self.seek_bar_shown = Some(Instant::now());
+   }
+
+   pub fn mouse_moved(&mut self) {
+      self.info_shown = Some(Instant::now());
+   }
+
+   /// 1.0 while fresh, fading to 0.0 over the last half second of the timeout
+   fn opacity(shown: Option<Instant>, timeout: Duration) -> f32 {
+      let Some(shown) = shown else { return 0.0 };
+      let remaining = timeout.as_secs_f32() - shown.elapsed().as_secs_f32();
+      (remaining / 0.5).clamp(0.0, 1.0)
+   }
+
+   pub fn paint(&self, painter: &Painter, rect: Rect, style: &OsdStyle, info: &OsdInfo) {
+      if !style.enabled {
+         return;
+      }
+
+      // a negative or nan timeout from a hand edited settings file shows nothing
+      let timeout = Duration::try_from_secs_f32(style.timeout_secs).unwrap_or(Duration::ZERO);
+      let margin = style.font_size * 0.75;
+
+      if let Some((text, shown)) = &self.message {
+         let opacity = Self::opacity(Some(*shown), timeout);
+         if opacity > 0.0 {
+            Self::text_box(painter, rect.left_top() + vec2(margin, margin), Align2::LEFT_TOP, text, style, opacity);
+         }
+      }
+
+      let info_opacity = match style.show_info_on_mouse_move {
+         true => Self::opacity(self.info_shown, timeout),
+         false => 0.0,
+      };
+      if info_opacity > 0.0 {
+         Self::text_box(painter, rect.center_top() + vec2(0.0, margin), Align2::CENTER_TOP, &info.title, style, info_opacity);
+
+         let clock = format!("{} / {}", format_timecode(info.position), format_timecode(info.duration));
+         Self::text_box(painter, rect.right_top() + vec2(-margin, margin), Align2::RIGHT_TOP, &clock, style, info_opacity);
+      }
+
+      let seek_opacity = Self::opacity(self.seek_bar_shown, timeout);
+      if seek_opacity > 0.0 {
+         self.paint_seek_bar(painter, rect, style, info, seek_opacity);
+      }
+   }
+
+   fn colors(style: &OsdStyle, opacity: f32) -> (Color32, Color32) {
+      let [r, g, b] = style.text_color;
+      let text = Color32::from_rgba_unmultiplied(r, g, b, (255.0 * opacity) as u8);
+      let background = Color32::from_black_alpha((style.background_opacity as f32 * opacity) as u8);
+      (text, background)
+   }
+
+   fn text_box(painter: &Painter, anchor: Pos2, align: Align2, text: &str, style: &OsdStyle, opacity: f32) {
+      let (text_color, background) = Self::colors(style, opacity);
+      let galley = painter.layout_no_wrap(text.to_string(), FontId::proportional(style.font_size), text_color);
+
+      let text_rect = align.anchor_size(anchor, galley.size());
+      painter.rect_filled(text_rect.expand(style.font_size * 0.25), 4.0, background);
+      painter.galley(text_rect.min, galley, text_color);
+   }
+
+   fn paint_seek_bar(&self, painter: &Painter, rect: Rect, style: &OsdStyle, info: &OsdInfo, opacity: f32) {
+      let (text_color, background) = Self::colors(style, opacity);
+
+      let width = rect.width() * 0.6;
+      let height = style.font_size * 0.6;
+      let bar = Rect::from_center_size(pos2(rect.center().x, rect.bottom() - style.font_size * 3.0), vec2(width, height));
+
+      let fraction = match info.duration > 0.0 {
+         true => (info.position / info.duration).clamp(0.0, 1.0) as f32,
+         false => 0.0,
+      };
+
+      painter.rect_filled(bar.expand(3.0), 4.0, background);
+      painter.rect_stroke(bar, 2.0, Stroke::new(1.0, text_color));
+      painter.rect_filled(Rect::from_min_size(bar.min, vec2(width * fraction, height)), 2.0, text_color);
+
+      let clock = format!("{} / {}", format_timecode(info.position), format_timecode(info.duration));
+      Self::text_box(painter, bar.center_top() - vec2(0.0, height), Align2::CENTER_BOTTOM, &clock, style, opacity);
+   }
+}
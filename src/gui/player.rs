@@ -1,25 +1,200 @@
+use crate::fraction_to_f64;
 use crate::wgpu::display_texture::WgpuEguiDisplayTexture;
 use crate::wgpu::pack::WgpuRenderPack;
-use anyhow::Result;
+use crate::wgpu::scaling::Scaler;
+use crate::wgpu::shader_chain::{BuiltinShader, ChainEntry, ChainShader};
+use crate::wgpu::tone_map::{ToneMapper, ToneMapping, DEFAULT_HDR_PEAK, DEFAULT_SDR_WHITE};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use anyhow::{Context, Result};
 use eframe::egui;
 use eframe::egui::panel::TopBottomSide;
-use eframe::egui::{CentralPanel, Frame, ImageSource, Key, Rect, Response, Sense, Slider, TopBottomPanel, Ui, UiBuilder, ViewportCommand};
+use eframe::egui::{pos2, Button, CentralPanel, CollapsingHeader, Color32, ColorImage, CursorIcon, DragValue, Frame, Id, ImageSource, Rect, Response, Sense, Slider, TextureHandle, TextureOptions, TopBottomPanel, Ui, UiBuilder, Vec2, ViewportCommand, Window};
 use eframe::egui::load::SizedTexture;
-use gstreamer::{ClockTime};
+use gstreamer::{ClockTime, SeekFlags};
+use gstreamer_video::VideoInterlaceMode;
 use lazy_bastard::lazy_bastard;
-use crate::gstreamer_internals::backend_framework::{GstreamerBackendFramework, PlayFlags};
+use serde::{Deserialize, Serialize};
+use crate::gstreamer_internals::audio_filters::AudioFilterSettings;
+use crate::gstreamer_internals::black_bars::BlackBarDetector;
+use crate::gstreamer_internals::backend_framework::{BackendOptions, GstreamerBackendFramework, PlayFlags};
+use crate::gstreamer_internals::color_balance::ColorAdjustments;
+use crate::gstreamer_internals::deinterlace::{DeinterlaceMethod, DeinterlaceMode, DeinterlaceSettings};
+use crate::gstreamer_internals::probe_cache::ProbeCache;
+use crate::gstreamer_internals::visualisation::{decode_image, DecodedImage};
+use crate::gstreamer_internals::volume::{format_gain, VolumeCurve};
+use crate::gui::audio_filters::audio_filters_editor;
+use crate::gui::color::color_editor;
+use crate::gui::keymap::{keymap_editor, Keymap, PlayerAction};
+use crate::gui::media_info::media_info_window;
+use crate::gui::osd::{Osd, OsdInfo, OsdStyle};
+use crate::gui::player_grid::decode_limit;
+use crate::gui::shader_chain::shader_chain_editor;
+use crate::gui::timeline::{format_timecode, Timeline};
+use crate::gui::view_transform::{AspectOverride, BlackBarCrop, CropPreset, Orientation, ViewTransform, ZOOM_RANGE};
+use crate::gui::visualisation::{level_meters, paint_cover, paint_oscilloscope, paint_spectrum, AudioVisual};
+
+/// zoom factor per zoom key press
+const ZOOM_STEP: f32 = 1.1;
+/// fraction of the video moved per pan key press
+const PAN_STEP: f32 = 0.05;
+
+/// settings that survive restarts, ``load`` and ``save`` use ``settings.json`` in the config dir
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSettings {
+   /// linear gain, 1.0 leaves the audio as it is
+   pub volume: f64,
+   pub muted: bool,
+   /// loudest the volume slider goes, 2.0 is +6 dB of boost
+   pub max_volume: f64,
+   pub volume_curve: VolumeCurve,
+   pub hardware_volume: bool,
+   pub audio_filters: AudioFilterSettings,
+   /// shown in place of the video for audio only files
+   pub audio_visual: AudioVisual,
+   /// factory used when ``audio_visual`` is ``Plugin``
+   pub vis_plugin: String,
+   /// peak and rms per channel next to the timeline
+   pub show_level_meters: bool,
+   /// draws the audio waveform behind the timeline, the first time for a file it's scanned in the background
+   pub timeline_waveform: bool,
+   /// used for every file without its own entry in ``file_colors``
+   pub color: ColorAdjustments,
+   /// adjustments remembered per uri
+   pub file_colors: HashMap<String, ColorAdjustments>,
+   /// applies the colour adjustments in a shader, off uses gstreamer's colour balance on the cpu
+   pub color_on_gpu: bool,
+   /// post process shaders run in order after the colour adjustments
+   pub shader_chain: Vec<ChainEntry>,
+   /// resizes the video to its on screen pixel size
+   pub scaler: Scaler,
+   /// how pq and hlg video is brought down to sdr
+   pub tone_mapper: ToneMapper,
+   /// cd/m² hdr content shows sdr white at
+   pub sdr_white_nits: f32,
+   /// looks for letterbox and pillarbox bars while playing
+   pub black_bar_crop: BlackBarCrop,
+   pub deinterlace: DeinterlaceSettings,
+   pub scroll_speed_mult: f32,
+   pub auto_skip_op_ed: bool,
+   pub probe_timeout_secs: f64,
+   pub use_probe_cache: bool,
+   pub keymap: Keymap,
+   pub osd: OsdStyle,
+   /// hides the menu and bottom bar in windowed mode too, using the fullscreen overlay instead
+   pub minimal_ui: bool,
+   /// seconds without mouse movement before the overlay controls and cursor hide
+   pub controls_hide_secs: f32,
+}
 
-lazy_bastard!(
-   pub struct SavedSettings {
-      volume: f32 => 0.5,
-      scroll_speed_mult: f32 => 5.0,
+impl Default for SavedSettings {
+   fn default() -> Self {
+      Self {
+         volume: 1.0,
+         muted: false,
+         max_volume: 2.0,
+         volume_curve: VolumeCurve::Cubic,
+         hardware_volume: false,
+         audio_filters: AudioFilterSettings::default(),
+         audio_visual: AudioVisual::Spectrum,
+         vis_plugin: "goom".to_string(),
+         show_level_meters: false,
+         timeline_waveform: false,
+         color: ColorAdjustments::default(),
+         file_colors: HashMap::new(),
+         color_on_gpu: true,
+         shader_chain: vec![],
+         scaler: Scaler::default(),
+         tone_mapper: ToneMapper::default(),
+         sdr_white_nits: DEFAULT_SDR_WHITE,
+         black_bar_crop: BlackBarCrop::default(),
+         deinterlace: DeinterlaceSettings::default(),
+         scroll_speed_mult: 5.0,
+         auto_skip_op_ed: false,
+         probe_timeout_secs: 5.0,
+         use_probe_cache: true,
+         keymap: Keymap::mpv_defaults(),
+         osd: OsdStyle::default(),
+         minimal_ui: false,
+         controls_hide_secs: 2.0,
+      }
+   }
+}
+
+impl SavedSettings {
+   fn path() -> Result<PathBuf> {
+      let dir = dirs::config_dir().context("No config directory")?.join("vid_v2");
+      std::fs::create_dir_all(&dir)?;
+      Ok(dir.join("settings.json"))
    }
-);
+
+   /// the saved settings, or the defaults if there are none or they don't parse
+   pub fn load() -> Self {
+      let loaded = Self::path()
+          .and_then(|path| Ok(std::fs::read(path)?))
+          .and_then(|bytes| Ok(serde_json::from_slice::<SavedSettings>(&bytes)?));
+
+      match loaded {
+         Ok(mut settings) => {
+            settings.keymap.fill_missing_defaults();
+            // the slider's range, anything else from a hand edited file would panic as a ``ClockTime``
+            settings.probe_timeout_secs = match settings.probe_timeout_secs.is_finite() {
+               true => settings.probe_timeout_secs.clamp(1.0, 60.0),
+               false => Self::default().probe_timeout_secs,
+            };
+            settings
+         }
+         Err(err) => {
+            println!("Using default settings, couldn't load saved ones {err}");
+            Self::default()
+         }
+      }
+   }
+
+   pub fn save(&self) -> Result<()> {
+      std::fs::write(Self::path()?, serde_json::to_vec_pretty(self)?)?;
+      Ok(())
+   }
+}
 
 lazy_bastard!(
    pub struct TempSettings {
       is_fullscreen: bool => false,
       queued_fullscreen_state: bool => false,
+      loop_a: Option<f64> => None,
+      loop_b: Option<f64> => None,
+      loop_repeats: u32 => 0,
+      last_chapter: Option<usize> => None,
+      show_media_info: bool => false,
+      show_settings: bool => false,
+      rebind_capture: Option<PlayerAction> => None,
+      last_mouse_move: Option<Instant> => None,
+      cover_art_failed: bool => false,
+      applied_vis_plugin: Option<String> => None,
+      // the picked plugin couldn't be set, the spectrum shows instead
+      vis_plugin_failed: bool => false,
+      current_uri: Option<String> => None,
+      applied_color_balance: Option<ColorAdjustments> => None,
+      display_pixels: Option<(u32, u32)> => None,
+      // uv of the frame on screen, [x, y, width, height]
+      display_uv: [f32; 4] => [0.0, 0.0, 1.0, 1.0],
+      view: ViewTransform => ViewTransform::default(),
+      // (view, fitted size) from the last layout, for turning pointer movement into pan
+      view_area: Option<(Rect, Vec2)> => None,
+      // the detected bars were offered or cropped already for this file
+      black_bars_handled: bool => false,
+      // read from the probe once it's ready, ``view.orientation`` starts from it
+      tag_orientation: Option<Orientation> => None,
+      applied_deinterlace: Option<DeinterlaceSettings> => None,
+      // ``Some(focused)`` while shown as a ``PlayerGrid`` tile
+      tile_focus: Option<bool> => None,
+      // what the backend was last told to scale frames down to
+      decode_limit: Option<(u32, u32)> => None,
+      // where the video was last drawn
+      video_rect: Option<Rect> => None,
    }
 );
 
@@ -28,6 +203,11 @@ pub struct VidioPlayer<B: GstreamerBackendFramework> {
    display_texture: WgpuEguiDisplayTexture,
    saved_settings: SavedSettings,
    temp_settings: TempSettings,
+   osd: Osd,
+   cover_art: Option<TextureHandle>,
+   /// decoding off the ui thread, it can take a while for big embedded images
+   cover_art_decode: Option<JoinHandle<Result<DecodedImage>>>,
+   black_bars: Option<BlackBarDetector>,
 }
 
 /////////////////////
@@ -35,14 +215,18 @@ pub struct VidioPlayer<B: GstreamerBackendFramework> {
 /////////////////////
 impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
    pub fn new(saved_settings: SavedSettings) -> Self {
-      let backend = Backend::init(&*crate::URI_PATH_BROKO_BAD).unwrap();
-
-      Self {
-         backend: Some(backend),
+      let mut player = Self {
+         backend: None,
          display_texture: WgpuEguiDisplayTexture::empty(),
          saved_settings,
          temp_settings: TempSettings::default(),
-      }
+         osd: Osd::default(),
+         cover_art: None,
+         cover_art_decode: None,
+         black_bars: None,
+      };
+      player.open_uri(&*crate::URI_PATH_BROKO_BAD).unwrap();
+      player
    }
 
    pub fn new_with_uri(uri: &str, saved_settings: SavedSettings) -> Result<Self> {
@@ -52,7 +236,34 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
    }
 
    pub fn open_uri(&mut self, uri: &str) -> Result<()> {
-      self.backend = Some(Backend::init(uri)?);
+      let options = BackendOptions {
+         probe_timeout: ClockTime::from_seconds_f64(self.saved_settings.probe_timeout_secs),
+         use_probe_cache: self.saved_settings.use_probe_cache,
+         volume: self.saved_settings.volume,
+         muted: self.saved_settings.muted,
+         max_volume: self.saved_settings.max_volume,
+         hardware_volume: self.saved_settings.hardware_volume,
+         audio_filters: self.saved_settings.audio_filters.clone(),
+         deinterlace: self.saved_settings.deinterlace,
+      };
+      self.backend = Some(Backend::init_with_options(uri, options)?);
+
+      // per file state, a fresh backend starts with visualisation off
+      self.cover_art = None;
+      self.cover_art_decode = None;
+      self.temp_settings.cover_art_failed = false;
+      self.temp_settings.applied_vis_plugin = None;
+      self.temp_settings.vis_plugin_failed = false;
+      self.temp_settings.applied_color_balance = None;
+      self.temp_settings.view = ViewTransform::default();
+      self.temp_settings.black_bars_handled = false;
+      self.temp_settings.tag_orientation = None;
+      self.temp_settings.applied_deinterlace = Some(self.saved_settings.deinterlace);
+      self.temp_settings.tile_focus = None;
+      self.temp_settings.decode_limit = None;
+      self.temp_settings.video_rect = None;
+      self.black_bars = None;
+      self.temp_settings.current_uri = Some(uri.to_string());
       Ok(())
    }
 
@@ -77,8 +288,13 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
    ) -> Result<()> {
       if self.backend.is_some() {
          let wgpu_render_pack: WgpuRenderPack = in_pack.into();
-         self.update_frame(&wgpu_render_pack)?;
+         self.leave_tile()?;
+         self.update_state(&wgpu_render_pack)?;
          self.show_internal(ui);
+
+         // kept in step so whatever changed the volume it gets saved
+         self.saved_settings.volume = self.get_backend().get_current_volume();
+         self.saved_settings.muted = self.get_backend().is_muted();
       } else {
          ui.label("Open a vidio to do shit");
       }
@@ -87,6 +303,83 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
       Ok(())
    }
 
+   /// displays just the video filling ``ui``, for ``PlayerGrid``, with no menu bar and no repaint request,
+   /// only a ``focused`` tile takes keyboard input and is audible, ``None`` when nothing is open
+   pub fn show_tile(&mut self, ui: &mut Ui, wgpu_render_pack: &WgpuRenderPack, focused: bool) -> Result<Option<Response>> {
+      if self.backend.is_none() {
+         return Ok(None);
+      }
+
+      self.set_tile_focus(focused)?;
+
+      let pixels = ui.max_rect().size() * ui.ctx().pixels_per_point();
+      let pixels = match self.temp_settings.view.orientation.is_sideways() {
+         true => (pixels.y as u32, pixels.x as u32),
+         false => (pixels.x as u32, pixels.y as u32),
+      };
+      let limit = decode_limit(pixels);
+      if self.temp_settings.decode_limit != limit {
+         self.mut_backend().set_max_video_size(limit)?;
+         self.temp_settings.decode_limit = limit;
+      }
+
+      self.update_state(wgpu_render_pack)?;
+      self.windows(ui);
+      let resp = self.player_ui(ui, ui.max_rect());
+
+      if focused {
+         self.saved_settings.volume = self.get_backend().get_current_volume();
+         self.saved_settings.muted = self.get_backend().is_muted();
+      }
+      Ok(Some(resp))
+   }
+
+   /// takes the frames of a tile that isn't drawn, so its pipeline doesn't stall waiting on the appsink
+   pub fn update_hidden_tile(&mut self, wgpu_render_pack: &WgpuRenderPack) -> Result<()> {
+      if self.backend.is_none() {
+         return Ok(());
+      }
+
+      self.set_tile_focus(false)?;
+      self.update_state(wgpu_render_pack)
+   }
+
+   pub fn saved_settings(&self) -> &SavedSettings {
+      &self.saved_settings
+   }
+
+   pub fn backend(&self) -> Option<&Backend> {
+      self.backend.as_ref()
+   }
+
+   pub fn backend_mut(&mut self) -> Option<&mut Backend> {
+      self.backend.as_mut()
+   }
+
+   pub fn display_texture(&self) -> &WgpuEguiDisplayTexture {
+      &self.display_texture
+   }
+
+   /// zoom, pan, crop and orientation, for keeping several players lined up
+   pub fn view(&self) -> ViewTransform {
+      self.temp_settings.view
+   }
+
+   pub fn set_view(&mut self, view: ViewTransform) {
+      self.temp_settings.view = view;
+   }
+
+   /// the on screen part of the video last frame, ``None`` before the first frame or when panned out of view
+   pub fn video_rect(&self) -> Option<Rect> {
+      self.temp_settings.video_rect
+   }
+
+   /// uv of the frame drawn in ``video_rect``, after the view's orientation
+   pub fn video_uv(&self) -> Rect {
+      let [x, y, width, height] = self.temp_settings.display_uv;
+      Rect::from_min_size(pos2(x, y), Vec2::new(width, height))
+   }
+
    pub fn set_fullscreen(&mut self, to: bool) {
       if self.temp_settings.is_fullscreen != to {
          self.temp_settings.queued_fullscreen_state = to;
@@ -108,13 +401,401 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
       self.backend.as_mut().unwrap()
    }
 
+   /// unfocused tiles are muted behind the saved state's back, focusing restores it
+   fn set_tile_focus(&mut self, focused: bool) -> Result<()> {
+      if self.temp_settings.tile_focus != Some(focused) {
+         self.temp_settings.tile_focus = Some(focused);
+         let muted = !focused || self.saved_settings.muted;
+         self.mut_backend().set_muted(muted)?;
+      }
+      Ok(())
+   }
+
+   /// undoes what being a tile changed once the player is shown on its own again,
+   /// the saved mute state comes back and frames are decoded at full size
+   fn leave_tile(&mut self) -> Result<()> {
+      if self.temp_settings.tile_focus.take().is_some() {
+         let muted = self.saved_settings.muted;
+         self.mut_backend().set_muted(muted)?;
+      }
+      if self.temp_settings.decode_limit.take().is_some() {
+         self.mut_backend().set_max_video_size(None)?;
+      }
+      Ok(())
+   }
+
+   /// everything done each frame before drawing
+   fn update_state(&mut self, wgpu_render_pack: &WgpuRenderPack) -> Result<()> {
+      self.sync_post_process(wgpu_render_pack)?;
+      self.update_frame(wgpu_render_pack)?;
+      self.sync_black_bars();
+      self.sync_orientation();
+      self.auto_skip_chapters()?;
+      self.sync_ab_loop();
+      self.sync_vis_plugin();
+      self.sync_audio_analysis()?;
+      Ok(())
+   }
+
    fn update_frame(&mut self, wgpu_render_pack: &WgpuRenderPack) -> Result<()> {
       if let Ok(update) = self.backend.as_mut().unwrap().update() {
+         if self.saved_settings.black_bar_crop != BlackBarCrop::Off {
+            self.black_bars.get_or_insert_with(BlackBarDetector::new).offer(&update.frame);
+         }
          self.display_texture.create_or_update(wgpu_render_pack, update.frame)?;
       }
       Ok(())
    }
 
+   /// copies the detected bars into the view, cropping or mentioning them once per file
+   fn sync_black_bars(&mut self) {
+      if self.saved_settings.black_bar_crop == BlackBarCrop::Off {
+         self.black_bars = None;
+      }
+
+      let bars = self.black_bars
+          .as_ref()
+          .and_then(|detector| detector.detected())
+          .filter(|borders| !borders.is_empty())
+          .map(|b| Rect::from_min_max(pos2(b.left, b.top), pos2(1.0 - b.right, 1.0 - b.bottom)));
+      self.temp_settings.view.black_bars = bars;
+
+      if bars.is_none() || self.temp_settings.black_bars_handled {
+         return;
+      }
+      self.temp_settings.black_bars_handled = true;
+
+      let view = &mut self.temp_settings.view;
+      match self.saved_settings.black_bar_crop {
+         BlackBarCrop::Auto if view.crop == CropPreset::None => {
+            view.crop = CropPreset::Detected;
+            self.osd.show_message("Cropped black bars");
+         }
+         BlackBarCrop::Offer if view.crop == CropPreset::None => {
+            self.osd.show_message("Black bars found, crop them from Video > Zoom & crop");
+         }
+         _ => {}
+      }
+   }
+
+   /// the user's shaders, after the bob deinterlacer when the gpu deinterlaces the current frames
+   fn shader_chain(&self) -> Vec<ChainEntry> {
+      let interlace_mode = self.backend
+          .as_ref()
+          .and_then(|backend| backend.get_latest_vidio_info())
+          .map_or(VideoInterlaceMode::Progressive, |info| info.interlace_mode());
+
+      let bob = ChainEntry {
+         shader: ChainShader::Builtin(BuiltinShader::Bob),
+         enabled: true,
+      };
+      match self.saved_settings.deinterlace.bob_on_gpu(interlace_mode) {
+         true => std::iter::once(bob).chain(self.saved_settings.shader_chain.iter().cloned()).collect(),
+         false => self.saved_settings.shader_chain.clone(),
+      }
+   }
+
+   /// turns the view to match the stream's ``image-orientation`` tag when the probe comes in
+   fn sync_orientation(&mut self) {
+      if self.temp_settings.tag_orientation.is_some() {
+         return;
+      }
+      let backend = self.backend.as_ref().unwrap();
+      let Ok(probe) = backend.get_probe() else {
+         return;
+      };
+
+      let track = backend.get_video_track().unwrap_or(0) as usize;
+      let orientation = probe.video_streams
+          .get(track)
+          .or(probe.video_streams.first())
+          .and_then(|(stream, _)| Orientation::from_tag(stream.rotation.as_ref()?))
+          .unwrap_or_default();
+      self.temp_settings.tag_orientation = Some(orientation);
+      self.temp_settings.view.orientation = orientation;
+   }
+
+   /// the current file's own adjustments if it has any, otherwise the global ones
+   fn color_adjustments(&self) -> ColorAdjustments {
+      self.temp_settings.current_uri
+          .as_ref()
+          .and_then(|uri| self.saved_settings.file_colors.get(uri))
+          .copied()
+          .unwrap_or(self.saved_settings.color)
+   }
+
+   /// sends the colour adjustments to whichever of the gpu pass or gstreamer's colour balance is in use,
+   /// the other one is kept at identity, and keeps the shader chain in step with the settings
+   fn sync_post_process(&mut self, wgpu_render_pack: &WgpuRenderPack) -> Result<()> {
+      let adjustments = self.color_adjustments();
+      let (gpu, balance) = match self.saved_settings.color_on_gpu {
+         true => (adjustments, ColorAdjustments::default()),
+         false => (ColorAdjustments::default(), adjustments),
+      };
+
+      let tone_mapping = ToneMapping {
+         mapper: self.saved_settings.tone_mapper,
+         peak_nits: self.hdr_peak_nits(),
+         sdr_white: self.saved_settings.sdr_white_nits,
+      };
+      self.display_texture.set_tone_mapping(wgpu_render_pack, &tone_mapping);
+      self.display_texture.set_color_adjustments(wgpu_render_pack, &gpu);
+      self.display_texture.set_shader_chain(wgpu_render_pack, &self.shader_chain());
+      self.display_texture.set_scaler(wgpu_render_pack, self.saved_settings.scaler);
+      self.display_texture.set_source_rect(wgpu_render_pack, self.temp_settings.display_uv, self.temp_settings.view.orientation.bits());
+      if let Some((width, height)) = self.temp_settings.display_pixels {
+         self.display_texture.set_output_size(wgpu_render_pack, width, height);
+      }
+
+      if self.temp_settings.applied_color_balance != Some(balance) {
+         self.mut_backend().set_color_balance(&balance)?;
+         self.temp_settings.applied_color_balance = Some(balance);
+      }
+
+      let deinterlace = self.saved_settings.deinterlace;
+      if self.temp_settings.applied_deinterlace != Some(deinterlace) {
+         self.temp_settings.applied_deinterlace = Some(deinterlace);
+         if let Err(err) = self.mut_backend().set_deinterlace(&deinterlace) {
+            self.osd.show_message(format!("Deinterlacing: {err}"));
+         }
+      }
+      Ok(())
+   }
+
+   /// brightest the current video stream gets, from its content light level or mastering display metadata
+   fn hdr_peak_nits(&self) -> Option<f32> {
+      let backend = self.backend.as_ref()?;
+      let probe = backend.get_probe().ok()?;
+      let track = backend.get_video_track().unwrap_or(0) as usize;
+      let hdr = probe.video_streams.get(track).or(probe.video_streams.first())?.0.hdr.as_ref()?;
+
+      hdr.max_content_light_level
+          .filter(|cll| *cll > 0)
+          .map(|cll| cll as f32)
+          .or(hdr.max_mastering_luminance.filter(|lum| *lum > 0.0).map(|lum| lum as f32))
+   }
+
+   fn tone_map_ui(&mut self, ui: &mut Ui) {
+      match self.display_texture.is_hdr() {
+         true => {
+            let peak = self.hdr_peak_nits();
+            ui.label(format!(
+               "HDR, peak {:.0} cd/m²{}",
+               peak.unwrap_or(DEFAULT_HDR_PEAK),
+               if peak.is_none() { " (assumed)" } else { "" },
+            ));
+         }
+         false => {
+            ui.label("Current video isn't HDR");
+         }
+      }
+
+      for mapper in ToneMapper::ALL {
+         ui.radio_value(&mut self.saved_settings.tone_mapper, *mapper, mapper.label());
+      }
+      ui.add(Slider::new(&mut self.saved_settings.sdr_white_nits, 80.0..=400.0).prefix("SDR white ").suffix(" cd/m²"));
+   }
+
+   fn deinterlace_ui(&mut self, ui: &mut Ui) {
+      let settings = &mut self.saved_settings.deinterlace;
+      for mode in DeinterlaceMode::ALL {
+         ui.radio_value(&mut settings.mode, *mode, mode.label());
+      }
+
+      ui.separator();
+      for method in DeinterlaceMethod::ALL {
+         ui.radio_value(&mut settings.method, *method, method.label());
+      }
+      if settings.method != DeinterlaceMethod::GpuBob {
+         ui.label("Runs on the cpu in 8 bit, HDR is lost");
+      }
+   }
+
+   /// aspect and crop presets and the zoom, all reset when the next file opens
+   fn view_ui(&mut self, ui: &mut Ui) {
+      let view = &mut self.temp_settings.view;
+
+      ui.label("Aspect ratio");
+      for aspect in AspectOverride::ALL {
+         ui.radio_value(&mut view.aspect, *aspect, aspect.label());
+      }
+
+      ui.separator();
+      ui.label("Crop");
+      for crop in CropPreset::ALL {
+         let enabled = *crop != CropPreset::Detected || view.black_bars.is_some() || view.crop == CropPreset::Detected;
+         ui.add_enabled_ui(enabled, |ui| ui.radio_value(&mut view.crop, *crop, crop.label()));
+      }
+
+      ui.separator();
+      ui.label(format!("Rotation {}", view.orientation.label()));
+      ui.horizontal(|ui| {
+         if ui.button("⟲").on_hover_text("Rotate left").clicked() {
+            view.orientation.rotate_counter_clockwise();
+         }
+         if ui.button("⟳").on_hover_text("Rotate right").clicked() {
+            view.orientation.rotate_clockwise();
+         }
+         if ui.button("↔").on_hover_text("Flip horizontally").clicked() {
+            view.orientation.flip_horizontal();
+         }
+         if ui.button("↕").on_hover_text("Flip vertically").clicked() {
+            view.orientation.flip_vertical();
+         }
+      });
+      let tagged = self.temp_settings.tag_orientation.unwrap_or_default();
+      if ui.add_enabled(view.orientation != tagged, Button::new("Use file orientation")).clicked() {
+         view.orientation = tagged;
+      }
+
+      ui.separator();
+      ui.label("Black bar detection");
+      for mode in BlackBarCrop::ALL {
+         ui.radio_value(&mut self.saved_settings.black_bar_crop, *mode, mode.label());
+      }
+
+      ui.separator();
+      ui.add(Slider::new(&mut view.zoom, ZOOM_RANGE).logarithmic(true).prefix("Zoom ").custom_formatter(|z, _| format!("{:.0}%", z * 100.0)));
+      if ui.add_enabled(view.is_zoomed(), Button::new("Reset zoom")).clicked() {
+         view.reset_zoom();
+      }
+   }
+
+   /// colour sliders editing the per file adjustments when ``Remember for this file`` is ticked
+   fn color_ui(&mut self, ui: &mut Ui) {
+      let Some(uri) = self.temp_settings.current_uri.clone() else {
+         return;
+      };
+
+      let mut per_file = self.saved_settings.file_colors.contains_key(&uri);
+      if ui.checkbox(&mut per_file, "Remember for this file").changed() {
+         match per_file {
+            true => {
+               let current = self.saved_settings.color;
+               self.saved_settings.file_colors.insert(uri.clone(), current);
+            }
+            false => {
+               self.saved_settings.file_colors.remove(&uri);
+            }
+         }
+      }
+
+      let gamma_supported = self.saved_settings.color_on_gpu;
+      let adjustments = match self.saved_settings.file_colors.get_mut(&uri) {
+         Some(adjustments) => adjustments,
+         None => &mut self.saved_settings.color,
+      };
+      color_editor(ui, adjustments, gamma_supported);
+   }
+
+   /// skips opening and ending chapters once when playback enters them
+   fn auto_skip_chapters(&mut self) -> Result<()> {
+      let current = self.get_backend().current_chapter();
+      if current == self.temp_settings.last_chapter {
+         return Ok(());
+      }
+      self.temp_settings.last_chapter = current;
+
+      if !self.saved_settings.auto_skip_op_ed || !self.get_backend().is_playing() {
+         return Ok(());
+      }
+
+      if let Some(index) = current {
+         let chapter = self.get_backend().get_chapters()[index].clone();
+         if let (true, Some(end)) = (chapter.is_opening_or_ending(), chapter.end) {
+            println!("Skipping chapter {}", chapter.display_title(index));
+            self.mut_backend().seek_time(SeekFlags::FLUSH | SeekFlags::ACCURATE, end)?;
+         }
+      }
+
+      Ok(())
+   }
+
+   /// the probe found no video, false until it finishes
+   fn is_audio_only(&mut self) -> bool {
+      self.get_backend().get_probe().is_ok_and(|probe| probe.video_streams.is_empty())
+   }
+
+   /// turns the gstreamer visualisation on for audio only files when it's picked, off otherwise,
+   /// one that can't be set is tried once and the spectrum drawn instead
+   fn sync_vis_plugin(&mut self) {
+      let wanted = match self.is_audio_only() && self.saved_settings.audio_visual == AudioVisual::Plugin {
+         true => Some(self.saved_settings.vis_plugin.clone()),
+         false => None,
+      };
+
+      if wanted != self.temp_settings.applied_vis_plugin {
+         self.temp_settings.applied_vis_plugin = wanted.clone();
+         self.temp_settings.vis_plugin_failed = false;
+         if let Err(err) = self.mut_backend().set_vis_plugin(wanted.as_deref()) {
+            self.osd.show_message(format!("Visualisation: {err}"));
+            self.temp_settings.vis_plugin_failed = true;
+            let _ = self.mut_backend().set_vis_plugin(None);
+         }
+      }
+   }
+
+   /// the gstreamer visualisation is drawing into the video sink
+   fn shows_vis_plugin(&mut self) -> bool {
+      self.is_audio_only() && self.saved_settings.audio_visual == AudioVisual::Plugin && !self.temp_settings.vis_plugin_failed
+   }
+
+   /// decoded once per file on another thread, tags can show up after the first few frames so it keeps asking until found
+   fn cover_art(&mut self, ctx: &egui::Context) -> Option<TextureHandle> {
+      if self.cover_art.is_some() || self.temp_settings.cover_art_failed {
+         return self.cover_art.clone();
+      }
+
+      match self.cover_art_decode.take() {
+         Some(decode) if decode.is_finished() => match decode.join().unwrap() {
+            Ok(image) => {
+               let color_image = ColorImage::from_rgba_unmultiplied([image.width, image.height], &image.rgba);
+               self.cover_art = Some(ctx.load_texture("cover art", color_image, TextureOptions::LINEAR));
+            }
+            Err(err) => {
+               println!("Couldn't decode cover art {err}");
+               self.temp_settings.cover_art_failed = true;
+            }
+         },
+         Some(decode) => self.cover_art_decode = Some(decode),
+         None => {
+            if let Some(sample) = self.get_backend().get_cover_art() {
+               self.cover_art_decode = Some(std::thread::spawn(move || decode_image(&sample)));
+            }
+         }
+      }
+      self.cover_art.clone()
+   }
+
+   /// the analysis only runs while something on screen reads it
+   fn sync_audio_analysis(&mut self) -> Result<()> {
+      let visual = self.is_audio_only() && match self.saved_settings.audio_visual {
+         AudioVisual::Plugin => self.temp_settings.vis_plugin_failed,
+         AudioVisual::CoverArt => self.cover_art.is_none(),
+         AudioVisual::Spectrum | AudioVisual::Oscilloscope => true,
+      };
+      let wanted = visual || self.saved_settings.show_level_meters;
+      self.mut_backend().set_audio_analysis(wanted)
+   }
+
+   fn paint_audio_visual(&mut self, ui: &mut Ui, rect: Rect) {
+      let painter = ui.painter_at(rect);
+      painter.rect_filled(rect, 0.0, Color32::BLACK);
+
+      let visual = self.saved_settings.audio_visual;
+      let cover = match visual {
+         AudioVisual::CoverArt => self.cover_art(ui.ctx()),
+         _ => None,
+      };
+
+      match (visual, cover) {
+         (AudioVisual::Oscilloscope, _) => paint_oscilloscope(&painter, rect, &self.get_backend().get_audio_samples()),
+         (_, Some(cover)) => paint_cover(&painter, rect.shrink(16.0), &cover),
+         // cover art without a cover and a plugin that failed fall back to the spectrum
+         _ => paint_spectrum(&painter, rect, self.get_backend().get_spectrum()),
+      }
+   }
+
    fn manage_fullscreen_state(&mut self, ui: &mut Ui) {
       let temp = &mut self.temp_settings;
       if temp.queued_fullscreen_state != temp.is_fullscreen {
@@ -131,10 +812,25 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
       }
    }
 
+   /// the settings and media info windows when open
+   fn windows(&mut self, ui: &mut Ui) {
+      // ids from the player's ui so several players' windows don't collide
+      if self.temp_settings.show_settings {
+         self.settings_window(ui.ctx(), ui.id().with("settings"));
+      }
+
+      if self.temp_settings.show_media_info {
+         let stats = self.get_backend().get_live_stats();
+         let probe = self.get_backend().get_probe().ok().cloned();
+         media_info_window(ui.ctx(), ui.id().with("media info"), &mut self.temp_settings.show_media_info, probe.as_ref(), &stats);
+      }
+   }
+
    fn show_internal(&mut self, ui: &mut Ui) {
       self.manage_fullscreen_state(ui);
+      self.windows(ui);
 
-      match self.temp_settings.is_fullscreen {
+      match self.temp_settings.is_fullscreen || self.saved_settings.minimal_ui {
          true => {
             self.player_ui(ui, ui.available_rect_before_wrap());
          }
@@ -156,29 +852,78 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
          if ui.button("Open url").clicked() {
             todo!()
          };
+
+         ui.separator();
+
+         ui.add(Slider::new(&mut self.saved_settings.probe_timeout_secs, 1.0..=60.0).prefix("Probe timeout ").suffix("s"));
+         ui.checkbox(&mut self.saved_settings.use_probe_cache, "Cache probe results");
+         if ui.button("Clear probe cache").clicked() {
+            if let Err(err) = ProbeCache::clear() {
+               println!("Couldn't clear probe cache {err}");
+            }
+         }
       });
 
       ui.menu_button("playback", |ui| {
          ui.menu_button("speed", |ui| {
             ui.horizontal(|ui| {
-               if ui.button("25% ").clicked() { self.mut_backend().change_playback_speed(0.25).unwrap(); }
-               if ui.button("50% ").clicked() { self.mut_backend().change_playback_speed(0.50).unwrap(); }
-               if ui.button("75% ").clicked() { self.mut_backend().change_playback_speed(0.75).unwrap(); }
-               if ui.button("100%").clicked() { self.mut_backend().change_playback_speed(1.00).unwrap(); }
+               if ui.button("25% ").clicked() { self.set_playback_speed(0.25); }
+               if ui.button("50% ").clicked() { self.set_playback_speed(0.50); }
+               if ui.button("75% ").clicked() { self.set_playback_speed(0.75); }
+               if ui.button("100%").clicked() { self.set_playback_speed(1.00); }
             });
 
             ui.horizontal(|ui| {
-               if ui.button("125%").clicked() { self.mut_backend().change_playback_speed(1.25).unwrap(); }
-               if ui.button("150%").clicked() { self.mut_backend().change_playback_speed(1.50).unwrap(); }
-               if ui.button("175%").clicked() { self.mut_backend().change_playback_speed(1.75).unwrap(); }
-               if ui.button("200%").clicked() { self.mut_backend().change_playback_speed(2.00).unwrap(); }
+               if ui.button("125%").clicked() { self.set_playback_speed(1.25); }
+               if ui.button("150%").clicked() { self.set_playback_speed(1.50); }
+               if ui.button("175%").clicked() { self.set_playback_speed(1.75); }
+               if ui.button("200%").clicked() { self.set_playback_speed(2.00); }
             });
 
             let mut pbs = self.get_backend().current_playback_speed();
             if ui.add(Slider::new(&mut pbs, 0.1..=5.0)).drag_stopped() {
-               self.mut_backend().change_playback_speed(pbs).unwrap();
+               self.set_playback_speed(pbs);
+            }
+         });
+      });
+
+      ui.menu_button("chapters", |ui| {
+         let chapters = self.get_backend().get_chapters().to_vec();
+         let current = self.get_backend().current_chapter();
+
+         if chapters.is_empty() {
+            ui.label("No chapters");
+         }
+
+         ui.horizontal(|ui| {
+            let result = match (ui.button("Previous").clicked(), ui.button("Next").clicked()) {
+               (true, _) => self.mut_backend().previous_chapter(),
+               (_, true) => self.mut_backend().next_chapter(),
+               _ => Ok(()),
+            };
+            if let Err(err) = result {
+               println!("Chapter seek failed {err}");
             }
          });
+
+         ui.checkbox(&mut self.saved_settings.auto_skip_op_ed, "Auto skip opening/ending");
+
+         ui.separator();
+
+         for (i, chapter) in chapters.iter().enumerate() {
+            let title = format!("{} | {}", format_timecode(chapter.start.seconds_f64()), chapter.display_title(i));
+
+            let formated_title = match Some(i) == current {
+               true => format!("{title} #"),
+               false => title,
+            };
+
+            if ui.button(formated_title).clicked() {
+               if let Err(err) = self.mut_backend().seek_chapter(i) {
+                  println!("Chapter seek failed {err}");
+               }
+            }
+         }
       });
 
       ui.menu_button("video", |ui| {
@@ -203,29 +948,38 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
                }
             }
          });
-      });
 
-      ui.menu_button("audio", |ui| {
-         ui.menu_button("audio track", |ui| {
-            let probe = self.get_backend().get_probe().unwrap().clone();
+         ui.menu_button("Colour", |ui| {
+            self.color_ui(ui);
+         });
+
+         ui.menu_button("Scaling", |ui| {
+            for scaler in Scaler::ALL {
+               ui.radio_value(&mut self.saved_settings.scaler, *scaler, scaler.label());
+            }
+         });
 
-            let current = self.get_backend().get_audio_track().unwrap();
-            for (i, (name_op, _id)) in probe.audio_streams.iter().enumerate() {
+         ui.menu_button("Zoom & crop", |ui| {
+            self.view_ui(ui);
+         });
 
-               let title = match &name_op.name {
-                  None => "No name".to_string(),
-                  Some(name) => name.clone(),
-               };
+         ui.menu_button("Deinterlace", |ui| {
+            self.deinterlace_ui(ui);
+         });
 
-               let formated_title = match i as u32 == current {
-                  true => format!("{i} | {title} #"),
-                  false => format!("{i} | {title}"),
-               };
+         ui.menu_button("Tone mapping", |ui| {
+            self.tone_map_ui(ui);
+         });
 
-               if ui.button(formated_title).clicked() {
-                  self.mut_backend().set_audio_track(i as u32).unwrap()
-               }
-            }
+         ui.menu_button("Shaders", |ui| {
+            let errors = self.display_texture.shader_errors();
+            shader_chain_editor(ui, &mut self.saved_settings.shader_chain, &errors);
+         });
+      });
+
+      ui.menu_button("audio", |ui| {
+         ui.menu_button("audio track", |ui| {
+            self.audio_track_list(ui);
          });
 
          ui.menu_button("Audio devices", |ui| {
@@ -244,43 +998,28 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
             }
          });
 
-         ui.menu_button("Mode", |ui| {
-            ui.label("Put surround sound settings or something hear");
+         ui.menu_button("Filters", |ui| {
+            self.audio_filters_ui(ui);
+         });
+
+         ui.menu_button("Visualisation", |ui| {
+            self.visualisation_ui(ui);
          });
 
          ui.menu_button("Vol scroll speed", |ui| {
             ui.add(Slider::new(&mut self.saved_settings.scroll_speed_mult, 1.0..=20.0));
          });
 
-         let mut val = self.get_backend().get_current_volume();
-         if ui.add(Slider::new(&mut val, self.get_backend().get_volume_range())).hovered() {
-            self.mut_backend().set_volume(val).unwrap();
-         }
+         ui.horizontal(|ui| {
+            self.mute_toggle(ui);
+            self.volume_slider(ui);
+         });
       });
 
       ui.menu_button("subtitles", |ui| {
 
          ui.menu_button("subtitle track", |ui| {
-            let probe = self.get_backend().get_probe().unwrap().clone();
-
-            let current = self.get_backend().get_sub_track().unwrap();
-            for (i, (name_op, _id)) in probe.captions.iter().enumerate() {
-
-
-               let title = match &name_op {
-                  None => "No name".to_string(),
-                  Some(name) => name.clone(),
-               };
-
-               let formated_title = match i as u32 == current {
-                  true => format!("{i} | {title} #"),
-                  false => format!("{i} | {title}"),
-               };
-
-               if ui.button(formated_title).clicked() {
-                  self.mut_backend().set_sub_track(i as u32).unwrap()
-               }
-            }
+            self.subtitle_track_list(ui);
          });
 
          let mut bool = self.get_backend().get_playflag_state(PlayFlags::SUBTITLES).unwrap();
@@ -291,17 +1030,21 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
 
       ui.menu_button("tools", |ui| {
          if ui.button("open settings").clicked() {
-            todo!()
+            self.temp_settings.show_settings = true;
          }
 
-         if ui.button("Fullscreen").clicked() {
-            self.temp_settings.queued_fullscreen_state = !self.temp_settings.queued_fullscreen_state;
+         if ui.button("Media information").clicked() {
+            self.temp_settings.show_media_info = true;
          }
 
          if ui.button("Fullscreen").clicked() {
             self.temp_settings.queued_fullscreen_state = !self.temp_settings.queued_fullscreen_state;
          }
 
+         ui.checkbox(&mut self.saved_settings.minimal_ui, "Minimal UI");
+         ui.checkbox(&mut self.saved_settings.show_level_meters, "Level meters");
+         ui.checkbox(&mut self.saved_settings.timeline_waveform, "Timeline waveform");
+
          if ui.button("Step_one_frame").clicked() {
             self.mut_backend().seek_frames(1).unwrap();
             // self.mut_backend().queue_frame_update();
@@ -368,34 +1111,50 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
    }
 
    fn player_interaction(&mut self, ui: &mut Ui, resp: Response) {
-      if resp.double_clicked() {
+      // in a grid double clicking maximizes the tile instead, that's left to the grid
+      let in_grid = self.temp_settings.tile_focus.is_some();
+      let has_focus = self.temp_settings.tile_focus != Some(false);
+      if resp.double_clicked() && !in_grid {
          self.temp_settings.queued_fullscreen_state = !self.temp_settings.queued_fullscreen_state;
       }
 
       // keyboard input
-      ui.ctx().input(|i| {
-         if i.key_pressed(Key::Space) {
-            match self.get_backend().is_paused() {
-               true => {
-                  self.mut_backend().start().unwrap();
-               }
-               false => {
-                  self.mut_backend().stop().unwrap();
-               }
+      if has_focus && !ui.ctx().wants_keyboard_input() && self.temp_settings.rebind_capture.is_none() {
+         let actions = ui.ctx().input(|i| self.saved_settings.keymap.pressed_actions(i));
+         for action in actions {
+            if let Err(err) = self.run_action(action) {
+               println!("{action:?} failed {err}");
             }
          }
-      });
+      }
+
+      if let Some((area, fitted)) = self.temp_settings.view_area {
+         if resp.dragged() {
+            self.temp_settings.view.pan += resp.drag_delta() / fitted;
+         }
+
+         // ctrl scroll or pinch, around the pointer
+         let zoom = ui.input(|i| i.zoom_delta());
+         if resp.hovered() && zoom != 1.0 {
+            let pointer = resp.hover_pos().unwrap_or(area.center());
+            self.temp_settings.view.zoom_at(zoom, (pointer - area.center()) / fitted);
+         }
+      }
 
       if resp.hovered() {
          ui.ctx().input(|i| {
             let raw_spd = i.raw_scroll_delta.y;
             let unit = raw_spd / 40.0;
 
-            if unit != 0.0 {
-               let digit = (unit * 0.01) * self.saved_settings.scroll_speed_mult * 2.5;
-               let c = self.get_backend().get_current_volume();
-               let set = (c + digit as f64).clamp(0.0, *self.get_backend().get_volume_range().end());
-               self.mut_backend().set_volume(set).unwrap();
+            // ctrl scroll zooms instead
+            if has_focus && unit != 0.0 && !i.modifiers.command {
+               let digit = unit * 0.005 * self.saved_settings.scroll_speed_mult;
+               self.nudge_volume(digit as f64).unwrap();
+               self.osd_feedback(PlayerAction::VolumeUp);
+            }
+
+            if i.pointer.is_moving() {
+               self.osd.mouse_moved();
             }
          });
       }
@@ -406,31 +1165,38 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
       });
    }
 
-   fn player_ui(&mut self, ui: &mut Ui, major_rect: Rect) {
+   fn player_ui(&mut self, ui: &mut Ui, major_rect: Rect) -> Response {
       CentralPanel::default().frame(Frame::none()).show_inside(ui, |ui| {
          let resp_rect = ui.available_rect_before_wrap();
-         if let Some(inner) = &self.display_texture.inner {
-            let correct_size = inner.texture.size();
-            let aspect = correct_size.width as f32 / correct_size.height as f32;
-
-            let max_width = major_rect.width();
-            let max_height = major_rect.height();
-            let mut inner_width = max_width;
-            let mut inner_height = inner_width / aspect;
-
-            if inner_height > max_height {
-               inner_height = max_height;
-               inner_width = inner_height * aspect;
+         let draw_visual = self.is_audio_only() && !self.shows_vis_plugin();
+
+         if draw_visual {
+            self.paint_audio_visual(ui, resp_rect);
+         } else if let (Some(inner), Some(texture_id)) = (&self.display_texture.inner, self.display_texture.texture_id()) {
+            let correct_size = inner.size();
+            let par = self.backend.as_ref().and_then(|b| b.get_latest_vidio_info()).map_or(1.0, |info| fraction_to_f64(info.par()) as f32);
+            let aspect = correct_size.width as f32 / correct_size.height as f32 * par;
+
+            match self.temp_settings.view.layout(aspect, major_rect) {
+               Some(layout) => {
+                  self.temp_settings.view_area = Some((major_rect, layout.fitted));
+                  self.temp_settings.video_rect = Some(layout.visible);
+
+                  // the scaler renders just the visible part at exactly this size, picked up next frame
+                  let pixels = (layout.visible.size() * ui.ctx().pixels_per_point()).round();
+                  self.temp_settings.display_pixels = Some((pixels.x as u32, pixels.y as u32));
+                  self.temp_settings.display_uv = [layout.uv.min.x, layout.uv.min.y, layout.uv.width(), layout.uv.height()];
+
+                  ui.allocate_new_ui(UiBuilder::new().max_rect(layout.visible), |ui| {
+                     ui.image(ImageSource::Texture(SizedTexture::new(texture_id, ui.available_size())));
+                  });
+               }
+               // panned off screen, only panning back matters
+               None => {
+                  self.temp_settings.view_area = Some((major_rect, major_rect.size()));
+                  self.temp_settings.video_rect = None;
+               }
             }
-
-            let mut inner_rect = major_rect;
-            inner_rect.set_width(inner_width);
-            inner_rect.set_height(inner_height);
-            inner_rect.set_center(major_rect.center());
-
-            ui.allocate_new_ui(UiBuilder::new().max_rect(inner_rect), |ui| {
-               ui.image(ImageSource::Texture(SizedTexture::new(inner.texture_id, ui.available_size())));
-            });
          };
 
          let resp = ui.allocate_rect(resp_rect, Sense {
@@ -438,8 +1204,20 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
             drag: true,
             focusable: false,
          });
-         self.player_interaction(ui, resp);
-      });
+         self.player_interaction(ui, resp.clone());
+
+         let info = OsdInfo {
+            title: self.media_title(),
+            position: self.get_backend().timecode().seconds_f64(),
+            duration: self.get_backend().get_duration().map_or(0.0, |d| d.seconds_f64()),
+         };
+         self.osd.paint(ui.painter(), resp_rect, &self.saved_settings.osd, &info);
+
+         if self.temp_settings.is_fullscreen || self.saved_settings.minimal_ui {
+            self.overlay_controls(ui, resp_rect);
+         }
+         resp
+      }).inner
    }
 
    fn top_ui(&mut self, ui: &mut Ui) {
@@ -463,17 +1241,28 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
                self.mut_backend().stop().unwrap();
             }
 
-            let mut change = self.get_backend().timecode().seconds_f64();
-            let max = self.get_backend().get_duration().unwrap().seconds_f64() - self.get_backend().get_frametime();
-            if ui.add(Slider::new(&mut change, 0.0..=max).prefix("Keyframe ")).changed() {
-               self.mut_backend().seek_timeline(
-                  ClockTime::from_seconds_f64(change),
-                  true
-               ).unwrap();
+            self.ab_loop_ui(ui);
+
+            if !self.get_backend().get_chapters().is_empty() {
+               let result = match (
+                  ui.button("|<").on_hover_text("Previous chapter").clicked(),
+                  ui.button(">|").on_hover_text("Next chapter").clicked(),
+               ) {
+                  (true, _) => self.mut_backend().previous_chapter(),
+                  (_, true) => self.mut_backend().next_chapter(),
+                  _ => Ok(()),
+               };
+               if let Err(err) = result {
+                  println!("Chapter seek failed {err}");
+               }
+            }
 
-               self.mut_backend().queue_frame_update();
+            if self.saved_settings.show_level_meters {
+               level_meters(ui, self.get_backend().get_levels());
             }
 
+            self.timeline_ui(ui);
+
             // let mut change = self.get_backend().timecode().seconds_f64();
             // let max = self.get_backend().get_duration().unwrap().seconds_f64() - self.get_backend().get_frametime();
             // if ui.add(Slider::new(&mut change, 0.0..=max).prefix("Exact ")).changed() {
@@ -487,5 +1276,639 @@ impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
          })
       });
    }
+
+   /// volume slider moving along ``SavedSettings::volume_curve``, hover shows the real gain
+   fn volume_slider(&mut self, ui: &mut Ui) {
+      let curve = self.saved_settings.volume_curve;
+      let gain = self.get_backend().get_current_volume();
+      let max = *self.get_backend().get_volume_range().end();
+
+      let mut position = curve.to_position(gain, max);
+      let resp = ui.add(Slider::new(&mut position, 0.0..=1.0).show_value(false))
+          .on_hover_text(format_gain(gain));
+      if resp.changed() {
+         self.mut_backend().set_volume(curve.to_gain(position, max)).unwrap();
+      }
+   }
+
+   fn audio_filters_ui(&mut self, ui: &mut Ui) {
+      let loudness = self.get_backend().get_loudness_state();
+      if audio_filters_editor(ui, &mut self.saved_settings.audio_filters, &loudness) {
+         self.mut_backend().set_audio_filters(&self.saved_settings.audio_filters).unwrap();
+      }
+   }
+
+   /// what audio only files show, the plugin list only when plugins are picked
+   fn visualisation_ui(&mut self, ui: &mut Ui) {
+      for visual in AudioVisual::ALL {
+         ui.radio_value(&mut self.saved_settings.audio_visual, *visual, visual.label());
+      }
+
+      if self.saved_settings.audio_visual == AudioVisual::Plugin {
+         ui.separator();
+         for (factory, long_name) in self.get_backend().list_vis_plugins() {
+            let selected = self.saved_settings.vis_plugin == factory;
+            if ui.selectable_label(selected, long_name).on_hover_text(&factory).clicked() {
+               self.saved_settings.vis_plugin = factory;
+               // forces sync_vis_plugin to swap the element
+               self.temp_settings.applied_vis_plugin = None;
+            }
+         }
+      }
+   }
+
+   fn mute_toggle(&mut self, ui: &mut Ui) {
+      let muted = self.get_backend().is_muted();
+      let text = match muted {
+         true => "Unmute",
+         false => "Mute",
+      };
+      if ui.selectable_label(muted, text).clicked() {
+         self.mut_backend().set_muted(!muted).unwrap();
+      }
+   }
+
+   /// time label and seek bar with chapter and loop markers, fills the rest of the row
+   fn timeline_ui(&mut self, ui: &mut Ui) {
+      let mut change = self.get_backend().timecode().seconds_f64();
+      let max = self.get_backend().get_duration().unwrap().seconds_f64() - self.get_backend().get_frametime();
+      ui.label(format!("{} / {}", format_timecode(change), format_timecode(max)));
+
+      if self.saved_settings.timeline_waveform {
+         self.mut_backend().request_waveform();
+      }
+
+      let mut timeline = Timeline::new(&mut change, max);
+      if let (true, Some(waveform)) = (self.saved_settings.timeline_waveform, self.get_backend().get_waveform()) {
+         timeline = timeline.waveform(&waveform.peaks);
+      }
+      if let Some(ab) = self.get_backend().get_ab_loop() {
+         timeline = timeline.range(ab.a.seconds_f64(), ab.b.seconds_f64(), Color32::from_rgba_unmultiplied(255, 200, 0, 60));
+      }
+      for chapter in self.get_backend().get_chapters().iter().skip(1) {
+         timeline = timeline.marker(chapter.start.seconds_f64(), Color32::GRAY);
+      }
+      if let Some(a) = self.temp_settings.loop_a {
+         timeline = timeline.marker(a, Color32::YELLOW);
+      }
+      if let Some(b) = self.temp_settings.loop_b {
+         timeline = timeline.marker(b, Color32::YELLOW);
+      }
+
+      if ui.add(timeline).changed() {
+         self.mut_backend().seek_timeline(
+            ClockTime::from_seconds_f64(change),
+            true
+         ).unwrap();
+
+         self.mut_backend().queue_frame_update();
+      }
+   }
+
+   fn audio_track_list(&mut self, ui: &mut Ui) {
+      let Ok(probe) = self.get_backend().get_probe().cloned() else {
+         ui.label("Probe not finished");
+         return;
+      };
+
+      let current = self.get_backend().get_audio_track().unwrap();
+      for (i, (name_op, _id)) in probe.audio_streams.iter().enumerate() {
+
+         let title = match &name_op.name {
+            None => "No name".to_string(),
+            Some(name) => name.clone(),
+         };
+
+         let formated_title = match i as u32 == current {
+            true => format!("{i} | {title} #"),
+            false => format!("{i} | {title}"),
+         };
+
+         if ui.button(formated_title).clicked() {
+            self.mut_backend().set_audio_track(i as u32).unwrap();
+            self.osd.show_message(format!("Audio: {}", self.audio_track_label(i)));
+         }
+      }
+   }
+
+   fn subtitle_track_list(&mut self, ui: &mut Ui) {
+      let Ok(probe) = self.get_backend().get_probe().cloned() else {
+         ui.label("Probe not finished");
+         return;
+      };
+
+      let current = self.get_backend().get_sub_track().unwrap();
+      for (i, (name_op, _id)) in probe.captions.iter().enumerate() {
+
+         let title = match &name_op.name {
+            None => "No name".to_string(),
+            Some(name) => name.clone(),
+         };
+
+         let formated_title = match i as u32 == current {
+            true => format!("{i} | {title} #"),
+            false => format!("{i} | {title}"),
+         };
+
+         if ui.button(formated_title).clicked() {
+            self.mut_backend().set_sub_track(i as u32).unwrap();
+            self.osd.show_message(format!("Subtitles: {}", self.subtitle_track_label(i)));
+         }
+      }
+   }
+
+   /// A-B repeat buttons, the loop starts once both points are set
+   fn ab_loop_ui(&mut self, ui: &mut Ui) {
+      let now = self.get_backend().timecode().seconds_f64();
+
+      if ui.button("A").on_hover_text("Set loop start").clicked() {
+         self.temp_settings.loop_a = Some(now);
+         self.apply_ab_loop();
+      }
+
+      if ui.button("B").on_hover_text("Set loop end").clicked() {
+         self.temp_settings.loop_b = Some(now);
+         self.apply_ab_loop();
+      }
+
+      ui.add(DragValue::new(&mut self.temp_settings.loop_repeats).custom_formatter(|n, _| {
+         match n as u32 {
+            0 => "x∞".to_string(),
+            n => format!("x{n}"),
+         }
+      })).on_hover_text("Loop repeats, 0 for forever");
+
+      if ui.button("Clear A-B").clicked() {
+         self.clear_ab_loop();
+      }
+   }
+
+   fn apply_ab_loop(&mut self) {
+      if let (Some(a), Some(b)) = (self.temp_settings.loop_a, self.temp_settings.loop_b) {
+         let (a, b) = (a.min(b), a.max(b));
+         let repeats = match self.temp_settings.loop_repeats {
+            0 => None,
+            n => Some(n),
+         };
+
+         if let Err(err) = self.mut_backend().set_ab_loop(
+            ClockTime::from_seconds_f64(a),
+            ClockTime::from_seconds_f64(b),
+            repeats,
+         ) {
+            println!("Couldn't start A-B loop {err}");
+         }
+      }
+   }
+
+   /// drops the markers once the backend has no loop, after the repeats ran out or it couldn't start,
+   /// a lone A is kept since no loop is expected yet
+   fn sync_ab_loop(&mut self) {
+      let temp = &mut self.temp_settings;
+      if temp.loop_a.is_some() && temp.loop_b.is_some() && self.backend.as_ref().unwrap().get_ab_loop().is_none() {
+         temp.loop_a = None;
+         temp.loop_b = None;
+      }
+   }
+
+   fn clear_ab_loop(&mut self) {
+      self.temp_settings.loop_a = None;
+      self.temp_settings.loop_b = None;
+      self.mut_backend().clear_ab_loop().unwrap();
+   }
 }
 
+
+/////////////////////////
+//// OVERLAY METHODS ////
+/////////////////////////
+impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
+   const OVERLAY_HEIGHT: f32 = 64.0;
+
+   /// translucent controls along the bottom of ``rect`` for fullscreen and minimal ui,
+   /// shown while the mouse moves and hidden along with the cursor after ``controls_hide_secs``
+   fn overlay_controls(&mut self, ui: &mut Ui, rect: Rect) {
+      let bar_rect = Rect::from_min_max(pos2(rect.left(), rect.bottom() - Self::OVERLAY_HEIGHT), rect.right_bottom());
+
+      let (moved, pointer_in_rect) = ui.ctx().input(|i| {
+         let inside = i.pointer.hover_pos().is_some_and(|p| rect.contains(p));
+         (inside && i.pointer.is_moving(), inside)
+      });
+      if moved {
+         self.temp_settings.last_mouse_move = Some(Instant::now());
+      }
+
+      // a negative or nan delay from a hand edited settings file hides them straight away
+      let hide_after = Duration::try_from_secs_f32(self.saved_settings.controls_hide_secs).unwrap_or(Duration::ZERO);
+      let recently_moved = self.temp_settings.last_mouse_move.is_some_and(|t| t.elapsed() < hide_after);
+      // popups from the track menus sit outside the bar, keep it up while they're open
+      let menu_open = ui.ctx().memory(|m| m.any_popup_open());
+      let visible = recently_moved || ui.rect_contains_pointer(bar_rect) || menu_open;
+
+      // from the player's ui so each tile of a grid fades on its own
+      let opacity = ui.ctx().animate_bool_with_time(ui.id().with("overlay controls"), visible, 0.2);
+      if opacity == 0.0 {
+         if pointer_in_rect {
+            ui.ctx().set_cursor_icon(CursorIcon::None);
+         }
+         return;
+      }
+
+      ui.allocate_new_ui(UiBuilder::new().max_rect(bar_rect), |ui| {
+         ui.multiply_opacity(opacity);
+         ui.painter().rect_filled(bar_rect, 0.0, Color32::from_black_alpha(160));
+
+         Frame::none().inner_margin(6.0).show(ui, |ui| {
+            ui.horizontal(|ui| {
+               self.timeline_ui(ui);
+            });
+
+            ui.horizontal(|ui| {
+               let play_text = match self.get_backend().is_paused() {
+                  true => "Play",
+                  false => "Pause",
+               };
+               if ui.button(play_text).clicked() {
+                  if let Err(err) = self.run_action(PlayerAction::TogglePause) {
+                     println!("{:?} failed {err}", PlayerAction::TogglePause);
+                  }
+               }
+
+               self.mute_toggle(ui);
+               self.volume_slider(ui);
+
+               if self.saved_settings.show_level_meters {
+                  level_meters(ui, self.get_backend().get_levels());
+               }
+
+               ui.menu_button("Audio", |ui| {
+                  self.audio_track_list(ui);
+               });
+
+               ui.menu_button("Subtitles", |ui| {
+                  self.subtitle_track_list(ui);
+               });
+
+               let fullscreen_text = match self.temp_settings.is_fullscreen {
+                  true => "Exit fullscreen",
+                  false => "Fullscreen",
+               };
+               if ui.button(fullscreen_text).clicked() {
+                  self.set_fullscreen(!self.temp_settings.is_fullscreen);
+               }
+            });
+         });
+      });
+   }
+}
+
+
+/////////////////////////
+//// ACTIONS METHODS ////
+/////////////////////////
+impl<Backend: GstreamerBackendFramework> VidioPlayer<Backend> {
+   /// runs ``action`` then reports it on the osd
+   pub fn run_action(&mut self, action: PlayerAction) -> Result<()> {
+      self.run_action_inner(action)?;
+      self.osd_feedback(action);
+      Ok(())
+   }
+
+   fn osd_feedback(&mut self, action: PlayerAction) {
+      let message = match action {
+         PlayerAction::TogglePause => match self.get_backend().is_paused() {
+            true => "Paused".to_string(),
+            false => "Playing".to_string(),
+         },
+         PlayerAction::SeekForward
+         | PlayerAction::SeekBackward
+         | PlayerAction::SeekForwardLong
+         | PlayerAction::SeekBackwardLong
+         | PlayerAction::NextChapter
+         | PlayerAction::PreviousChapter => {
+            self.osd.show_seek_bar();
+            return;
+         }
+         PlayerAction::SpeedUp | PlayerAction::SpeedDown | PlayerAction::SpeedReset => self.speed_text(),
+         PlayerAction::Mute => match self.get_backend().is_muted() {
+            true => "Muted".to_string(),
+            false => "Unmuted".to_string(),
+         },
+         PlayerAction::VolumeUp | PlayerAction::VolumeDown => {
+            let volume = format_gain(self.get_backend().get_current_volume());
+            match self.get_backend().is_muted() {
+               true => format!("Volume {volume} (muted)"),
+               false => format!("Volume {volume}"),
+            }
+         }
+         PlayerAction::NextAudioTrack | PlayerAction::PreviousAudioTrack => {
+            let track = self.get_backend().get_audio_track().unwrap_or(0) as usize;
+            format!("Audio: {}", self.audio_track_label(track))
+         }
+         PlayerAction::CycleSubtitles | PlayerAction::ToggleSubtitles => {
+            match self.get_backend().get_playflag_state(PlayFlags::SUBTITLES).unwrap_or(false) {
+               false => "Subtitles off".to_string(),
+               true => {
+                  let track = self.get_backend().get_sub_track().unwrap_or(0) as usize;
+                  format!("Subtitles: {}", self.subtitle_track_label(track))
+               }
+            }
+         }
+         PlayerAction::Screenshot => "Screenshot saved".to_string(),
+         PlayerAction::AbLoop => match (self.temp_settings.loop_a, self.temp_settings.loop_b) {
+            (Some(a), None) => format!("Loop A {}", format_timecode(a)),
+            (Some(a), Some(b)) => format!("Loop {} - {}", format_timecode(a), format_timecode(b)),
+            _ => "Loop cleared".to_string(),
+         },
+         PlayerAction::ZoomIn | PlayerAction::ZoomOut => format!("Zoom {:.0}%", self.temp_settings.view.zoom * 100.0),
+         PlayerAction::ResetView => "Zoom reset".to_string(),
+         PlayerAction::CycleAspect => format!("Aspect {}", self.temp_settings.view.aspect.label()),
+         PlayerAction::CycleCrop => format!("Crop {}", self.temp_settings.view.crop.label()),
+         PlayerAction::FrameStep
+         | PlayerAction::FrameBackStep
+         | PlayerAction::ToggleFullscreen
+         | PlayerAction::ExitFullscreen
+         | PlayerAction::MediaInfo
+         | PlayerAction::PanLeft
+         | PlayerAction::PanRight
+         | PlayerAction::PanUp
+         | PlayerAction::PanDown => return,
+      };
+
+      self.osd.show_message(message);
+   }
+
+   /// title tag of the media, or the file name when there isn't one
+   fn media_title(&mut self) -> String {
+      let Ok(probe) = self.get_backend().get_probe() else {
+         return String::new();
+      };
+
+      if let Some((_, title)) = probe.tags.iter().find(|(name, _)| name == "title") {
+         return title.clone();
+      }
+
+      let file_name = url::Url::parse(&probe.uri)
+          .ok()
+          .and_then(|url| url.to_file_path().ok())
+          .and_then(|path| path.file_name().map(|n| n.to_string_lossy().to_string()));
+
+      file_name.unwrap_or_else(|| probe.uri.rsplit('/').next().unwrap_or(&probe.uri).to_string())
+   }
+
+   fn audio_track_label(&mut self, index: usize) -> String {
+      let stream = self.get_backend().get_probe().ok().and_then(|p| p.audio_streams.get(index).map(|s| s.0.clone()));
+      match stream {
+         None => format!("Track {}", index + 1),
+         Some(stream) => stream.name.or(stream.language).unwrap_or_else(|| format!("Track {}", index + 1)),
+      }
+   }
+
+   fn subtitle_track_label(&mut self, index: usize) -> String {
+      let stream = self.get_backend().get_probe().ok().and_then(|p| p.captions.get(index).map(|s| s.0.clone()));
+      match stream {
+         None => format!("Track {}", index + 1),
+         Some(stream) => stream.name.or(stream.language).unwrap_or_else(|| format!("Track {}", index + 1)),
+      }
+   }
+
+   fn run_action_inner(&mut self, action: PlayerAction) -> Result<()> {
+      match action {
+         PlayerAction::TogglePause => match self.get_backend().is_paused() {
+            true => self.mut_backend().start()?,
+            false => self.mut_backend().stop()?,
+         },
+
+         PlayerAction::SeekForward => self.seek_relative(5.0)?,
+         PlayerAction::SeekBackward => self.seek_relative(-5.0)?,
+         PlayerAction::SeekForwardLong => self.seek_relative(30.0)?,
+         PlayerAction::SeekBackwardLong => self.seek_relative(-30.0)?,
+         PlayerAction::FrameStep => self.mut_backend().seek_frames(1)?,
+         PlayerAction::FrameBackStep => self.mut_backend().seek_frames(-1)?,
+
+         PlayerAction::SpeedUp => self.scale_speed(1.1)?,
+         PlayerAction::SpeedDown => self.scale_speed(1.0 / 1.1)?,
+         PlayerAction::SpeedReset => self.mut_backend().change_playback_speed(1.0)?,
+
+         PlayerAction::VolumeUp => self.nudge_volume(0.02)?,
+         PlayerAction::VolumeDown => self.nudge_volume(-0.02)?,
+         PlayerAction::Mute => {
+            let muted = self.get_backend().is_muted();
+            self.mut_backend().set_muted(!muted)?;
+         }
+
+         PlayerAction::NextAudioTrack => self.cycle_audio_track(1)?,
+         PlayerAction::PreviousAudioTrack => self.cycle_audio_track(-1)?,
+         PlayerAction::CycleSubtitles => self.cycle_subtitles()?,
+         PlayerAction::ToggleSubtitles => {
+            let enabled = self.get_backend().get_playflag_state(PlayFlags::SUBTITLES)?;
+            self.mut_backend().toggle_playflag(!enabled, PlayFlags::SUBTITLES)?;
+         }
+
+         PlayerAction::NextChapter => self.mut_backend().next_chapter()?,
+         PlayerAction::PreviousChapter => self.mut_backend().previous_chapter()?,
+
+         PlayerAction::ToggleFullscreen => {
+            self.temp_settings.queued_fullscreen_state = !self.temp_settings.queued_fullscreen_state;
+         }
+         PlayerAction::ExitFullscreen => self.set_fullscreen(false),
+
+         PlayerAction::Screenshot => {
+            let path = Self::screenshot_path()?;
+            self.get_backend().screenshot(&path)?;
+            println!("Saved screenshot to {}", path.display());
+         }
+
+         // same as mpv, first press sets a, second sets b, third clears
+         PlayerAction::AbLoop => match (self.temp_settings.loop_a, self.temp_settings.loop_b) {
+            (None, _) => {
+               self.temp_settings.loop_a = Some(self.get_backend().timecode().seconds_f64());
+            }
+            (Some(_), None) => {
+               self.temp_settings.loop_b = Some(self.get_backend().timecode().seconds_f64());
+               self.apply_ab_loop();
+            }
+            (Some(_), Some(_)) => self.clear_ab_loop(),
+         },
+
+         PlayerAction::MediaInfo => {
+            self.temp_settings.show_media_info = !self.temp_settings.show_media_info;
+         }
+
+         PlayerAction::ZoomIn => self.temp_settings.view.zoom_at(ZOOM_STEP, Vec2::ZERO),
+         PlayerAction::ZoomOut => self.temp_settings.view.zoom_at(1.0 / ZOOM_STEP, Vec2::ZERO),
+         PlayerAction::PanLeft => self.temp_settings.view.pan.x -= PAN_STEP,
+         PlayerAction::PanRight => self.temp_settings.view.pan.x += PAN_STEP,
+         PlayerAction::PanUp => self.temp_settings.view.pan.y -= PAN_STEP,
+         PlayerAction::PanDown => self.temp_settings.view.pan.y += PAN_STEP,
+         PlayerAction::ResetView => self.temp_settings.view.reset_zoom(),
+         PlayerAction::CycleAspect => self.temp_settings.view.aspect = self.temp_settings.view.aspect.next(),
+         PlayerAction::CycleCrop => self.temp_settings.view.crop = self.temp_settings.view.crop.next(),
+      }
+
+      Ok(())
+   }
+
+   fn seek_relative(&mut self, seconds: f64) -> Result<()> {
+      // zero when the duration query fails, live streams and some network sources have none
+      let duration = self.get_backend().get_duration()?.seconds_f64();
+      let mut target = (self.get_backend().timecode().seconds_f64() + seconds).max(0.0);
+      if duration > 0.0 {
+         target = target.min(duration);
+      }
+
+      self.mut_backend().seek_timeline(ClockTime::from_seconds_f64(target), false)?;
+      self.mut_backend().queue_frame_update();
+      Ok(())
+   }
+
+   fn speed_text(&mut self) -> String {
+      format!("Speed {:.2}x", self.get_backend().current_playback_speed())
+   }
+
+   /// from the playback menu, shown on the osd the same as the speed keys
+   fn set_playback_speed(&mut self, speed: f64) {
+      match self.mut_backend().change_playback_speed(speed) {
+         Ok(()) => {
+            let text = self.speed_text();
+            self.osd.show_message(text);
+         }
+         Err(err) => println!("Speed change failed {err}"),
+      }
+   }
+
+   fn scale_speed(&mut self, by: f64) -> Result<()> {
+      let speed = (self.get_backend().current_playback_speed() * by).clamp(0.1, 5.0);
+      self.mut_backend().change_playback_speed(speed)
+   }
+
+   /// moves the volume by ``by`` of the slider, so steps follow the volume curve
+   fn nudge_volume(&mut self, by: f64) -> Result<()> {
+      let curve = self.saved_settings.volume_curve;
+      let max = *self.get_backend().get_volume_range().end();
+      let position = curve.to_position(self.get_backend().get_current_volume(), max) + by;
+      self.mut_backend().set_volume(curve.to_gain(position, max))
+   }
+
+   fn cycle_audio_track(&mut self, by: i32) -> Result<()> {
+      let count = self.get_backend().get_probe()?.audio_streams.len() as i32;
+      if count == 0 {
+         return Ok(());
+      }
+
+      let current = self.get_backend().get_audio_track()? as i32;
+      self.mut_backend().set_audio_track((current + by).rem_euclid(count) as u32)
+   }
+
+   /// steps through every subtitle track then off, like mpv
+   fn cycle_subtitles(&mut self) -> Result<()> {
+      let count = self.get_backend().get_probe()?.captions.len() as u32;
+      let enabled = self.get_backend().get_playflag_state(PlayFlags::SUBTITLES)?;
+      let current = self.get_backend().get_sub_track()?;
+
+      match (enabled, current + 1 >= count) {
+         (false, _) => {
+            self.mut_backend().set_sub_track(0)?;
+            self.mut_backend().toggle_playflag(true, PlayFlags::SUBTITLES)?;
+         }
+         (true, true) => self.mut_backend().toggle_playflag(false, PlayFlags::SUBTITLES)?,
+         (true, false) => self.mut_backend().set_sub_track(current + 1)?,
+      }
+
+      Ok(())
+   }
+
+   fn screenshot_path() -> Result<PathBuf> {
+      let dir = dirs::picture_dir()
+          .or_else(dirs::home_dir)
+          .context("Nowhere to save screenshots")?
+          .join("vid_v2");
+      std::fs::create_dir_all(&dir)?;
+
+      let stamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+      Ok(dir.join(format!("screenshot-{stamp}.png")))
+   }
+
+   fn settings_window(&mut self, ctx: &egui::Context, id: Id) {
+      let mut open = self.temp_settings.show_settings;
+
+      Window::new("Settings")
+          .id(id)
+          .open(&mut open)
+          .default_width(460.0)
+          .show(ctx, |ui| {
+             egui::ScrollArea::vertical().show(ui, |ui| {
+                CollapsingHeader::new("General").default_open(true).show(ui, |ui| {
+                   ui.add(Slider::new(&mut self.saved_settings.scroll_speed_mult, 1.0..=20.0).prefix("Vol scroll speed "));
+                   ui.checkbox(&mut self.saved_settings.auto_skip_op_ed, "Auto skip opening/ending chapters");
+                   ui.add(Slider::new(&mut self.saved_settings.probe_timeout_secs, 1.0..=60.0).prefix("Probe timeout ").suffix("s"));
+                   ui.checkbox(&mut self.saved_settings.use_probe_cache, "Cache probe results");
+                   ui.checkbox(&mut self.saved_settings.minimal_ui, "Minimal UI when windowed");
+                   ui.add(Slider::new(&mut self.saved_settings.controls_hide_secs, 0.5..=10.0).prefix("Hide controls after ").suffix("s"));
+                });
+
+                CollapsingHeader::new("Video").show(ui, |ui| {
+                   ui.checkbox(&mut self.saved_settings.color_on_gpu, "Colour adjustments on the gpu")
+                       .on_hover_text("Off uses GStreamer's colour balance, screenshots include it but gamma isn't available");
+                   self.color_ui(ui);
+
+                   ui.separator();
+                   ui.horizontal_wrapped(|ui| {
+                      ui.label("Scaling");
+                      for scaler in Scaler::ALL {
+                         ui.radio_value(&mut self.saved_settings.scaler, *scaler, scaler.label());
+                      }
+                   });
+
+                   ui.separator();
+                   ui.label("HDR tone mapping");
+                   self.tone_map_ui(ui);
+
+                   ui.separator();
+                   ui.label("Deinterlacing");
+                   self.deinterlace_ui(ui);
+
+                   ui.separator();
+                   let errors = self.display_texture.shader_errors();
+                   shader_chain_editor(ui, &mut self.saved_settings.shader_chain, &errors);
+                });
+
+                CollapsingHeader::new("Audio").show(ui, |ui| {
+                   ui.horizontal(|ui| {
+                      ui.label("Volume curve");
+                      for curve in VolumeCurve::ALL {
+                         ui.radio_value(&mut self.saved_settings.volume_curve, *curve, curve.label());
+                      }
+                   });
+
+                   let max = Slider::new(&mut self.saved_settings.max_volume, 1.0..=4.0)
+                       .prefix("Max volume ")
+                       .custom_formatter(|v, _| format_gain(v));
+                   if ui.add(max).changed() {
+                      self.mut_backend().set_max_volume(self.saved_settings.max_volume).unwrap();
+                   }
+
+                   ui.checkbox(&mut self.saved_settings.hardware_volume, "Use the output device volume")
+                       .on_hover_text("Only for sinks that support it, applies to the next file opened");
+                });
+
+                CollapsingHeader::new("Audio filters").show(ui, |ui| {
+                   self.audio_filters_ui(ui);
+                });
+
+                CollapsingHeader::new("On screen display").show(ui, |ui| {
+                   self.saved_settings.osd.settings_ui(ui);
+                });
+
+                CollapsingHeader::new("Keybindings").default_open(true).show(ui, |ui| {
+                   keymap_editor(ui, &mut self.saved_settings.keymap, &mut self.temp_settings.rebind_capture);
+                });
+             });
+          });
+
+      if !open {
+         self.temp_settings.rebind_capture = None;
+      }
+      self.temp_settings.show_settings = open;
+   }
+}
@@ -0,0 +1,212 @@
+use std::time::Duration;
+use anyhow::Result;
+use eframe::egui::{vec2, Color32, Rect, Stroke, Ui, UiBuilder};
+use crate::gstreamer_internals::backend_framework::GstreamerBackendFramework;
+use crate::gui::player::{SavedSettings, VidioPlayer};
+use crate::wgpu::pack::WgpuRenderPack;
+
+/// sizes small tiles have their frames scaled down to, bucketed so resizing doesn't renegotiate every frame,
+/// anything bigger than the last is decoded at full size
+const DECODE_WIDTHS: &[u32] = &[320, 480, 640, 960, 1280, 1920];
+const DECODE_HEIGHTS: &[u32] = &[180, 270, 360, 540, 720, 1080];
+
+/// how often to redraw when nothing is playing, so probes and paused seeks still show up
+const IDLE_REPAINT: Duration = Duration::from_millis(250);
+
+/// frame size limit for a view ``pixels`` big, ``None`` when it's big enough to want the full frame
+pub(crate) fn decode_limit(pixels: (u32, u32)) -> Option<(u32, u32)> {
+   let width = DECODE_WIDTHS.iter().find(|w| **w >= pixels.0)?;
+   let height = DECODE_HEIGHTS.iter().find(|h| **h >= pixels.1)?;
+   Some((*width, *height))
+}
+
+/// columns and rows that fit ``count`` tiles, as square as possible leaning wide
+fn grid_shape(count: usize) -> (usize, usize) {
+   let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+   let rows = count.div_ceil(columns).max(1);
+   (columns, rows)
+}
+
+/// ``count`` equal tiles over ``area`` in reading order
+fn tile_rects(area: Rect, count: usize, spacing: f32) -> Vec<Rect> {
+   let (columns, rows) = grid_shape(count);
+   let size = vec2(
+      (area.width() - spacing * (columns - 1) as f32) / columns as f32,
+      (area.height() - spacing * (rows - 1) as f32) / rows as f32,
+   );
+
+   (0..count)
+       .map(|i| {
+          let (column, row) = (i % columns, i / columns);
+          let min = area.min + vec2(column as f32, row as f32) * (size + vec2(spacing, spacing));
+          Rect::from_min_size(min, size)
+       })
+       .collect()
+}
+
+/// several players in a grid sharing one render pack, for watching many streams at once,
+/// clicking a tile focuses it so it's the one that's audible and takes the keyboard, double clicking maximizes it
+pub struct PlayerGrid<B: GstreamerBackendFramework> {
+   players: Vec<VidioPlayer<B>>,
+   focused: usize,
+   maximized: Option<usize>,
+   /// gap between tiles in points
+   pub spacing: f32,
+}
+
+impl<Backend: GstreamerBackendFramework> Default for PlayerGrid<Backend> {
+   fn default() -> Self {
+      Self {
+         players: vec![],
+         focused: 0,
+         maximized: None,
+         spacing: 2.0,
+      }
+   }
+}
+
+impl<Backend: GstreamerBackendFramework> PlayerGrid<Backend> {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   /// adds a tile playing ``uri``
+   pub fn open(&mut self, uri: &str, saved_settings: SavedSettings) -> Result<()> {
+      self.players.push(VidioPlayer::new_with_uri(uri, saved_settings)?);
+      Ok(())
+   }
+
+   pub fn push(&mut self, player: VidioPlayer<Backend>) {
+      self.players.push(player);
+   }
+
+   pub fn remove(&mut self, index: usize) -> Option<VidioPlayer<Backend>> {
+      if index >= self.players.len() {
+         return None;
+      }
+      let player = self.players.remove(index);
+      self.maximized = None;
+      if self.focused > index {
+         self.focused -= 1;
+      }
+      self.focused = self.focused.min(self.players.len().saturating_sub(1));
+      Some(player)
+   }
+
+   pub fn players(&self) -> &[VidioPlayer<Backend>] {
+      &self.players
+   }
+
+   pub fn players_mut(&mut self) -> &mut [VidioPlayer<Backend>] {
+      &mut self.players
+   }
+
+   pub fn focused(&self) -> Option<usize> {
+      (self.focused < self.players.len()).then_some(self.focused)
+   }
+
+   pub fn set_focused(&mut self, index: usize) {
+      self.focused = index.min(self.players.len().saturating_sub(1));
+   }
+
+   pub fn maximized(&self) -> Option<usize> {
+      self.maximized
+   }
+
+   /// shows only ``index`` across the whole grid, ``None`` goes back to the grid
+   pub fn set_maximized(&mut self, index: Option<usize>) {
+      self.maximized = index.filter(|i| *i < self.players.len());
+   }
+
+   /// draws every tile, or just the maximized one, and asks for a repaint when the quickest playing tile needs one
+   pub fn show<R: Into<WgpuRenderPack>>(&mut self, ui: &mut Ui, in_pack: R) -> Result<()> {
+      let wgpu_render_pack: WgpuRenderPack = in_pack.into();
+      let area = ui.available_rect_before_wrap();
+
+      let shown: Vec<usize> = match self.maximized {
+         Some(i) => vec![i],
+         None => (0..self.players.len()).collect(),
+      };
+      let rects = tile_rects(area, shown.len(), self.spacing);
+
+      // tiles hidden behind a maximized one still have their frames taken
+      if let Some(maximized) = self.maximized {
+         for (i, player) in self.players.iter_mut().enumerate() {
+            if i != maximized {
+               player.update_hidden_tile(&wgpu_render_pack)?;
+            }
+         }
+      }
+
+      let mut clicked = None;
+      let mut double_clicked = None;
+      for (i, rect) in shown.into_iter().zip(rects) {
+         let focused = i == self.focused;
+         let resp = ui.allocate_new_ui(UiBuilder::new().max_rect(rect).id_salt(("player tile", i)), |ui| {
+            ui.set_clip_rect(rect);
+            self.players[i].show_tile(ui, &wgpu_render_pack, focused)
+         }).inner?;
+
+         let Some(resp) = resp else { continue };
+         if resp.clicked() {
+            clicked = Some(i);
+         }
+         if resp.double_clicked() {
+            double_clicked = Some(i);
+         }
+
+         if focused && self.players.len() > 1 {
+            ui.painter().rect_stroke(rect, 0.0, Stroke::new(2.0, Color32::from_rgb(80, 140, 255)));
+         }
+      }
+
+      if let Some(i) = clicked {
+         self.focused = i;
+      }
+      if let Some(i) = double_clicked {
+         self.maximized = match self.maximized {
+            Some(_) => None,
+            None => Some(i),
+         };
+      }
+
+      // one repaint request for every tile, as soon as the fastest playing one has a new frame,
+      // variable framerate streams report no frametime and just get the idle rate
+      let frametime = self.players
+          .iter()
+          .filter_map(|player| player.backend())
+          .filter(|backend| backend.is_playing())
+          .filter_map(|backend| Duration::try_from_secs_f64(backend.get_frametime()).ok())
+          .min();
+      ui.ctx().request_repaint_after(frametime.unwrap_or(IDLE_REPAINT));
+      Ok(())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use eframe::egui::pos2;
+
+   #[test]
+   fn grid_shapes() {
+      assert_eq!(grid_shape(1), (1, 1));
+      assert_eq!(grid_shape(4), (2, 2));
+      assert_eq!(grid_shape(5), (3, 2));
+      assert_eq!(grid_shape(16), (4, 4));
+   }
+
+   #[test]
+   fn tiles_cover_the_area() {
+      let area = Rect::from_min_max(pos2(0.0, 0.0), pos2(1000.0, 500.0));
+      let rects = tile_rects(area, 4, 0.0);
+      assert_eq!(rects[0], Rect::from_min_max(pos2(0.0, 0.0), pos2(500.0, 250.0)));
+      assert_eq!(rects[3], Rect::from_min_max(pos2(500.0, 250.0), pos2(1000.0, 500.0)));
+   }
+
+   #[test]
+   fn small_tiles_decode_small() {
+      assert_eq!(decode_limit((400, 220)), Some((480, 270)));
+      assert_eq!(decode_limit((2560, 1440)), None);
+   }
+}
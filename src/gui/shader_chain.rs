@@ -0,0 +1,60 @@
+use eframe::egui::{Button, Color32, RichText, Ui};
+use crate::wgpu::shader_chain::{list_user_shaders, user_shader_dir, BuiltinShader, ChainEntry, ChainShader};
+
+/// reorderable list of post process shaders with an add menu of the builtins and user shader files,
+/// ``errors`` are shown under the shader they belong to
+pub fn shader_chain_editor(ui: &mut Ui, chain: &mut Vec<ChainEntry>, errors: &[(String, String)]) {
+   let mut move_up = None;
+   let mut remove = None;
+
+   for (i, entry) in chain.iter_mut().enumerate() {
+      let label = entry.shader.label();
+      ui.horizontal(|ui| {
+         ui.checkbox(&mut entry.enabled, &label);
+         if ui.add_enabled(i > 0, Button::new("⏶").small()).on_hover_text("Run earlier").clicked() {
+            move_up = Some(i);
+         }
+         if ui.small_button("🗙").on_hover_text("Remove").clicked() {
+            remove = Some(i);
+         }
+      });
+
+      if let Some((_, error)) = errors.iter().find(|(shader, _)| *shader == label).filter(|_| entry.enabled) {
+         ui.label(RichText::new(error).small().color(Color32::LIGHT_RED).monospace());
+      }
+   }
+
+   if let Some(i) = move_up {
+      chain.swap(i, i - 1);
+   }
+   if let Some(i) = remove {
+      chain.remove(i);
+   }
+
+   ui.menu_button("Add shader", |ui| {
+      for builtin in BuiltinShader::ALL {
+         if ui.button(builtin.label()).clicked() {
+            chain.push(ChainEntry { shader: ChainShader::Builtin(*builtin), enabled: true });
+            ui.close_menu();
+         }
+      }
+
+      ui.separator();
+
+      let user_shaders = list_user_shaders();
+      if user_shaders.is_empty() {
+         ui.label("No user shaders");
+      }
+      for path in user_shaders {
+         let shader = ChainShader::File(path);
+         if ui.button(shader.label()).clicked() {
+            chain.push(ChainEntry { shader, enabled: true });
+            ui.close_menu();
+         }
+      }
+   });
+
+   if let Ok(dir) = user_shader_dir() {
+      ui.label(RichText::new(format!("User shaders go in {}, they only define fs_main and reload on save", dir.display())).small().weak());
+   }
+}
@@ -0,0 +1,128 @@
+use eframe::egui::{pos2, vec2, Color32, Rect, Response, Sense, Stroke, Ui, Widget};
+
+/// formats seconds as ``h:mm:ss`` or ``m:ss`` for anything under an hour
+pub fn format_timecode(seconds: f64) -> String {
+   let total = seconds.max(0.0) as u64;
+   let (h, m, s) = (total / 3600, (total / 60) % 60, total % 60);
+   match h {
+      0 => format!("{m}:{s:02}"),
+      _ => format!("{h}:{m:02}:{s:02}"),
+   }
+}
+
+/// seek bar that can draw markers and highlighted ranges on top of the rail,
+/// ``changed()`` is set while the user clicks or drags it
+pub struct Timeline<'a> {
+   value: &'a mut f64,
+   max: f64,
+   markers: Vec<(f64, Color32)>,
+   ranges: Vec<(f64, f64, Color32)>,
+   waveform: Option<&'a [u8]>,
+}
+
+impl<'a> Timeline<'a> {
+   pub fn new(value: &'a mut f64, max: f64) -> Self {
+      Self {
+         value,
+         max,
+         markers: vec![],
+         ranges: vec![],
+         waveform: None,
+      }
+   }
+
+   /// vertical line at ``at`` seconds
+   pub fn marker(mut self, at: f64, color: Color32) -> Self {
+      self.markers.push((at, color));
+      self
+   }
+
+   /// tinted section of the rail between ``start`` and ``end`` seconds
+   pub fn range(mut self, start: f64, end: f64, color: Color32) -> Self {
+      self.ranges.push((start, end, color));
+      self
+   }
+
+   /// peaks spread evenly over the whole length, drawn behind the rail on a taller bar
+   pub fn waveform(mut self, peaks: &'a [u8]) -> Self {
+      self.waveform = Some(peaks).filter(|peaks| !peaks.is_empty());
+      self
+   }
+}
+
+impl Widget for Timeline<'_> {
+   fn ui(self, ui: &mut Ui) -> Response {
+      let height = match self.waveform {
+         Some(_) => ui.spacing().interact_size.y * 2.0,
+         None => ui.spacing().interact_size.y,
+      };
+      let desired_size = vec2(ui.available_width(), height);
+      let (rect, mut response) = ui.allocate_exact_size(desired_size, Sense::click_and_drag());
+
+      let max = self.max.max(f64::EPSILON);
+      let to_x = |t: f64| rect.left() + rect.width() * (t / max).clamp(0.0, 1.0) as f32;
+      let to_time = |x: f32| ((x - rect.left()) / rect.width()).clamp(0.0, 1.0) as f64 * max;
+
+      if let Some(pointer) = response.interact_pointer_pos() {
+         let new_value = to_time(pointer.x);
+         if new_value != *self.value {
+            *self.value = new_value;
+            response.mark_changed();
+         }
+      }
+
+      if ui.is_rect_visible(rect) {
+         let painter = ui.painter();
+         let visuals = ui.style().interact(&response);
+
+         if let Some(peaks) = self.waveform {
+            let played_x = to_x(*self.value);
+            let columns = rect.width().round().max(1.0) as usize;
+            let column_width = rect.width() / columns as f32;
+            // one column per point, the loudest peak under it
+            for column in 0..columns {
+               let x = rect.left() + (column as f32 + 0.5) * column_width;
+               let start = (column * peaks.len() / columns).min(peaks.len() - 1);
+               let end = ((column + 1) * peaks.len() / columns).clamp(start + 1, peaks.len());
+               let peak = peaks[start..end].iter().copied().max().unwrap_or(0);
+
+               let half = rect.height() / 2.0 * peak as f32 / 255.0;
+               let color = match x <= played_x {
+                  true => ui.visuals().selection.bg_fill.gamma_multiply(0.6),
+                  false => ui.visuals().widgets.inactive.bg_fill,
+               };
+               painter.line_segment([pos2(x, rect.center().y - half), pos2(x, rect.center().y + half)], Stroke::new(column_width, color));
+            }
+         }
+
+         let rail_height = match self.waveform {
+            Some(_) => rect.height() * 0.15,
+            None => rect.height() * 0.3,
+         };
+         let rail = Rect::from_center_size(rect.center(), vec2(rect.width(), rail_height));
+         let rounding = rail.height() / 2.0;
+         painter.rect_filled(rail, rounding, ui.visuals().extreme_bg_color);
+
+         for (start, end, color) in &self.ranges {
+            let range_rect = Rect::from_x_y_ranges(to_x(*start)..=to_x(*end), rail.y_range());
+            painter.rect_filled(range_rect, 0.0, *color);
+         }
+
+         let played = Rect::from_min_max(rail.min, pos2(to_x(*self.value), rail.max.y));
+         painter.rect_filled(played, rounding, ui.visuals().selection.bg_fill);
+
+         for (at, color) in &self.markers {
+            let x = to_x(*at);
+            painter.line_segment([pos2(x, rect.top()), pos2(x, rect.bottom())], Stroke::new(2.0, *color));
+         }
+
+         let handle = pos2(to_x(*self.value), rect.center().y);
+         painter.circle(handle, ui.spacing().interact_size.y / 3.0, visuals.bg_fill, visuals.fg_stroke);
+      }
+
+      match response.hover_pos() {
+         Some(hover) => response.on_hover_text_at_pointer(format_timecode(to_time(hover.x))),
+         None => response,
+      }
+   }
+}
@@ -0,0 +1,404 @@
+use eframe::egui::{pos2, vec2, Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// furthest the view zooms in or out
+pub const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.25..=8.0;
+
+/// shape the video is drawn at, ``Source`` uses the frame size and pixel aspect ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AspectOverride {
+   #[default]
+   Source,
+   Ratio16x9,
+   Ratio4x3,
+   Ratio21x9,
+   /// fills the view, distorting the picture
+   Stretch,
+   /// fills the view keeping the aspect, cutting off whatever doesn't fit
+   Fill,
+}
+
+impl AspectOverride {
+   pub const ALL: &'static [AspectOverride] = &[Self::Source, Self::Ratio16x9, Self::Ratio4x3, Self::Ratio21x9, Self::Stretch, Self::Fill];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Source => "Source",
+         Self::Ratio16x9 => "16:9",
+         Self::Ratio4x3 => "4:3",
+         Self::Ratio21x9 => "21:9",
+         Self::Stretch => "Stretch",
+         Self::Fill => "Fill",
+      }
+   }
+
+   /// the one after this in ``ALL``, wrapping round
+   pub fn next(self) -> Self {
+      let i = Self::ALL.iter().position(|a| *a == self).unwrap_or(0);
+      Self::ALL[(i + 1) % Self::ALL.len()]
+   }
+
+   fn ratio(self) -> Option<f32> {
+      match self {
+         Self::Ratio16x9 => Some(16.0 / 9.0),
+         Self::Ratio4x3 => Some(4.0 / 3.0),
+         Self::Ratio21x9 => Some(21.0 / 9.0),
+         Self::Source | Self::Stretch | Self::Fill => None,
+      }
+   }
+}
+
+/// what happens when black bars are found in the video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlackBarCrop {
+   /// no detection at all
+   Off,
+   /// says so on the osd, the crop is picked from the menu
+   #[default]
+   Offer,
+   /// crops as soon as they're found, once per file
+   Auto,
+}
+
+impl BlackBarCrop {
+   pub const ALL: &'static [BlackBarCrop] = &[Self::Off, Self::Offer, Self::Auto];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Off => "Off",
+         Self::Offer => "Offer",
+         Self::Auto => "Crop automatically",
+      }
+   }
+}
+
+/// a centred crop to a fixed aspect ratio, or to the detected black bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CropPreset {
+   #[default]
+   None,
+   /// whatever ``ViewTransform::black_bars`` holds, nothing until bars are found
+   Detected,
+   /// 16:9 picture letterboxed into a 4:3 frame
+   RemoveLetterbox,
+   Ratio4x3,
+   Ratio239,
+}
+
+impl CropPreset {
+   pub const ALL: &'static [CropPreset] = &[Self::None, Self::Detected, Self::RemoveLetterbox, Self::Ratio4x3, Self::Ratio239];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::None => "None",
+         Self::Detected => "Detected black bars",
+         Self::RemoveLetterbox => "Remove letterbox",
+         Self::Ratio4x3 => "4:3",
+         Self::Ratio239 => "2.39:1",
+      }
+   }
+
+   /// the one after this in ``ALL``, wrapping round
+   pub fn next(self) -> Self {
+      let i = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+      Self::ALL[(i + 1) % Self::ALL.len()]
+   }
+
+   fn ratio(self) -> Option<f32> {
+      match self {
+         Self::None | Self::Detected => None,
+         Self::RemoveLetterbox => Some(16.0 / 9.0),
+         Self::Ratio4x3 => Some(4.0 / 3.0),
+         Self::Ratio239 => Some(2.39),
+      }
+   }
+
+   /// uv of the part of a ``frame_aspect`` frame that's kept
+   pub fn uv_rect(self, frame_aspect: f32) -> Rect {
+      let full = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+      let Some(ratio) = self.ratio() else {
+         return full;
+      };
+
+      let size = match frame_aspect > ratio {
+         // wider than the crop, cut the sides
+         true => vec2(ratio / frame_aspect, 1.0),
+         false => vec2(1.0, frame_aspect / ratio),
+      };
+      Rect::from_center_size(full.center(), size)
+   }
+}
+
+/// turns then mirrors the frame, the same order gstreamer's ``image-orientation`` tag uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Orientation {
+   /// clockwise, 0..4
+   pub quarter_turns: u8,
+   /// mirrored left to right after turning
+   pub flipped: bool,
+}
+
+impl Orientation {
+   /// reads tags like ``rotate-90`` or ``flip-rotate-270``
+   pub fn from_tag(tag: &str) -> Option<Self> {
+      let (flipped, degrees) = match tag.strip_prefix("flip-rotate-") {
+         Some(degrees) => (true, degrees),
+         None => (false, tag.strip_prefix("rotate-")?),
+      };
+      let quarter_turns = match degrees {
+         "0" => 0,
+         "90" => 1,
+         "180" => 2,
+         "270" => 3,
+         _ => return None,
+      };
+      Some(Self {
+         quarter_turns,
+         flipped,
+      })
+   }
+
+   pub fn label(self) -> String {
+      let degrees = self.quarter_turns as u32 * 90;
+      match self.flipped {
+         true => format!("{degrees}° flipped"),
+         false => format!("{degrees}°"),
+      }
+   }
+
+   /// turns what's on screen, which under a mirror is the other way round for the frame
+   pub fn rotate_clockwise(&mut self) {
+      let turn = if self.flipped { 3 } else { 1 };
+      self.quarter_turns = (self.quarter_turns + turn) % 4;
+   }
+
+   pub fn rotate_counter_clockwise(&mut self) {
+      let turn = if self.flipped { 1 } else { 3 };
+      self.quarter_turns = (self.quarter_turns + turn) % 4;
+   }
+
+   pub fn flip_horizontal(&mut self) {
+      self.flipped = !self.flipped;
+   }
+
+   /// a vertical flip is a horizontal one turned half way round
+   pub fn flip_vertical(&mut self) {
+      self.flipped = !self.flipped;
+      self.quarter_turns = (self.quarter_turns + 2) % 4;
+   }
+
+   /// width and height trade places
+   pub fn is_sideways(self) -> bool {
+      self.quarter_turns % 2 == 1
+   }
+
+   /// as ``orientation`` in ``chain_header.wgsl``
+   pub fn bits(self) -> u32 {
+      (self.quarter_turns % 4) as u32 | (self.flipped as u32) << 2
+   }
+
+   /// uv in the frame to uv on screen
+   fn apply(self, uv: Pos2) -> Pos2 {
+      let turned = match self.quarter_turns % 4 {
+         1 => pos2(1.0 - uv.y, uv.x),
+         2 => pos2(1.0 - uv.x, 1.0 - uv.y),
+         3 => pos2(uv.y, 1.0 - uv.x),
+         _ => uv,
+      };
+      match self.flipped {
+         true => pos2(1.0 - turned.x, turned.y),
+         false => turned,
+      }
+   }
+
+   /// a uv rect of the frame as it ends up on screen
+   pub fn apply_rect(self, rect: Rect) -> Rect {
+      Rect::from_two_pos(self.apply(rect.min), self.apply(rect.max))
+   }
+
+   /// uv on screen back to uv in the frame, undoing ``apply``
+   pub fn unapply(self, uv: Pos2) -> Pos2 {
+      let unflipped = match self.flipped {
+         true => pos2(1.0 - uv.x, uv.y),
+         false => uv,
+      };
+      let unturn = Self {
+         quarter_turns: (4 - self.quarter_turns % 4) % 4,
+         flipped: false,
+      };
+      unturn.apply(unflipped)
+   }
+}
+
+/// where the video lands in the view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewLayout {
+   /// size of the video at zoom 1, pan is measured in it
+   pub fitted: Vec2,
+   /// the on screen part of the video, inside the view
+   pub visible: Rect,
+   /// uv of the frame shown in ``visible``
+   pub uv: Rect,
+}
+
+/// zoom, pan, crop and aspect of the video view, reset for every file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewTransform {
+   pub zoom: f32,
+   /// offset of the video centre from the view centre, in fractions of the fitted size
+   pub pan: Vec2,
+   pub crop: CropPreset,
+   pub aspect: AspectOverride,
+   /// uv of the picture inside the black bars, once they've been detected
+   pub black_bars: Option<Rect>,
+   /// from the stream's orientation tag until changed by hand
+   pub orientation: Orientation,
+}
+
+impl Default for ViewTransform {
+   fn default() -> Self {
+      Self {
+         zoom: 1.0,
+         pan: Vec2::ZERO,
+         crop: CropPreset::default(),
+         aspect: AspectOverride::default(),
+         black_bars: None,
+         orientation: Orientation::default(),
+      }
+   }
+}
+
+impl ViewTransform {
+   pub fn is_zoomed(&self) -> bool {
+      self.zoom != 1.0 || self.pan != Vec2::ZERO
+   }
+
+   pub fn reset_zoom(&mut self) {
+      self.zoom = 1.0;
+      self.pan = Vec2::ZERO;
+   }
+
+   /// zooms by ``factor`` keeping whatever is under ``anchor`` where it is,
+   /// ``anchor`` is relative to the view centre in fractions of the fitted size
+   pub fn zoom_at(&mut self, factor: f32, anchor: Vec2) {
+      let zoom = (self.zoom * factor).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+      let applied = zoom / self.zoom;
+      self.pan = anchor - (anchor - self.pan) * applied;
+      self.zoom = zoom;
+   }
+
+   /// uv of the part of the frame that's kept, ``frame_aspect`` and the result are after orientation
+   pub fn crop_uv(&self, frame_aspect: f32) -> Rect {
+      match (self.crop, self.black_bars) {
+         (CropPreset::Detected, Some(bars)) => self.orientation.apply_rect(bars),
+         (crop, _) => crop.uv_rect(frame_aspect),
+      }
+   }
+
+   /// ``frame_aspect`` is width over height of the decoded frame with the pixel aspect ratio applied,
+   /// ``None`` when the video is panned entirely out of ``view``, the uv in the result is after orientation
+   pub fn layout(&self, frame_aspect: f32, view: Rect) -> Option<ViewLayout> {
+      let frame_aspect = match self.orientation.is_sideways() {
+         true => 1.0 / frame_aspect,
+         false => frame_aspect,
+      };
+      let crop = self.crop_uv(frame_aspect);
+      let content_aspect = frame_aspect * crop.width() / crop.height();
+      let shown_aspect = match self.aspect {
+         AspectOverride::Stretch => view.aspect_ratio(),
+         other => other.ratio().unwrap_or(content_aspect),
+      };
+
+      // contain, or cover for fill
+      let width_limited = (view.aspect_ratio() < shown_aspect) != (self.aspect == AspectOverride::Fill);
+      let fitted = match width_limited {
+         true => vec2(view.width(), view.width() / shown_aspect),
+         false => vec2(view.height() * shown_aspect, view.height()),
+      };
+
+      let screen = Rect::from_center_size(view.center() + self.pan * fitted, fitted * self.zoom);
+      let visible = screen.intersect(view);
+      if !visible.is_positive() {
+         return None;
+      }
+
+      let to_uv = |p: f32, min: f32, size: f32, uv_min: f32, uv_size: f32| uv_min + (p - min) / size * uv_size;
+      let uv = Rect::from_min_max(
+         pos2(
+            to_uv(visible.min.x, screen.min.x, screen.width(), crop.min.x, crop.width()),
+            to_uv(visible.min.y, screen.min.y, screen.height(), crop.min.y, crop.height()),
+         ),
+         pos2(
+            to_uv(visible.max.x, screen.min.x, screen.width(), crop.min.x, crop.width()),
+            to_uv(visible.max.y, screen.min.y, screen.height(), crop.min.y, crop.height()),
+         ),
+      );
+
+      Some(ViewLayout {
+         fitted,
+         visible,
+         uv,
+      })
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn view() -> Rect {
+      Rect::from_min_size(pos2(0.0, 0.0), vec2(1600.0, 900.0))
+   }
+
+   #[test]
+   fn fits_with_letterbox() {
+      let layout = ViewTransform::default().layout(2.0, view()).unwrap();
+      assert_eq!(layout.visible, Rect::from_min_max(pos2(0.0, 50.0), pos2(1600.0, 850.0)));
+      assert_eq!(layout.uv, Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)));
+   }
+
+   #[test]
+   fn crop_cuts_the_long_side() {
+      let uv = CropPreset::Ratio4x3.uv_rect(16.0 / 9.0);
+      assert!((uv.width() - 0.75).abs() < 1e-5);
+      assert_eq!(uv.height(), 1.0);
+   }
+
+   #[test]
+   fn sideways_swaps_the_aspect() {
+      let transform = ViewTransform {
+         orientation: Orientation::from_tag("rotate-90").unwrap(),
+         ..Default::default()
+      };
+      let layout = transform.layout(16.0 / 9.0, view()).unwrap();
+      assert!((layout.visible.aspect_ratio() - 9.0 / 16.0).abs() < 1e-4);
+   }
+
+   #[test]
+   fn flips_undo_themselves() {
+      let mut orientation = Orientation::from_tag("flip-rotate-90").unwrap();
+      let corner = orientation.apply(pos2(0.0, 0.0));
+      orientation.rotate_clockwise();
+      orientation.flip_vertical();
+      orientation.flip_vertical();
+      orientation.rotate_counter_clockwise();
+      assert_eq!(orientation, Orientation::from_tag("flip-rotate-90").unwrap());
+      assert_eq!(corner, pos2(0.0, 0.0));
+      assert_eq!(orientation.unapply(orientation.apply(pos2(0.25, 0.75))), pos2(0.25, 0.75));
+   }
+
+   #[test]
+   fn zoom_keeps_anchor_still() {
+      let mut transform = ViewTransform::default();
+      let anchor = vec2(0.25, -0.1);
+      let before = transform.layout(16.0 / 9.0, view()).unwrap();
+      let point = view().center() + anchor * before.fitted;
+      let uv_before = before.uv.min + (point - before.visible.min) / before.visible.size() * before.uv.size();
+
+      transform.zoom_at(2.0, anchor);
+      let after = transform.layout(16.0 / 9.0, view()).unwrap();
+      let uv_after = after.uv.min + (point - after.visible.min) / after.visible.size() * after.uv.size();
+
+      assert!((uv_before - uv_after).length() < 1e-4);
+   }
+}
@@ -0,0 +1,127 @@
+use eframe::egui::{pos2, vec2, Color32, Painter, Rect, Response, Sense, Shape, Stroke, TextureHandle, Ui};
+use serde::{Deserialize, Serialize};
+use crate::gstreamer_internals::visualisation::{ChannelLevel, LEVEL_FLOOR, SPECTRUM_THRESHOLD};
+
+/// what's drawn in place of the video for audio only files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AudioVisual {
+   #[default]
+   Spectrum,
+   Oscilloscope,
+   /// falls back to the spectrum when the file has none
+   CoverArt,
+   /// a gstreamer visualisation element rendered into the video sink
+   Plugin,
+}
+
+impl AudioVisual {
+   pub const ALL: &'static [AudioVisual] = &[Self::Spectrum, Self::Oscilloscope, Self::CoverArt, Self::Plugin];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Spectrum => "Spectrum",
+         Self::Oscilloscope => "Oscilloscope",
+         Self::CoverArt => "Cover art",
+         Self::Plugin => "GStreamer plugin",
+      }
+   }
+}
+
+/// bars drawn, the linear spectrum bands get grouped into these on a log scale
+const BARS: usize = 64;
+
+/// log spaced bars of the loudest band in each group
+pub fn paint_spectrum(painter: &Painter, rect: Rect, magnitudes: &[f32]) {
+   if magnitudes.len() < 2 {
+      return;
+   }
+
+   let floor = SPECTRUM_THRESHOLD as f32;
+   let bands = magnitudes.len() as f32;
+   let bar_width = rect.width() / BARS as f32;
+
+   for bar in 0..BARS {
+      // band 0 is dc so the scale starts at 1
+      let start = bands.powf(bar as f32 / BARS as f32) as usize;
+      let end = (bands.powf((bar + 1) as f32 / BARS as f32) as usize).max(start + 1).min(magnitudes.len());
+      let Some(loudest) = magnitudes[start.min(end - 1)..end].iter().copied().reduce(f32::max) else {
+         continue;
+      };
+
+      let level = ((loudest - floor) / -floor).clamp(0.0, 1.0);
+      let height = rect.height() * level;
+      let x = rect.left() + bar as f32 * bar_width;
+      let bar_rect = Rect::from_min_max(pos2(x + 1.0, rect.bottom() - height), pos2(x + bar_width - 1.0, rect.bottom()));
+
+      let color = Color32::from_rgb((80.0 + 175.0 * level) as u8, (200.0 - 120.0 * level) as u8, 255);
+      painter.rect_filled(bar_rect, 1.0, color);
+   }
+}
+
+/// the latest samples as a line through the middle of ``rect``
+pub fn paint_oscilloscope(painter: &Painter, rect: Rect, samples: &[f32]) {
+   painter.line_segment([rect.left_center(), rect.right_center()], Stroke::new(1.0, Color32::from_gray(40)));
+
+   if samples.len() < 2 {
+      return;
+   }
+
+   let step = rect.width() / (samples.len() - 1) as f32;
+   let points = samples
+       .iter()
+       .enumerate()
+       .map(|(i, s)| pos2(rect.left() + i as f32 * step, rect.center().y - s.clamp(-1.0, 1.0) * rect.height() * 0.45))
+       .collect();
+
+   painter.add(Shape::line(points, Stroke::new(1.5, Color32::from_rgb(120, 220, 160))));
+}
+
+/// ``texture`` fitted inside ``rect`` keeping its aspect ratio
+pub fn paint_cover(painter: &Painter, rect: Rect, texture: &TextureHandle) {
+   let size = texture.size_vec2();
+   let scale = (rect.width() / size.x).min(rect.height() / size.y);
+   let cover_rect = Rect::from_center_size(rect.center(), size * scale);
+
+   painter.image(texture.id(), cover_rect, Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)), Color32::WHITE);
+   painter.rect_stroke(cover_rect.expand(1.0), 0.0, Stroke::new(1.0, Color32::from_gray(60)));
+}
+
+/// stacked horizontal meter per channel, rms as the bar and peak as a tick, hover lists the numbers
+pub fn level_meters(ui: &mut Ui, levels: &[ChannelLevel]) -> Response {
+   let (rect, response) = ui.allocate_exact_size(vec2(80.0, ui.spacing().interact_size.y), Sense::hover());
+
+   if ui.is_rect_visible(rect) {
+      let painter = ui.painter();
+      painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+      let to_x = |db: f64| rect.left() + rect.width() * ((db - LEVEL_FLOOR) / -LEVEL_FLOOR).clamp(0.0, 1.0) as f32;
+      let row_height = rect.height() / levels.len().max(1) as f32;
+
+      for (i, level) in levels.iter().enumerate() {
+         let top = rect.top() + i as f32 * row_height;
+         let row = Rect::from_min_max(pos2(rect.left(), top + 1.0), pos2(rect.right(), top + row_height - 1.0));
+
+         // green up to -12 dBFS, yellow to -3 and red past that
+         let color = match level.rms_db {
+            db if db > -3.0 => Color32::from_rgb(230, 70, 60),
+            db if db > -12.0 => Color32::from_rgb(230, 200, 60),
+            _ => Color32::from_rgb(80, 200, 110),
+         };
+         painter.rect_filled(Rect::from_min_max(row.min, pos2(to_x(level.rms_db), row.max.y)), 0.0, color);
+
+         let peak = to_x(level.peak_db);
+         painter.line_segment([pos2(peak, row.top()), pos2(peak, row.bottom())], Stroke::new(1.5, Color32::WHITE));
+      }
+   }
+
+   let text = levels
+       .iter()
+       .enumerate()
+       .map(|(i, l)| format!("ch {i}  peak {:.1} dB  rms {:.1} dB", l.peak_db, l.rms_db))
+       .collect::<Vec<_>>()
+       .join("\n");
+   match text.is_empty() {
+      true => response,
+      false => response.on_hover_text(text),
+   }
+}
@@ -11,15 +11,42 @@ pub mod gstreamer_internals {
     pub mod prober;
     pub mod backend_framework;
     pub mod backend_v2;
+    pub mod ab_loop;
+    pub mod probe_cache;
+    pub mod volume;
+    pub mod audio_filters;
+    pub mod loudness;
+    pub mod visualisation;
+    pub mod audio_scan;
+    pub mod waveform;
+    pub mod color_balance;
+    pub mod black_bars;
+    pub mod deinterlace;
 }
 
 pub mod gui {
     pub mod player;
+    pub mod timeline;
+    pub mod media_info;
+    pub mod keymap;
+    pub mod osd;
+    pub mod audio_filters;
+    pub mod visualisation;
+    pub mod color;
+    pub mod shader_chain;
+    pub mod view_transform;
+    pub mod player_grid;
+    pub mod comparison;
 }
 
 pub mod wgpu {
     pub mod pack;
     pub mod display_texture;
+    pub mod post_process;
+    pub mod shader_chain;
+    pub mod scaling;
+    pub mod tone_map;
+    pub mod difference;
 }
 
 
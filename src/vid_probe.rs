@@ -0,0 +1,226 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use gstreamer::ClockTime;
+use vid_v2::gstreamer_internals::prober::Probe;
+use vid_v2::gui::timeline::format_timecode;
+
+const MEDIA_EXTENSIONS: &[&str] = &[
+   "mkv", "mp4", "m4v", "webm", "avi", "mov", "ts", "m2ts", "flv", "wmv", "mpg", "mpeg",
+   "mp3", "flac", "ogg", "opus", "wav", "m4a", "aac",
+];
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+   Table,
+   Json,
+   Ndjson,
+}
+
+/// Probes media files with the same discoverer the player uses,
+/// exits with 1 if anything couldn't be read
+#[derive(Parser)]
+#[command(name = "vid-probe")]
+struct Args {
+   /// files, directories, globs or uris
+   #[arg(required = true)]
+   inputs: Vec<String>,
+
+   /// discoverer timeout per file in seconds
+   #[arg(short, long, default_value_t = 5.0, value_parser = parse_timeout)]
+   timeout: f64,
+
+   /// files probed at once, defaults to the number of cpus
+   #[arg(short, long)]
+   jobs: Option<usize>,
+
+   #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+   format: OutputFormat,
+
+   /// probe every file found in directories, not just known media extensions
+   #[arg(long)]
+   all_files: bool,
+}
+
+/// ``ClockTime::from_seconds_f64`` panics on negative or non finite seconds
+fn parse_timeout(arg: &str) -> Result<f64, String> {
+   match arg.parse::<f64>() {
+      Ok(secs) if secs.is_finite() && secs > 0.0 => Ok(secs),
+      Ok(_) => Err("has to be a positive number of seconds".to_string()),
+      Err(err) => Err(err.to_string()),
+   }
+}
+
+fn main() {
+   let args = Args::parse();
+
+   if let Err(err) = gstreamer::init() {
+      eprintln!("Couldn't init gstreamer {err}");
+      std::process::exit(2);
+   }
+
+   let mut failed = false;
+   let mut uris = vec![];
+   for input in &args.inputs {
+      if let Err(err) = expand_input(input, args.all_files, &mut uris) {
+         eprintln!("error: {input}: {err}");
+         failed = true;
+      }
+   }
+
+   let jobs = args.jobs
+       .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, |n| n.get()))
+       .max(1);
+   let results = probe_all(&uris, jobs, ClockTime::from_seconds_f64(args.timeout));
+
+   let mut json_out = vec![];
+
+   for (uri, result) in &results {
+      match (result, args.format) {
+         (Ok(probe), OutputFormat::Table) => print_table(probe),
+         (Ok(probe), OutputFormat::Json) => json_out.push(serde_json::to_value(probe).unwrap()),
+         (Ok(probe), OutputFormat::Ndjson) => println!("{}", serde_json::to_string(probe).unwrap()),
+         (Err(err), format) => {
+            failed = true;
+            eprintln!("error: {uri}: {err}");
+            let value = serde_json::json!({ "uri": uri, "error": err.to_string() });
+            match format {
+               OutputFormat::Table => (),
+               OutputFormat::Json => json_out.push(value),
+               OutputFormat::Ndjson => println!("{value}"),
+            }
+         }
+      }
+   }
+
+   if let OutputFormat::Json = args.format {
+      println!("{}", serde_json::to_string_pretty(&json_out).unwrap());
+   }
+
+   std::process::exit(if failed { 1 } else { 0 });
+}
+
+/// turns one argument into uris, directories are walked recursively
+fn expand_input(input: &str, all_files: bool, out: &mut Vec<String>) -> Result<()> {
+   if input.contains("://") {
+      out.push(input.to_string());
+      return Ok(());
+   }
+
+   let paths: Vec<PathBuf> = match input.contains(['*', '?', '[']) {
+      true => glob::glob(input)?.flatten().collect(),
+      false => vec![PathBuf::from(input)],
+   };
+
+   if paths.is_empty() {
+      anyhow::bail!("Nothing matched");
+   }
+
+   for path in paths {
+      match path.is_dir() {
+         true => walk_dir(&path, all_files, out)?,
+         false => out.push(to_uri(&path)?),
+      }
+   }
+
+   Ok(())
+}
+
+fn walk_dir(dir: &Path, all_files: bool, out: &mut Vec<String>) -> Result<()> {
+   let mut entries = std::fs::read_dir(dir)?.flatten().map(|e| e.path()).collect::<Vec<_>>();
+   entries.sort();
+
+   for path in entries {
+      if path.is_dir() {
+         walk_dir(&path, all_files, out)?;
+         continue;
+      }
+
+      let is_media = path
+          .extension()
+          .and_then(|e| e.to_str())
+          .is_some_and(|e| MEDIA_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+
+      if all_files || is_media {
+         out.push(to_uri(&path)?);
+      }
+   }
+
+   Ok(())
+}
+
+fn to_uri(path: &Path) -> Result<String> {
+   let path = path.canonicalize().with_context(|| format!("Couldn't read {}", path.display()))?;
+   vid_v2::path_to_uri(&path)
+}
+
+/// runs discovery on ``jobs`` threads, results keep the input order
+fn probe_all(uris: &[String], jobs: usize, timeout: ClockTime) -> Vec<(String, Result<Probe>)> {
+   let next = AtomicUsize::new(0);
+   let results = Mutex::new((0..uris.len()).map(|_| None).collect::<Vec<_>>());
+
+   std::thread::scope(|scope| {
+      for _ in 0..jobs.min(uris.len()) {
+         scope.spawn(|| loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let Some(uri) = uris.get(i) else { break };
+
+            let res = Probe::from_uri_with_timeout(uri, timeout);
+            results.lock().unwrap()[i] = Some(res);
+         });
+      }
+   });
+
+   uris.iter()
+       .cloned()
+       .zip(results.into_inner().unwrap().into_iter().flatten())
+       .collect()
+}
+
+fn print_table(probe: &Probe) {
+   let or_dash = |s: &Option<String>| s.clone().unwrap_or_else(|| "-".to_string());
+
+   println!("{}", probe.uri);
+   println!(
+      "  container {:<12} duration {:<10} seekable {:<6} chapters {}",
+      or_dash(&probe.container),
+      probe.duration.map_or("-".to_string(), |d| format_timecode(d.seconds_f64())),
+      probe.seekable,
+      probe.chapters.len(),
+   );
+
+   for (stream, i) in &probe.video_streams {
+      let (w, h) = stream.resolution.unwrap_or((0, 0));
+      println!(
+         "  video {i:<3} {:<16} {:>5}x{:<5} {:>7.3} fps {:<10} {}",
+         or_dash(&stream.codec),
+         w,
+         h,
+         stream.fps.unwrap_or(0.0),
+         or_dash(&stream.pixel_format),
+         stream.hdr.as_ref().map_or("", |h| h.transfer.as_str()),
+      );
+   }
+
+   for (stream, i) in &probe.audio_streams {
+      println!(
+         "  audio {i:<3} {:<16} {:<8} {:>6} Hz {:<4} {}",
+         or_dash(&stream.codec),
+         stream.channel_layout,
+         stream.sample_rate,
+         or_dash(&stream.language),
+         or_dash(&stream.name),
+      );
+   }
+
+   for (stream, i) in &probe.captions {
+      println!(
+         "  sub   {i:<3} {:<16} {:<4} {}",
+         or_dash(&stream.codec),
+         or_dash(&stream.language),
+         or_dash(&stream.name),
+      );
+   }
+}
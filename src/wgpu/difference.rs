@@ -0,0 +1,85 @@
+use eframe::egui::TextureId;
+use eframe::wgpu::{CommandEncoderDescriptor, Device};
+use crate::wgpu::display_texture::{Output, WgpuEguiDisplayTexture};
+use crate::wgpu::pack::WgpuRenderPack;
+use crate::wgpu::post_process::{PassConfig, ShaderPass};
+
+/// matches ``Params`` in ``difference.wgsl``
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DifferenceUniforms {
+   gain: f32,
+   _pad: [f32; 3],
+}
+
+/// draws how far apart two players' decoded frames are into a texture of its own at the first one's video size,
+/// taken before either player's colour adjustments, shader chain and scaler so those neither hide nor add differences
+pub struct DifferencePass {
+   pass: ShaderPass,
+   output: Option<Output>,
+}
+
+impl DifferencePass {
+   pub fn new(device: &Device) -> Self {
+      let config = PassConfig {
+         planes: 2,
+         ..Default::default()
+      };
+
+      Self {
+         pass: ShaderPass::with_config(
+            device,
+            "Difference",
+            include_str!("shaders/difference.wgsl"),
+            std::mem::size_of::<DifferenceUniforms>() as u64,
+            config,
+         ),
+         output: None,
+      }
+   }
+
+   /// redraws the difference scaled by ``gain``, ``None`` until both have a frame,
+   /// a second source of another size is resampled to the first's
+   pub fn update(&mut self, render_pack: &WgpuRenderPack, first: &WgpuEguiDisplayTexture, second: &WgpuEguiDisplayTexture, gain: f32) -> Option<TextureId> {
+      let ((first, size), (second, _)) = (first.decoded()?, second.decoded()?);
+
+      if self.output.as_ref().map(|output| output.texture.size()) != Some(size) {
+         if let Some(output) = self.output.take() {
+            output.free(render_pack);
+         }
+         self.output = Some(Output::create(render_pack, size));
+      }
+      let output = self.output.as_ref()?;
+
+      let uniforms = DifferenceUniforms {
+         gain,
+         _pad: [0.0; 3],
+      };
+      let bind_group = self.pass.bind_planes(&render_pack.device, &[first, second]);
+
+      let mut encoder = render_pack.device.create_command_encoder(&CommandEncoderDescriptor {
+         label: Some("Difference encoder"),
+      });
+      self.pass.run(render_pack, &mut encoder, &bind_group, &output.target, bytemuck::bytes_of(&uniforms));
+      render_pack.queue.submit(Some(encoder.finish()));
+
+      Some(output.texture_id)
+   }
+
+   pub fn free(&mut self, render_pack: &WgpuRenderPack) {
+      if let Some(output) = self.output.take() {
+         output.free(render_pack);
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use crate::wgpu::shader_chain::validate;
+
+   #[test]
+   fn difference_validates() {
+      let source = include_str!("shaders/difference.wgsl");
+      assert!(validate(source, 2).is_ok(), "{:?}", validate(source, 2));
+   }
+}
@@ -1,56 +1,166 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use anyhow::{bail, Result};
 use eframe::egui::TextureId;
-use eframe::wgpu::{AddressMode, Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, FilterMode, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, SamplerDescriptor, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension};
+use eframe::wgpu::{AddressMode, BindGroup, Buffer, BufferDescriptor, BufferUsages, CommandEncoder, CommandEncoderDescriptor, Device, Extent3d, FilterMode, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, SamplerDescriptor, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension};
 use gstreamer_video::video_frame::Readable;
-use gstreamer_video::{VideoFormat, VideoFrame, VideoFrameExt};
+use gstreamer_video::{VideoColorimetry, VideoFormat, VideoFrame, VideoFrameExt};
+use crate::gstreamer_internals::color_balance::ColorAdjustments;
 use crate::wgpu::pack::WgpuRenderPack;
+use crate::wgpu::post_process::{ColorPass, PASS_FORMAT};
+use crate::wgpu::scaling::{ScalePass, Scaler};
+use crate::wgpu::shader_chain::{ChainEntry, Field, ShaderChain};
+use crate::wgpu::tone_map::{HdrTransfer, ToneMapPass, ToneMapSource, ToneMapping, DECODE_FORMAT};
 
-fn aligned_bytes_per_row(width: u32) -> u32 {
-   let bytes_per_pixel = 4; // For example, RGBA format
+/// how a negotiated video format goes onto the gpu, one texture per plane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UploadFormat {
+   /// 8 bit srgb, the colour pass reads it directly
+   Rgba8,
+   Rgb10a2,
+   Rgba16,
+   /// P010 and P016
+   YuvPlanes,
+}
+
+impl UploadFormat {
+   /// the player only asks gstreamer for these
+   fn of(format: VideoFormat) -> Result<Self> {
+      match format {
+         VideoFormat::Rgba => Ok(Self::Rgba8),
+         VideoFormat::Rgb10a2Le => Ok(Self::Rgb10a2),
+         VideoFormat::Rgba64Le => Ok(Self::Rgba16),
+         VideoFormat::P01010le | VideoFormat::P016Le => Ok(Self::YuvPlanes),
+         other => bail!("Gstreamer player can't upload {other:?}"),
+      }
+   }
+
+   fn planes(self) -> &'static [TextureFormat] {
+      match self {
+         Self::Rgba8 => &[TextureFormat::Rgba8UnormSrgb],
+         Self::Rgb10a2 => &[TextureFormat::Rgb10a2Unorm],
+         Self::Rgba16 => &[TextureFormat::Rgba16Uint],
+         Self::YuvPlanes => &[TextureFormat::R16Uint, TextureFormat::Rg16Uint],
+      }
+   }
+
+   /// size of ``plane`` for a ``frame`` sized video, chroma is half size both ways
+   fn plane_size(self, plane: usize, frame: Extent3d) -> Extent3d {
+      match (self, plane) {
+         (Self::YuvPlanes, 1) => Extent3d {
+            width: frame.width.div_ceil(2),
+            height: frame.height.div_ceil(2),
+            depth_or_array_layers: 1,
+         },
+         _ => frame,
+      }
+   }
+
+   /// ``None`` for 8 bit, which needs no decoding
+   fn tone_map_source(self) -> Option<ToneMapSource> {
+      match self {
+         Self::Rgba8 => None,
+         Self::Rgb10a2 => Some(ToneMapSource::Rgb10a2),
+         Self::Rgba16 => Some(ToneMapSource::Rgba16),
+         Self::YuvPlanes => Some(ToneMapSource::YuvPlanes),
+      }
+   }
+}
+
+fn bytes_per_pixel(format: TextureFormat) -> u32 {
+   format.block_copy_size(None).expect("planes are uncompressed colour formats")
+}
+
+fn aligned_bytes_per_row(width: u32, bytes_per_pixel: u32) -> u32 {
    let bytes_per_row = width * bytes_per_pixel;
    let aligned_bytes_per_row = eframe::wgpu::util::align_to(bytes_per_row as u64, 256) as u32;
 
    aligned_bytes_per_row
 }
 
-pub struct Inner {
-   pub texture: Texture,
-   pub view: TextureView,
-   pub buffer: Buffer,
-   pub texture_id: TextureId,
+/// texture a pass can draw into and the next one read from
+fn pass_target(render_pack: &WgpuRenderPack, size: Extent3d, format: TextureFormat) -> (Texture, TextureView) {
+   let texture = render_pack.device.create_texture(&TextureDescriptor {
+      label: Some("Pass texture"),
+      size,
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+      view_formats: &[],
+   });
+   let view = view_as(&texture, format);
+   (texture, view)
+}
+
+fn view_as(texture: &Texture, format: TextureFormat) -> TextureView {
+   texture.create_view(&TextureViewDescriptor {
+      label: Some("Tex view"),
+      format: Some(format),
+      dimension: Some(TextureViewDimension::D2),
+      aspect: TextureAspect::All,
+      base_mip_level: 0,
+      mip_level_count: Some(1), // Ensure this is within the texture's mip level count
+      base_array_layer: 0,
+      array_layer_count: Some(1),
+   })
+}
+
+/// every gpu stage between the uploaded frame and the texture egui draws, made once the device is known
+struct Stages {
+   tone_map_passes: HashMap<ToneMapSource, ToneMapPass>,
+   tone_mapping: ToneMapping,
+   color_pass: ColorPass,
+   color_adjustments: ColorAdjustments,
+   shader_chain: ShaderChain,
+   scale_pass: ScalePass,
+   /// uv ``[x, y, width, height]`` of the frame the scaler draws, less than all of it when zoomed or cropped
+   source_rect: [f32; 4],
+   /// turns and mirroring the scaler applies, see ``Orientation::bits``
+   orientation: u32,
+}
+
+impl Stages {
+   fn new(device: &Device) -> Self {
+      Self {
+         tone_map_passes: ToneMapSource::ALL.iter().map(|source| (*source, ToneMapPass::new(device, *source))).collect(),
+         tone_mapping: ToneMapping::default(),
+         color_pass: ColorPass::new(device),
+         color_adjustments: ColorAdjustments::default(),
+         shader_chain: ShaderChain::new(),
+         scale_pass: ScalePass::new(device, Scaler::default()),
+         source_rect: [0.0, 0.0, 1.0, 1.0],
+         orientation: 0,
+      }
+   }
+}
+
+/// the texture egui draws, sized to the frames on screen pixel size rather than the video
+pub(crate) struct Output {
+   pub(crate) texture: Texture,
+   /// ``PASS_FORMAT`` view, drawn into and read by passes after this one
+   pub(crate) target: TextureView,
+   pub(crate) texture_id: TextureId,
 }
-impl Inner {
-   fn create(width: u32, height: u32, render_pack: &WgpuRenderPack) -> Result<Self> {
-      // tex
-      let size = Extent3d {
-         width,
-         height,
-         depth_or_array_layers: 1,
-      };
 
+impl Output {
+   pub(crate) fn create(render_pack: &WgpuRenderPack, size: Extent3d) -> Self {
       let texture = render_pack.device.create_texture(&TextureDescriptor {
-         label: Some("Render texture"),
+         label: Some("Post processed texture"),
          size,
          mip_level_count: 1,
          sample_count: 1,
          dimension: TextureDimension::D2,
          format: TextureFormat::Rgba8UnormSrgb,
-         usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-         view_formats: &[],
+         usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+         view_formats: &[PASS_FORMAT],
       });
 
-      let view = texture.create_view(&TextureViewDescriptor {
-         label: Some("Tex view"),
-         format: Some(texture.format()),
-         dimension: Some(TextureViewDimension::D2),
-         aspect: TextureAspect::All,
-         base_mip_level: 0,
-         mip_level_count: Some(1), // Ensure this is within the texture's mip level count
-         base_array_layer: 0,
-         array_layer_count: Some(1),
-      });
+      // egui reads it as srgb, the passes write the gamma encoded values through a unorm view
+      let view = view_as(&texture, texture.format());
+      let target = view_as(&texture, PASS_FORMAT);
 
-      // sampler
+      // drawn 1:1 with screen pixels so filtering here would only blur
       let sampler_desc = SamplerDescriptor {
          label: Some("Texture Sampler"),
          address_mode_u: AddressMode::ClampToEdge,
@@ -62,123 +172,382 @@ impl Inner {
          ..Default::default()
       };
 
-      // buffer
-      let aligned_bytes_per_row = aligned_bytes_per_row(width);
-      let buffer_size = (aligned_bytes_per_row * height) as u64;
-      // let buffer_size = ((width * height) * 4u32) as u64;
-
-      let buffer = render_pack.device.create_buffer(&BufferDescriptor {
-         label: Some("TextureBuffer"),
-         size: buffer_size,
-         usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
-         mapped_at_creation: false,
-      });
-
-      // tex_id
       let texture_id = render_pack.renderer.write().register_native_texture_with_sampler_options(
          &render_pack.device,
          &view,
          sampler_desc,
       );
 
+      Self {
+         texture,
+         target,
+         texture_id,
+      }
+   }
+
+   pub(crate) fn free(&self, render_pack: &WgpuRenderPack) {
+      render_pack.renderer.write().free_texture(&self.texture_id);
+   }
+}
 
-      Ok(Self {
+/// one uploaded plane of the frame and the buffer it's staged through
+struct Plane {
+   texture: Texture,
+   view: TextureView,
+   buffer: Buffer,
+}
+
+impl Plane {
+   fn create(render_pack: &WgpuRenderPack, size: Extent3d, format: TextureFormat) -> Self {
+      let texture = render_pack.device.create_texture(&TextureDescriptor {
+         label: Some("Render texture"),
+         size,
+         mip_level_count: 1,
+         sample_count: 1,
+         dimension: TextureDimension::D2,
+         format,
+         usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+         view_formats: if format.is_srgb() { &[PASS_FORMAT] } else { &[] },
+      });
+
+      // the passes work on the gamma encoded values
+      let view = view_as(&texture, if format.is_srgb() { PASS_FORMAT } else { format });
+
+      let buffer = render_pack.device.create_buffer(&BufferDescriptor {
+         label: Some("TextureBuffer"),
+         size: (aligned_bytes_per_row(size.width, bytes_per_pixel(format)) * size.height) as u64,
+         usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+         mapped_at_creation: false,
+      });
+
+      Self {
          texture,
          view,
          buffer,
-         texture_id,
-      })
+      }
    }
 
-   fn update(&self, data: Vec<u8>, render_state: &WgpuRenderPack) -> Result<()> {
-      let width = self.texture.width();
+   /// stages ``data`` with rows ``stride`` bytes apart and records the copy into the texture
+   fn write(&self, render_state: &WgpuRenderPack, encoder: &mut CommandEncoder, data: &[u8], stride: usize) {
       let height = self.texture.height();
-      let aligned_bytes_per_row = aligned_bytes_per_row(width);
+      let row_bytes = (self.texture.width() * bytes_per_pixel(self.texture.format())) as usize;
+      let aligned_bytes_per_row = aligned_bytes_per_row(self.texture.width(), bytes_per_pixel(self.texture.format()));
 
-      let aligned_data = if aligned_bytes_per_row != width * 4 {
+      // gstreamer's own padding sometimes already matches what wgpu wants
+      if stride == aligned_bytes_per_row as usize && data.len() >= stride * height as usize {
+         render_state.queue.write_buffer(&self.buffer, 0, &data[..stride * height as usize]);
+      } else {
          let mut prog = vec![0u8; (aligned_bytes_per_row * height) as usize];
-         for row in 0..height {
-            let src_start = (row * width * 4) as usize;
-            let src_end = src_start + (width * 4) as usize;
-            let dst_start = (row * aligned_bytes_per_row) as usize;
-            prog[dst_start..dst_start + (width * 4) as usize].copy_from_slice(&data[src_start..src_end]);
+         for row in 0..height as usize {
+            let src_start = row * stride;
+            let dst_start = row * aligned_bytes_per_row as usize;
+            prog[dst_start..dst_start + row_bytes].copy_from_slice(&data[src_start..src_start + row_bytes]);
          }
-         prog
-      } else {
-         data
-      };
+         render_state.queue.write_buffer(&self.buffer, 0, &prog);
+      }
 
-      // write to buffer then text
-      {
-         render_state.queue.write_buffer(&self.buffer, 0, &aligned_data);
-
-         let mut encoder = render_state.device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some("Tex encoder"),
-         });
-
-         encoder.copy_buffer_to_texture(
-            ImageCopyBuffer {
-               buffer: &self.buffer,
-               layout: ImageDataLayout {
-                  offset: 0,
-                  bytes_per_row: Some(aligned_bytes_per_row),
-                  rows_per_image: Some(self.texture.height()),
-               },
+      encoder.copy_buffer_to_texture(
+         ImageCopyBuffer {
+            buffer: &self.buffer,
+            layout: ImageDataLayout {
+               offset: 0,
+               bytes_per_row: Some(aligned_bytes_per_row),
+               rows_per_image: Some(height),
             },
-            ImageCopyTexture {
-               texture: &self.texture,
-               mip_level: 0,
-               origin: Origin3d::ZERO,
-               aspect: TextureAspect::All,
-            },
-            self.texture.size(),
-         );
+         },
+         ImageCopyTexture {
+            texture: &self.texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+         },
+         self.texture.size(),
+      );
+   }
+}
+
+/// the extra first pass for deep colour uploads, writing sdr into a texture the colour pass reads
+struct Decode {
+   source: ToneMapSource,
+   bind_group: BindGroup,
+   target: (Texture, TextureView),
+}
+
+pub struct Inner {
+   upload: UploadFormat,
+   /// the decoded frame as uploaded, luma first for planar formats
+   planes: Vec<Plane>,
+   /// of the latest frame, decides how ``decode`` linearises it
+   colorimetry: VideoColorimetry,
+   decode: Option<Decode>,
+   /// what egui draws, use ``WgpuEguiDisplayTexture::texture_id``
+   output: Output,
+   color_bind_group: BindGroup,
+   /// ping pong targets at video size for the colour pass and shader chain
+   intermediates: [(Texture, TextureView); 2],
+}
+impl Inner {
+   fn create(width: u32, height: u32, upload: UploadFormat, colorimetry: VideoColorimetry, output_size: Extent3d, render_pack: &WgpuRenderPack, stages: &Stages) -> Result<Self> {
+      let size = Extent3d {
+         width,
+         height,
+         depth_or_array_layers: 1,
+      };
+
+      let planes = upload
+          .planes()
+          .iter()
+          .enumerate()
+          .map(|(i, format)| Plane::create(render_pack, upload.plane_size(i, size), *format))
+          .collect::<Vec<_>>();
+
+      let output = Output::create(render_pack, output_size);
+      let decode = upload.tone_map_source().map(|source| {
+         let views = planes.iter().map(|plane| &plane.view).collect::<Vec<_>>();
+         Decode {
+            source,
+            bind_group: stages.tone_map_passes[&source].bind(&render_pack.device, &views),
+            target: pass_target(render_pack, size, DECODE_FORMAT),
+         }
+      });
+      let color_source = decode.as_ref().map_or(&planes[0].view, |decode| &decode.target.1);
+      let color_bind_group = stages.color_pass.bind(&render_pack.device, color_source);
+      let intermediates = [pass_target(render_pack, size, PASS_FORMAT), pass_target(render_pack, size, PASS_FORMAT)];
+
+      Ok(Self {
+         upload,
+         planes,
+         colorimetry,
+         decode,
+         output,
+         color_bind_group,
+         intermediates,
+      })
+   }
+
+   /// the video size in pixels
+   pub fn size(&self) -> Extent3d {
+      self.planes[0].texture.size()
+   }
+
+   /// tone mapping for deep colour frames, colour adjustments then each chain shader ping ponging between the intermediates,
+   /// then the scaler draws whichever was written last into the output
+   fn process(&self, render_state: &WgpuRenderPack, encoder: &mut CommandEncoder, stages: &Stages) {
+      if let Some(decode) = &self.decode {
+         let pass = &stages.tone_map_passes[&decode.source];
+         pass.run(render_state, encoder, &decode.bind_group, &decode.target.1, &self.colorimetry, &stages.tone_mapping);
+      }
+      stages.color_pass.run(render_state, encoder, &self.color_bind_group, &self.intermediates[0].1, &stages.color_adjustments);
+
+      let frame_size = [self.size().width as f32, self.size().height as f32];
+      let chain_uniforms = stages.shader_chain.uniforms(frame_size, frame_size);
+
+      let passes = stages.shader_chain.passes();
+      for (i, pass) in passes.iter().enumerate() {
+         let bind_group = pass.bind(&render_state.device, &self.intermediates[i % 2].1);
+         pass.run(render_state, encoder, &bind_group, &self.intermediates[(i + 1) % 2].1, bytemuck::bytes_of(&chain_uniforms));
+      }
+
+      let output_size = [self.output.texture.width() as f32, self.output.texture.height() as f32];
+      let scale_uniforms = stages.shader_chain.uniforms(frame_size, output_size).with_source_rect(stages.source_rect).with_orientation(stages.orientation);
+      let scale = stages.scale_pass.pass();
+      let bind_group = scale.bind(&render_state.device, &self.intermediates[passes.len() % 2].1);
+      scale.run(render_state, encoder, &bind_group, &self.output.target, bytemuck::bytes_of(&scale_uniforms));
+   }
+
+   /// reruns the post processing on the frame already uploaded, for when it changes while paused
+   fn reprocess(&self, render_state: &WgpuRenderPack, stages: &Stages) {
+      let mut encoder = render_state.device.create_command_encoder(&CommandEncoderDescriptor {
+         label: Some("Post process encoder"),
+      });
+      self.process(render_state, &mut encoder, stages);
+      render_state.queue.submit(Some(encoder.finish()));
+   }
+
+   fn resize_output(&mut self, render_pack: &WgpuRenderPack, size: Extent3d) {
+      self.output.free(render_pack);
+      self.output = Output::create(render_pack, size);
+   }
+
+   /// the same layout and size as ``frame``, otherwise it needs recreating
+   fn matches(&self, upload: UploadFormat, width: u32, height: u32) -> bool {
+      self.upload == upload && self.size().width == width && self.size().height == height
+   }
+
+   fn update(&self, frame: &VideoFrame<Readable>, render_state: &WgpuRenderPack, stages: &Stages) -> Result<()> {
+      let mut encoder = render_state.device.create_command_encoder(&CommandEncoderDescriptor {
+         label: Some("Tex encoder"),
+      });
 
-         render_state.queue.submit(Some(encoder.finish()));
+      // write each plane to its buffer then texture
+      for (i, plane) in self.planes.iter().enumerate() {
+         let stride = frame.plane_stride()[i] as usize;
+         plane.write(render_state, &mut encoder, frame.plane_data(i as u32)?, stride);
       }
 
+      self.process(render_state, &mut encoder, stages);
+
+      render_state.queue.submit(Some(encoder.finish()));
+
       Ok(())
    }
 }
 
 pub struct WgpuEguiDisplayTexture {
    pub inner: Option<Inner>,
+   stages: Option<Stages>,
+   /// on screen size in physical pixels, the video size until the player says otherwise
+   output_size: Option<Extent3d>,
 }
 
 impl WgpuEguiDisplayTexture {
    pub fn empty() -> Self {
       Self {
          inner: None,
+         stages: None,
+         output_size: None,
+      }
+   }
+
+   fn stages(&mut self, device: &Device) -> &mut Stages {
+      self.stages.get_or_insert_with(|| Stages::new(device))
+   }
+
+   fn reprocess(&self, render_pack: &WgpuRenderPack) {
+      if let (Some(inner), Some(stages)) = (&self.inner, &self.stages) {
+         inner.reprocess(render_pack, stages);
+      }
+   }
+
+   /// what to hand egui, ``None`` before the first frame
+   pub fn texture_id(&self) -> Option<TextureId> {
+      self.inner.as_ref().map(|inner| inner.output.texture_id)
+   }
+
+   /// the frame at video size as the colour pass reads it, gamma encoded and before colour adjustments,
+   /// the shader chain and the scaler, for passes comparing several players' sources
+   pub(crate) fn decoded(&self) -> Option<(&TextureView, Extent3d)> {
+      let inner = self.inner.as_ref()?;
+      let view = inner.decode.as_ref().map_or(&inner.planes[0].view, |decode| &decode.target.1);
+      Some((view, inner.size()))
+   }
+
+   /// applied on the gpu to every frame from now on, the current one is redrawn straight away
+   pub fn set_color_adjustments(&mut self, render_pack: &WgpuRenderPack, adjustments: &ColorAdjustments) {
+      let stages = self.stages(&render_pack.device);
+      if stages.color_adjustments == *adjustments {
+         return;
+      }
+      stages.color_adjustments = *adjustments;
+      self.reprocess(render_pack);
+   }
+
+   /// swaps in a new chain when ``entries`` changed and reloads shader files edited on disk,
+   /// the current frame is redrawn if either happened
+   pub fn set_shader_chain(&mut self, render_pack: &WgpuRenderPack, entries: &[ChainEntry]) {
+      let chain = &mut self.stages(&render_pack.device).shader_chain;
+      let changed = chain.set_entries(&render_pack.device, entries);
+      let reloaded = chain.reload_changed(&render_pack.device);
+      if changed || reloaded {
+         self.reprocess(render_pack);
+      }
+   }
+
+   /// how pq and hlg frames are brought down to sdr, redraws the current frame if it changed
+   pub fn set_tone_mapping(&mut self, render_pack: &WgpuRenderPack, mapping: &ToneMapping) {
+      let stages = self.stages(&render_pack.device);
+      if stages.tone_mapping == *mapping {
+         return;
+      }
+      stages.tone_mapping = *mapping;
+      self.reprocess(render_pack);
+   }
+
+   /// the current frame is pq or hlg and being tone mapped
+   pub fn is_hdr(&self) -> bool {
+      self.inner.as_ref().is_some_and(|inner| inner.decode.is_some() && HdrTransfer::of(&inner.colorimetry) != HdrTransfer::Sdr)
+   }
+
+   /// ``(shader, error)`` for chain shaders that failed to compile
+   pub fn shader_errors(&self) -> Vec<(String, String)> {
+      self.stages.as_ref().map_or_else(Vec::new, |stages| stages.shader_chain.errors())
+   }
+
+   pub fn set_scaler(&mut self, render_pack: &WgpuRenderPack, scaler: Scaler) {
+      let stages = self.stages(&render_pack.device);
+      if stages.scale_pass.scaler() == scaler {
+         return;
+      }
+      stages.scale_pass = ScalePass::new(&render_pack.device, scaler);
+      self.reprocess(render_pack);
+   }
+
+   /// the part of the frame shown as uv ``[x, y, width, height]``, the output then only holds that part,
+   /// ``source_rect`` is measured after ``orientation`` turns and mirrors the frame
+   pub fn set_source_rect(&mut self, render_pack: &WgpuRenderPack, source_rect: [f32; 4], orientation: u32) {
+      let stages = self.stages(&render_pack.device);
+      if stages.source_rect == source_rect && stages.orientation == orientation {
+         return;
+      }
+      stages.source_rect = source_rect;
+      stages.orientation = orientation;
+      self.reprocess(render_pack);
+   }
+
+   /// size in physical pixels the frame is drawn at, the output is remade and redrawn when it changes
+   pub fn set_output_size(&mut self, render_pack: &WgpuRenderPack, width: u32, height: u32) {
+      let size = Extent3d {
+         width: width.max(1),
+         height: height.max(1),
+         depth_or_array_layers: 1,
+      };
+      if self.output_size == Some(size) {
+         return;
       }
+      self.output_size = Some(size);
+
+      if let Some(inner) = &mut self.inner {
+         inner.resize_output(render_pack, size);
+      }
+      self.reprocess(render_pack);
    }
 
    /// updates or creates and update the current texture
    pub fn create_or_update(&mut self, render_pack: &WgpuRenderPack, frame: VideoFrame<Readable>) -> Result<()> {
-      let format = frame.format();
-      if !matches!(format, VideoFormat::Rgba) { panic!("Gstreamer player must use the format sRGBA"); };
+      let upload = UploadFormat::of(frame.format())?;
+      let colorimetry = frame.info().colorimetry();
 
       let (width, height) = (frame.width(), frame.height());
-      let data = frame.plane_data(0)?.to_owned();
 
+      let output_size = self.output_size.unwrap_or(Extent3d { width, height, depth_or_array_layers: 1 });
+      let stages = self.stages.get_or_insert_with(|| Stages::new(&render_pack.device));
+      let field = Field::of(frame.info().interlace_mode(), frame.flags(), stages.shader_chain.frame());
+      stages.shader_chain.next_frame(field);
 
       match &mut self.inner {
          // not created yet
          None => {
-            let new_inner = Inner::create(width, height, render_pack)?;
-            new_inner.update(data, render_pack)?;
+            let new_inner = Inner::create(width, height, upload, colorimetry, output_size, render_pack, stages)?;
+            new_inner.update(&frame, render_pack, stages)?;
             self.inner = Some(new_inner);
          }
          Some(inner) => {
-            match inner.texture.width() != width || inner.texture.height() != height {
-               // wrong size
+            match !inner.matches(upload, width, height) {
+               // wrong size, or the format was renegotiated mid stream
                true => {
-                  let new_inner = Inner::create(width, height, render_pack)?;
-                  new_inner.update(data, render_pack)?;
+                  // the old output is only freed once there's a replacement, so a failure leaves it drawable
+                  let new_inner = Inner::create(width, height, upload, colorimetry, output_size, render_pack, stages)?;
+                  if let Err(err) = new_inner.update(&frame, render_pack, stages) {
+                     new_inner.output.free(render_pack);
+                     return Err(err);
+                  }
+                  inner.output.free(render_pack);
                   self.inner = Some(new_inner);
                }
                // normal update
                false => {
-                  inner.update(data, render_pack)?;
+                  inner.colorimetry = colorimetry;
+                  inner.update(&frame, render_pack, stages)?;
                }
             }
          }
@@ -192,4 +561,3 @@ impl WgpuEguiDisplayTexture {
       self.inner = None;
    }
 }
-
@@ -0,0 +1,252 @@
+use std::borrow::Cow;
+use eframe::wgpu::{AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBindingType, BufferDescriptor, BufferUsages, Color, ColorTargetState, ColorWrites, CommandEncoder, Device, ErrorFilter, FilterMode, FragmentState, LoadOp, MultisampleState, Operations, PipelineLayoutDescriptor, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, TextureFormat, TextureSampleType, TextureView, TextureViewDimension, VertexState};
+use crate::gstreamer_internals::color_balance::ColorAdjustments;
+use crate::wgpu::pack::WgpuRenderPack;
+
+/// format the passes read and write, the textures are srgb but viewed as unorm
+/// so shaders see the same gamma encoded values gstreamer does
+pub const PASS_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+/// what a pass reads and writes, the default is one filterable texture drawn into ``PASS_FORMAT``
+#[derive(Debug, Clone, Copy)]
+pub struct PassConfig {
+   pub sample_type: TextureSampleType,
+   /// source textures, the ones after the first are at ``@binding(3)`` onwards
+   pub planes: u32,
+   pub target: TextureFormat,
+}
+
+impl Default for PassConfig {
+   fn default() -> Self {
+      Self {
+         sample_type: TextureSampleType::Float { filterable: true },
+         planes: 1,
+         target: PASS_FORMAT,
+      }
+   }
+}
+
+/// one full screen fragment shader pass,
+/// the shader gets the source at ``@binding(0)``, a sampler at ``@binding(1)`` and its uniforms at ``@binding(2)``,
+/// the uniforms are visible to the vertex shader too
+pub struct ShaderPass {
+   pipeline: RenderPipeline,
+   layout: BindGroupLayout,
+   sampler: Sampler,
+   uniforms: Buffer,
+}
+
+impl ShaderPass {
+   /// ``source`` needs ``vs_main`` and ``fs_main`` entry points
+   pub fn new(device: &Device, label: &str, source: &str, uniform_size: u64) -> Self {
+      Self::with_config(device, label, source, uniform_size, PassConfig::default())
+   }
+
+   /// ``new`` for shaders that might not fit the pass, like user files,
+   /// wgpu's validation errors come back here instead of panicking as uncaptured device errors
+   pub fn try_new(device: &Device, label: &str, source: &str, uniform_size: u64) -> Result<Self, String> {
+      device.push_error_scope(ErrorFilter::Validation);
+      let pass = Self::new(device, label, source, uniform_size);
+      match pollster::block_on(device.pop_error_scope()) {
+         Some(err) => Err(err.to_string()),
+         None => Ok(pass),
+      }
+   }
+
+   /// for sources that aren't a single filterable texture, an integer source has to be ``textureLoad``ed rather than sampled
+   pub fn with_config(device: &Device, label: &str, source: &str, uniform_size: u64, config: PassConfig) -> Self {
+      let shader = device.create_shader_module(ShaderModuleDescriptor {
+         label: Some(label),
+         source: ShaderSource::Wgsl(Cow::Borrowed(source)),
+      });
+
+      let plane_entry = |binding| BindGroupLayoutEntry {
+         binding,
+         visibility: ShaderStages::FRAGMENT,
+         ty: BindingType::Texture {
+            sample_type: config.sample_type,
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+         },
+         count: None,
+      };
+
+      let mut entries = vec![
+         plane_entry(0),
+         BindGroupLayoutEntry {
+            binding: 1,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            count: None,
+         },
+         BindGroupLayoutEntry {
+            binding: 2,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Buffer {
+               ty: BufferBindingType::Uniform,
+               has_dynamic_offset: false,
+               min_binding_size: None,
+            },
+            count: None,
+         },
+      ];
+      entries.extend((1..config.planes).map(|plane| plane_entry(plane + 2)));
+
+      let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+         label: Some(label),
+         entries: &entries,
+      });
+
+      let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+         label: Some(label),
+         bind_group_layouts: &[&layout],
+         push_constant_ranges: &[],
+      });
+
+      let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+         label: Some(label),
+         layout: Some(&pipeline_layout),
+         vertex: VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: &[],
+         },
+         fragment: Some(FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(ColorTargetState {
+               format: config.target,
+               blend: None,
+               write_mask: ColorWrites::ALL,
+            })],
+         }),
+         primitive: PrimitiveState::default(),
+         depth_stencil: None,
+         multisample: MultisampleState::default(),
+         multiview: None,
+         cache: None,
+      });
+
+      let sampler = device.create_sampler(&SamplerDescriptor {
+         label: Some(label),
+         address_mode_u: AddressMode::ClampToEdge,
+         address_mode_v: AddressMode::ClampToEdge,
+         address_mode_w: AddressMode::ClampToEdge,
+         mag_filter: FilterMode::Linear,
+         min_filter: FilterMode::Linear,
+         mipmap_filter: FilterMode::Nearest,
+         ..Default::default()
+      });
+
+      let uniforms = device.create_buffer(&BufferDescriptor {
+         label: Some(label),
+         size: uniform_size,
+         usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+         mapped_at_creation: false,
+      });
+
+      Self {
+         pipeline,
+         layout,
+         sampler,
+         uniforms,
+      }
+   }
+
+   /// bind group reading ``source``, only needs remaking when the source texture is
+   pub fn bind(&self, device: &Device, source: &TextureView) -> BindGroup {
+      self.bind_planes(device, &[source])
+   }
+
+   /// ``bind`` for passes with more than one plane, in the order the bindings go
+   pub fn bind_planes(&self, device: &Device, planes: &[&TextureView]) -> BindGroup {
+      let mut entries = vec![
+      BindGroupEntry { binding: 0, resource: BindingResource::TextureView(planes[0]) },
+      BindGroupEntry { binding: 1, resource: BindingResource::Sampler(&self.sampler) },
+      BindGroupEntry { binding: 2, resource: self.uniforms.as_entire_binding() },
+      ];
+      entries.extend(planes.iter().enumerate().skip(1).map(|(plane, view)| BindGroupEntry {
+         binding: plane as u32 + 2,
+         resource: BindingResource::TextureView(view),
+      }));
+
+      device.create_bind_group(&BindGroupDescriptor {
+         label: Some("Pass bind group"),
+         layout: &self.layout,
+         entries: &entries,
+      })
+   }
+
+   /// records the pass into ``encoder``, ``uniforms`` are written before the encoder is submitted
+   pub fn run(&self, render_pack: &WgpuRenderPack, encoder: &mut CommandEncoder, bind_group: &BindGroup, target: &TextureView, uniforms: &[u8]) {
+      render_pack.queue.write_buffer(&self.uniforms, 0, uniforms);
+
+      let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+         label: Some("Post process pass"),
+         color_attachments: &[Some(RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: Operations {
+               load: LoadOp::Clear(Color::BLACK),
+               store: StoreOp::Store,
+            },
+         })],
+         depth_stencil_attachment: None,
+         timestamp_writes: None,
+         occlusion_query_set: None,
+      });
+
+      pass.set_pipeline(&self.pipeline);
+      pass.set_bind_group(0, bind_group, &[]);
+      pass.draw(0..3, 0..1);
+   }
+}
+
+/// matches ``Adjustments`` in ``color_adjust.wgsl``, padded to 16 bytes
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ColorUniforms {
+   brightness: f32,
+   contrast: f32,
+   saturation: f32,
+   hue: f32,
+   gamma: f32,
+   _pad: [f32; 3],
+}
+
+impl From<&ColorAdjustments> for ColorUniforms {
+   fn from(adjustments: &ColorAdjustments) -> Self {
+      Self {
+         brightness: adjustments.brightness,
+         contrast: adjustments.contrast,
+         saturation: adjustments.saturation,
+         hue: adjustments.hue,
+         gamma: adjustments.gamma.max(0.01),
+         _pad: [0.0; 3],
+      }
+   }
+}
+
+/// colour adjustments as a shader pass, costs a draw per frame and nothing on the cpu
+pub struct ColorPass {
+   pass: ShaderPass,
+}
+
+impl ColorPass {
+   pub fn new(device: &Device) -> Self {
+      Self {
+         pass: ShaderPass::new(device, "Color adjust", include_str!("shaders/color_adjust.wgsl"), std::mem::size_of::<ColorUniforms>() as u64),
+      }
+   }
+
+   pub fn bind(&self, device: &Device, source: &TextureView) -> BindGroup {
+      self.pass.bind(device, source)
+   }
+
+   pub fn run(&self, render_pack: &WgpuRenderPack, encoder: &mut CommandEncoder, bind_group: &BindGroup, target: &TextureView, adjustments: &ColorAdjustments) {
+      let uniforms = ColorUniforms::from(adjustments);
+      self.pass.run(render_pack, encoder, bind_group, target, bytemuck::bytes_of(&uniforms));
+   }
+}
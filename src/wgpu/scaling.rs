@@ -0,0 +1,79 @@
+use eframe::wgpu::Device;
+use serde::{Deserialize, Serialize};
+use crate::wgpu::post_process::ShaderPass;
+use crate::wgpu::shader_chain::{ChainUniforms, CHAIN_HEADER};
+
+const CATMULL_ROM: &str = include_str!("shaders/catmull_rom.wgsl");
+
+/// how the processed frame is resized to its on screen pixel size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Scaler {
+   Nearest,
+   #[default]
+   Bilinear,
+   CatmullRom,
+   Lanczos3,
+   /// catmull-rom with extra sharpening, for upscaling low resolution video
+   Sharp,
+}
+
+impl Scaler {
+   pub const ALL: &'static [Scaler] = &[Self::Nearest, Self::Bilinear, Self::CatmullRom, Self::Lanczos3, Self::Sharp];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Nearest => "Nearest",
+         Self::Bilinear => "Bilinear",
+         Self::CatmullRom => "Catmull-Rom",
+         Self::Lanczos3 => "Lanczos-3",
+         Self::Sharp => "Sharp upscale",
+      }
+   }
+
+   fn source(self) -> String {
+      let body = match self {
+         Self::Nearest => include_str!("shaders/scale_nearest.wgsl"),
+         Self::Bilinear => include_str!("shaders/scale_bilinear.wgsl"),
+         Self::CatmullRom => include_str!("shaders/scale_bicubic.wgsl"),
+         Self::Lanczos3 => include_str!("shaders/scale_lanczos.wgsl"),
+         Self::Sharp => include_str!("shaders/scale_sharp.wgsl"),
+      };
+      format!("{CHAIN_HEADER}{CATMULL_ROM}{body}")
+   }
+}
+
+/// the last pass, draws into the texture egui shows at exactly its on screen size
+pub struct ScalePass {
+   scaler: Scaler,
+   pass: ShaderPass,
+}
+
+impl ScalePass {
+   pub fn new(device: &Device, scaler: Scaler) -> Self {
+      Self {
+         scaler,
+         pass: ShaderPass::new(device, scaler.label(), &scaler.source(), std::mem::size_of::<ChainUniforms>() as u64),
+      }
+   }
+
+   pub fn scaler(&self) -> Scaler {
+      self.scaler
+   }
+
+   pub fn pass(&self) -> &ShaderPass {
+      &self.pass
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::wgpu::shader_chain::validate;
+
+   #[test]
+   fn scalers_validate() {
+      for scaler in Scaler::ALL {
+         assert!(validate(&scaler.source(), 1).is_ok(), "{} {:?}", scaler.label(), validate(&scaler.source(), 1));
+      }
+   }
+}
@@ -0,0 +1,360 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use anyhow::{Context, Result};
+use eframe::wgpu::naga;
+use eframe::wgpu::Device;
+use gstreamer_video::{VideoFrameFlags, VideoInterlaceMode};
+use serde::{Deserialize, Serialize};
+use crate::wgpu::post_process::ShaderPass;
+
+/// bindings, uniforms and vertex shader every chain shader is appended to
+pub const CHAIN_HEADER: &str = include_str!("shaders/chain_header.wgsl");
+
+/// how often user shader files are checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuiltinShader {
+   Sharpen,
+   FilmGrain,
+   Deband,
+   Crt,
+   /// put at the front of the chain by the player when deinterlacing on the gpu, not offered in the chain ui
+   Bob,
+}
+
+impl BuiltinShader {
+   /// the ones a user can add to their chain
+   pub const ALL: &'static [BuiltinShader] = &[Self::Sharpen, Self::FilmGrain, Self::Deband, Self::Crt];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Sharpen => "Sharpen",
+         Self::FilmGrain => "Film grain",
+         Self::Deband => "Deband",
+         Self::Crt => "CRT",
+         Self::Bob => "Deinterlace (bob)",
+      }
+   }
+
+   fn source(self) -> &'static str {
+      match self {
+         Self::Sharpen => include_str!("shaders/sharpen.wgsl"),
+         Self::FilmGrain => include_str!("shaders/film_grain.wgsl"),
+         Self::Deband => include_str!("shaders/deband.wgsl"),
+         Self::Crt => include_str!("shaders/crt.wgsl"),
+         Self::Bob => include_str!("shaders/bob.wgsl"),
+      }
+   }
+}
+
+/// which field of the current frame the bob keeps, ``field`` in the chain uniforms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+   Top = 0,
+   Bottom = 1,
+   /// both fields are from the same moment, shown as they are
+   Weave = 2,
+}
+
+impl Field {
+   /// a single field buffer is that field, a progressive frame in a mixed stream is woven,
+   /// otherwise it alternates every frame starting from the one the buffer says comes first
+   pub fn of(interlace_mode: VideoInterlaceMode, flags: VideoFrameFlags, frame: u32) -> Self {
+      let top_first = flags.contains(VideoFrameFlags::TFF);
+      if interlace_mode == VideoInterlaceMode::Mixed && !flags.contains(VideoFrameFlags::INTERLACED) {
+         return Self::Weave;
+      }
+      if flags.contains(VideoFrameFlags::ONEFIELD) {
+         return if top_first { Self::Top } else { Self::Bottom };
+      }
+      match top_first == (frame % 2 == 0) {
+         true => Self::Top,
+         false => Self::Bottom,
+      }
+   }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChainShader {
+   Builtin(BuiltinShader),
+   /// a wgsl file defining ``fs_main``, reloaded whenever it changes on disk
+   File(PathBuf),
+}
+
+impl ChainShader {
+   pub fn label(&self) -> String {
+      match self {
+         Self::Builtin(builtin) => builtin.label().to_string(),
+         Self::File(path) => path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string()),
+      }
+   }
+
+   fn source(&self) -> Result<String> {
+      let body = match self {
+         Self::Builtin(builtin) => builtin.source().to_string(),
+         Self::File(path) => std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?,
+      };
+      Ok(format!("{CHAIN_HEADER}{body}"))
+   }
+
+   fn modified(&self) -> Option<SystemTime> {
+      match self {
+         Self::Builtin(_) => None,
+         Self::File(path) => std::fs::metadata(path).and_then(|meta| meta.modified()).ok(),
+      }
+   }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainEntry {
+   pub shader: ChainShader,
+   pub enabled: bool,
+}
+
+/// where user shaders are looked for, ``vid_v2/shaders`` in the config dir
+pub fn user_shader_dir() -> Result<PathBuf> {
+   let dir = dirs::config_dir().context("No config directory")?.join("vid_v2").join("shaders");
+   std::fs::create_dir_all(&dir)?;
+   Ok(dir)
+}
+
+/// every ``.wgsl`` file in ``user_shader_dir``
+pub fn list_user_shaders() -> Vec<PathBuf> {
+   let Ok(dir) = user_shader_dir() else {
+      return vec![];
+   };
+
+   let pattern = dir.join("*.wgsl");
+   let mut paths = glob::glob(&pattern.to_string_lossy())
+       .map(|paths| paths.flatten().collect::<Vec<_>>())
+       .unwrap_or_default();
+   paths.sort();
+   paths
+}
+
+/// parses and validates with naga first, wgpu treats a bad shader as a fatal device error,
+/// then checks it fits a ``ShaderPass`` reading ``planes`` textures: ``vs_main``, ``fs_main`` writing one ``vec4<f32>``
+/// and nothing bound but the source, sampler, uniforms and extra planes
+pub(crate) fn validate(source: &str, planes: u32) -> Result<(), String> {
+   let module = naga::front::wgsl::parse_str(source).map_err(|err| err.emit_to_string(source))?;
+   naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+       .validate(&module)
+       .map_err(|err| err.emit_to_string(source))?;
+
+   let entry_point = |name: &str, stage| module.entry_points.iter().find(|entry| entry.name == name && entry.stage == stage);
+   entry_point("vs_main", naga::ShaderStage::Vertex).ok_or("No @vertex fn vs_main")?;
+   let fs_main = entry_point("fs_main", naga::ShaderStage::Fragment).ok_or("No @fragment fn fs_main")?;
+
+   let writes_vec4 = fs_main.function.result.as_ref().is_some_and(|result| {
+      matches!(result.binding, Some(naga::Binding::Location { location: 0, .. }))
+          && matches!(module.types[result.ty].inner, naga::TypeInner::Vector { size: naga::VectorSize::Quad, scalar: naga::Scalar::F32 })
+   });
+   if !writes_vec4 {
+      return Err("fs_main has to return @location(0) vec4<f32>".to_string());
+   }
+
+   for (_, global) in module.global_variables.iter() {
+      let Some(binding) = &global.binding else { continue };
+      let inner = &module.types[global.ty].inner;
+      let fits = match (binding.group, binding.binding) {
+         (0, 0) => matches!(inner, naga::TypeInner::Image { .. }),
+         (0, 1) => matches!(inner, naga::TypeInner::Sampler { .. }),
+         (0, 2) => global.space == naga::AddressSpace::Uniform,
+         (0, plane) => plane < planes + 2 && matches!(inner, naga::TypeInner::Image { .. }),
+         _ => false,
+      };
+      if !fits {
+         return Err(format!("@group({}) @binding({}) isn't something the pass binds", binding.group, binding.binding));
+      }
+   }
+   Ok(())
+}
+
+/// matches ``Uniforms`` in ``chain_header.wgsl``
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChainUniforms {
+   time: f32,
+   frame: u32,
+   frame_size: [f32; 2],
+   output_size: [f32; 2],
+   orientation: u32,
+   field: u32,
+   source_rect: [f32; 4],
+}
+
+impl ChainUniforms {
+   /// draws only ``[x, y, width, height]`` of the source in uv, for the scaler when the view is zoomed or cropped
+   pub fn with_source_rect(self, source_rect: [f32; 4]) -> Self {
+      Self {
+         source_rect,
+         ..self
+      }
+   }
+
+   /// turns and mirrors the source, see ``Orientation::bits``
+   pub fn with_orientation(self, orientation: u32) -> Self {
+      Self {
+         orientation,
+         ..self
+      }
+   }
+}
+
+struct LoadedShader {
+   shader: ChainShader,
+   pass: Option<ShaderPass>,
+   error: Option<String>,
+   modified: Option<SystemTime>,
+}
+
+impl LoadedShader {
+   fn load(device: &Device, shader: &ChainShader) -> Self {
+      let modified = shader.modified();
+      let compiled = shader.source()
+          .map_err(|err| err.to_string())
+          .and_then(|source| validate(&source, 1).map(|_| source))
+          .and_then(|source| ShaderPass::try_new(device, &shader.label(), &source, std::mem::size_of::<ChainUniforms>() as u64));
+
+      let (pass, error) = match compiled {
+         Ok(pass) => (Some(pass), None),
+         Err(err) => {
+            println!("Shader {} failed {err}", shader.label());
+            (None, Some(err))
+         }
+      };
+
+      Self {
+         shader: shader.clone(),
+         pass,
+         error,
+         modified,
+      }
+   }
+}
+
+/// the enabled user and builtin shaders run in order after the colour adjustments,
+/// a shader that fails to compile is skipped and its error kept for the ui
+pub struct ShaderChain {
+   entries: Vec<ChainEntry>,
+   loaded: Vec<LoadedShader>,
+   started: Instant,
+   frame: u32,
+   /// see ``Field``
+   field: u32,
+   last_reload_check: Instant,
+}
+
+impl ShaderChain {
+   pub fn new() -> Self {
+      Self {
+         entries: vec![],
+         loaded: vec![],
+         started: Instant::now(),
+         frame: 0,
+         field: Field::Weave as u32,
+         last_reload_check: Instant::now(),
+      }
+   }
+
+   /// rebuilds the passes if ``entries`` differ from the last ones, true if anything changed
+   pub fn set_entries(&mut self, device: &Device, entries: &[ChainEntry]) -> bool {
+      if self.entries == entries {
+         return false;
+      }
+
+      self.entries = entries.to_vec();
+      self.loaded = entries
+          .iter()
+          .filter(|entry| entry.enabled)
+          .map(|entry| LoadedShader::load(device, &entry.shader))
+          .collect();
+      true
+   }
+
+   /// recompiles shader files that changed on disk, true if any did
+   pub fn reload_changed(&mut self, device: &Device) -> bool {
+      if self.last_reload_check.elapsed() < RELOAD_INTERVAL {
+         return false;
+      }
+      self.last_reload_check = Instant::now();
+
+      let mut reloaded = false;
+      for loaded in &mut self.loaded {
+         if loaded.shader.modified() != loaded.modified {
+            println!("Reloading shader {}", loaded.shader.label());
+            *loaded = LoadedShader::load(device, &loaded.shader);
+            reloaded = true;
+         }
+      }
+      reloaded
+   }
+
+   /// compiled passes in order
+   pub fn passes(&self) -> Vec<&ShaderPass> {
+      self.loaded.iter().filter_map(|loaded| loaded.pass.as_ref()).collect()
+   }
+
+   /// ``(shader, error)`` for every shader that didn't compile
+   pub fn errors(&self) -> Vec<(String, String)> {
+      self.loaded
+          .iter()
+          .filter_map(|loaded| Some((loaded.shader.label(), loaded.error.clone()?)))
+          .collect()
+   }
+
+   /// frames shown since the chain was built
+   pub fn frame(&self) -> u32 {
+      self.frame
+   }
+
+   pub fn next_frame(&mut self, field: Field) {
+      self.frame = self.frame.wrapping_add(1);
+      self.field = field as u32;
+   }
+
+   pub fn uniforms(&self, frame_size: [f32; 2], output_size: [f32; 2]) -> ChainUniforms {
+      ChainUniforms {
+         time: self.started.elapsed().as_secs_f32(),
+         frame: self.frame,
+         frame_size,
+         output_size,
+         orientation: 0,
+         field: self.field,
+         source_rect: [0.0, 0.0, 1.0, 1.0],
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn builtins_validate() {
+      for builtin in BuiltinShader::ALL.iter().chain([&BuiltinShader::Bob]) {
+         let source = ChainShader::Builtin(*builtin).source().unwrap();
+         assert!(validate(&source, 1).is_ok(), "{} {:?}", builtin.label(), validate(&source, 1));
+      }
+   }
+
+   #[test]
+   fn bob_alternates_fields() {
+      let interleaved = VideoInterlaceMode::Interleaved;
+      assert_eq!(Field::of(interleaved, VideoFrameFlags::TFF, 0), Field::Top);
+      assert_eq!(Field::of(interleaved, VideoFrameFlags::TFF, 1), Field::Bottom);
+      assert_eq!(Field::of(interleaved, VideoFrameFlags::empty(), 0), Field::Bottom);
+      assert_eq!(Field::of(VideoInterlaceMode::Mixed, VideoFrameFlags::TFF, 0), Field::Weave);
+      assert_eq!(Field::of(VideoInterlaceMode::Mixed, VideoFrameFlags::INTERLACED, 0), Field::Bottom);
+   }
+
+   #[test]
+   fn broken_shader_is_reported() {
+      let source = format!("{CHAIN_HEADER}@fragment fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {{ return nope; }}");
+      assert!(validate(&source, 1).is_err());
+
+      // valid wgsl, but not something a pass can run
+      assert!(validate(CHAIN_HEADER, 1).is_err());
+   }
+}
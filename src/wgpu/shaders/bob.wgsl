@@ -0,0 +1,19 @@
+// deinterlaces by keeping one field and rebuilding the other field's lines from the lines either side,
+// halves the vertical detail but leaves no combing, runs first in the chain while the source is still frame sized,
+// ``uniforms.field`` picks the field so motion keeps its order, frames that aren't interlaced are woven untouched
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   if uniforms.field == 2u {
+      return tap(in.uv);
+   }
+
+   let size = vec2<f32>(textureDimensions(source));
+   let row = u32(in.uv.y * size.y);
+   if row % 2u == uniforms.field {
+      return tap(in.uv);
+   }
+
+   let line = vec2<f32>(0.0, 1.0 / size.y);
+   return (tap(in.uv - line) + tap(in.uv + line)) * 0.5;
+}
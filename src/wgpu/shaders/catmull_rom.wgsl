@@ -0,0 +1,32 @@
+// catmull-rom bicubic in nine bilinear taps instead of sixteen point ones
+
+fn catmull_rom(uv: vec2<f32>) -> vec4<f32> {
+   let size = vec2<f32>(textureDimensions(source));
+   let position = uv * size;
+   let centre = floor(position - 0.5) + 0.5;
+   let f = position - centre;
+
+   let w0 = f * (-0.5 + f * (1.0 - 0.5 * f));
+   let w1 = 1.0 + f * f * (-2.5 + 1.5 * f);
+   let w2 = f * (0.5 + f * (2.0 - 1.5 * f));
+   let w3 = f * f * (-0.5 + 0.5 * f);
+
+   // the middle two taps merged into one bilinear tap
+   let w12 = w1 + w2;
+   let p0 = (centre - 1.0) / size;
+   let p12 = (centre + w2 / w12) / size;
+   let p3 = (centre + 2.0) / size;
+
+   var result = tap(vec2<f32>(p0.x, p0.y)) * w0.x * w0.y
+      + tap(vec2<f32>(p12.x, p0.y)) * w12.x * w0.y
+      + tap(vec2<f32>(p3.x, p0.y)) * w3.x * w0.y
+      + tap(vec2<f32>(p0.x, p12.y)) * w0.x * w12.y
+      + tap(vec2<f32>(p12.x, p12.y)) * w12.x * w12.y
+      + tap(vec2<f32>(p3.x, p12.y)) * w3.x * w12.y
+      + tap(vec2<f32>(p0.x, p3.y)) * w0.x * w3.y
+      + tap(vec2<f32>(p12.x, p3.y)) * w12.x * w3.y
+      + tap(vec2<f32>(p3.x, p3.y)) * w3.x * w3.y;
+
+   return clamp(result, vec4<f32>(0.0), vec4<f32>(1.0));
+}
+
@@ -0,0 +1,74 @@
+// put in front of every shader in the post process chain, so a chain shader only has to define fs_main,
+// ``tap`` and ``hash`` are there for convenience
+
+struct Uniforms {
+   // seconds since the chain was built
+   time: f32,
+   // frames shown since the chain was built
+   frame: u32,
+   // decoded video size in pixels
+   frame_size: vec2<f32>,
+   // size of the texture being written in pixels
+   output_size: vec2<f32>,
+   // clockwise quarter turns in the low two bits, mirrored after turning if bit 2 is set,
+   // only ever set for the scaler
+   orientation: u32,
+   // field a deinterlacer keeps, 0 top, 1 bottom, 2 for frames whose fields belong together and are woven,
+   // from the frame's buffer flags and alternating frame to frame
+   field: u32,
+   // part of the source drawn as uv offset and size, the whole frame except for the scaler when zoomed or cropped,
+   // measured after ``orientation`` is applied
+   source_rect: vec4<f32>,
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> uniforms: Uniforms;
+
+struct VertexOut {
+   @builtin(position) position: vec4<f32>,
+   @location(0) uv: vec2<f32>,
+}
+
+// one triangle that covers the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+   let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+   var out: VertexOut;
+   out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+   out.uv = unorient(uniforms.source_rect.xy + uv * uniforms.source_rect.zw);
+   return out;
+}
+
+// uv on screen to uv in the source, undoing ``uniforms.orientation``
+fn unorient(uv: vec2<f32>) -> vec2<f32> {
+   var p = uv;
+   if (uniforms.orientation & 4u) != 0u {
+      p.x = 1.0 - p.x;
+   }
+   switch uniforms.orientation & 3u {
+      case 1u: {
+         return vec2<f32>(p.y, 1.0 - p.x);
+      }
+      case 2u: {
+         return 1.0 - p;
+      }
+      case 3u: {
+         return vec2<f32>(1.0 - p.y, p.x);
+      }
+      default: {
+         return p;
+      }
+   }
+}
+
+// the previous pass at ``uv``, safe to call from non uniform control flow
+fn tap(uv: vec2<f32>) -> vec4<f32> {
+   return textureSampleLevel(source, source_sampler, uv, 0.0);
+}
+
+// cheap per pixel noise in 0..1
+fn hash(p: vec2<f32>) -> f32 {
+   return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
@@ -0,0 +1,57 @@
+// brightness, contrast, saturation, hue and gamma in the same order as videobalance then gamma,
+// done on the gamma encoded values so it matches the gstreamer fallback
+
+struct Adjustments {
+   brightness: f32,
+   contrast: f32,
+   saturation: f32,
+   hue: f32,
+   gamma: f32,
+   _pad0: f32,
+   _pad1: f32,
+   _pad2: f32,
+}
+
+@group(0) @binding(0) var frame: texture_2d<f32>;
+@group(0) @binding(1) var frame_sampler: sampler;
+@group(0) @binding(2) var<uniform> adjust: Adjustments;
+
+struct VertexOut {
+   @builtin(position) position: vec4<f32>,
+   @location(0) uv: vec2<f32>,
+}
+
+// one triangle that covers the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+   let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+   var out: VertexOut;
+   out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+   out.uv = uv;
+   return out;
+}
+
+const PI: f32 = 3.14159265;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let source = textureSample(frame, frame_sampler, in.uv);
+
+   var rgb = (source.rgb - 0.5) * adjust.contrast + 0.5 + adjust.brightness;
+
+   // hue and saturation act on the chroma of bt709 ycbcr
+   let y = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+   let chroma = vec2<f32>((rgb.b - y) / 1.8556, (rgb.r - y) / 1.5748);
+   let angle = adjust.hue * PI;
+   let rotated = vec2<f32>(
+      chroma.x * cos(angle) - chroma.y * sin(angle),
+      chroma.x * sin(angle) + chroma.y * cos(angle),
+   ) * adjust.saturation;
+
+   let r = y + 1.5748 * rotated.y;
+   let b = y + 1.8556 * rotated.x;
+   let g = (y - 0.2126 * r - 0.0722 * b) / 0.7152;
+
+   rgb = pow(clamp(vec3<f32>(r, g, b), vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / adjust.gamma));
+   return vec4<f32>(rgb, source.a);
+}
@@ -0,0 +1,28 @@
+// curved screen, scanlines, an rgb aperture mask and a vignette
+
+const SCANLINE_DEPTH: f32 = 0.3;
+const LINE_PIXELS: f32 = 3.0;
+const PI: f32 = 3.14159265;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let centred = in.uv * 2.0 - 1.0;
+   let curved = centred * (1.0 + dot(centred, centred) * vec2<f32>(0.03, 0.04));
+   let uv = curved * 0.5 + 0.5;
+
+   if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+      return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+   }
+
+   var colour = tap(uv).rgb;
+
+   let line = sin(uv.y * uniforms.output_size.y / LINE_PIXELS * PI);
+   colour *= 1.0 - SCANLINE_DEPTH * (1.0 - line * line);
+
+   var mask = vec3<f32>(0.85);
+   mask[u32(in.position.x) % 3u] = 1.0;
+   colour *= mask * 1.15;
+
+   let vignette = 1.0 - dot(centred, centred) * 0.15;
+   return vec4<f32>(clamp(colour * vignette, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
@@ -0,0 +1,28 @@
+// swaps a pixel for the average of four random taps around it when they're all close to it,
+// only nearly flat areas are touched so edges and detail survive, then dithers the result
+
+const RADIUS: f32 = 16.0;
+const THRESHOLD: f32 = 0.012;
+const DITHER: f32 = 1.0 / 255.0;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let texel = 1.0 / vec2<f32>(textureDimensions(source));
+   let pixel = floor(in.uv * uniforms.output_size);
+
+   let angle = hash(pixel) * 6.2831853;
+   let distance = (hash(pixel + 17.0) * 0.5 + 0.5) * RADIUS;
+   let offset = vec2<f32>(cos(angle), sin(angle)) * distance * texel;
+
+   let centre = tap(in.uv);
+   let average = (tap(in.uv + offset)
+      + tap(in.uv - offset)
+      + tap(in.uv + vec2<f32>(-offset.y, offset.x))
+      + tap(in.uv + vec2<f32>(offset.y, -offset.x))) * 0.25;
+
+   let flat_area = all(abs(average.rgb - centre.rgb) < vec3<f32>(THRESHOLD));
+   let smoothed = select(centre.rgb, average.rgb, flat_area);
+   let dither = (hash(pixel + 31.0) - 0.5) * DITHER;
+
+   return vec4<f32>(smoothed + dither, centre.a);
+}
@@ -0,0 +1,35 @@
+// absolute difference of two finished frames, scaled up so small encoding errors are visible
+
+struct Params {
+   gain: f32,
+   _pad0: f32,
+   _pad1: f32,
+   _pad2: f32,
+}
+
+@group(0) @binding(0) var first: texture_2d<f32>;
+@group(0) @binding(1) var frame_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: Params;
+@group(0) @binding(3) var second: texture_2d<f32>;
+
+struct VertexOut {
+   @builtin(position) position: vec4<f32>,
+   @location(0) uv: vec2<f32>,
+}
+
+// one triangle that covers the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+   let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+   var out: VertexOut;
+   out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+   out.uv = uv;
+   return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let a = textureSample(first, frame_sampler, in.uv).rgb;
+   let b = textureSample(second, frame_sampler, in.uv).rgb;
+   return vec4<f32>(min(abs(a - b) * params.gain, vec3<f32>(1.0)), 1.0);
+}
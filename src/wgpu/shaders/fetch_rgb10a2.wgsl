@@ -0,0 +1,8 @@
+// RGB10A2_LE, already normalised by the texture format
+
+@group(0) @binding(0) var frame: texture_2d<f32>;
+
+fn fetch(uv: vec2<f32>) -> vec4<f32> {
+   return textureLoad(frame, texel(uv, textureDimensions(frame)), 0);
+}
+
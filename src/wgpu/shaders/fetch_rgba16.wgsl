@@ -0,0 +1,8 @@
+// RGBA64_LE as a 16 bit integer texture
+
+@group(0) @binding(0) var frame: texture_2d<u32>;
+
+fn fetch(uv: vec2<f32>) -> vec4<f32> {
+   return vec4<f32>(textureLoad(frame, texel(uv, textureDimensions(frame)), 0)) / 65535.0;
+}
+
@@ -0,0 +1,25 @@
+// P010 and P016, a 16 bit luma plane and a half size plane of interleaved chroma,
+// P010 keeps its 10 bits in the high bits so both normalise the same way,
+// integer textures can't be sampled so chroma is filtered bilinearly here,
+// sited as h.264 and hevc put it, level with every other luma column and between two rows
+
+@group(0) @binding(0) var luma: texture_2d<u32>;
+@group(0) @binding(3) var chroma: texture_2d<u32>;
+
+fn chroma_at(p: vec2<i32>) -> vec2<f32> {
+   let dims = vec2<i32>(textureDimensions(chroma));
+   return vec2<f32>(textureLoad(chroma, clamp(p, vec2<i32>(0), dims - 1), 0).rg);
+}
+
+fn fetch(uv: vec2<f32>) -> vec4<f32> {
+   let y = f32(textureLoad(luma, texel(uv, textureDimensions(luma)), 0).r);
+
+   // the luma pixel's centre measured in chroma samples
+   let at = (uv * vec2<f32>(textureDimensions(luma)) - vec2<f32>(0.5, 1.0)) / 2.0;
+   let base = vec2<i32>(floor(at));
+   let f = fract(at);
+   let top = mix(chroma_at(base), chroma_at(base + vec2<i32>(1, 0)), f.x);
+   let bottom = mix(chroma_at(base + vec2<i32>(0, 1)), chroma_at(base + vec2<i32>(1, 1)), f.x);
+   let c = mix(top, bottom, f.y);
+   return vec4<f32>(yuv_to_rgb(vec3<f32>(y, c) / 65535.0), 1.0);
+}
@@ -0,0 +1,15 @@
+// animated luma grain, strongest in the midtones like real film
+
+const AMOUNT: f32 = 0.06;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let colour = tap(in.uv);
+   let pixel = floor(in.uv * uniforms.output_size);
+   let noise = hash(pixel + f32(uniforms.frame % 1024u) * 7.31) - 0.5;
+
+   let luma = dot(colour.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+   let weight = 1.0 - abs(luma * 2.0 - 1.0);
+
+   return vec4<f32>(clamp(colour.rgb + noise * AMOUNT * weight, vec3<f32>(0.0), vec3<f32>(1.0)), colour.a);
+}
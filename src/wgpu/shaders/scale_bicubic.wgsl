@@ -0,0 +1,4 @@
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   return catmull_rom(in.uv);
+}
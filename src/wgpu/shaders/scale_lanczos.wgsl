@@ -0,0 +1,46 @@
+// lanczos-3, the kernel widens when shrinking so downscaled video is filtered instead of aliased
+
+const RADIUS: f32 = 3.0;
+const PI: f32 = 3.14159265;
+// caps the taps at 24 a side for extreme downscales
+const MAX_SCALE: f32 = 4.0;
+
+fn lanczos(x: f32) -> f32 {
+   if abs(x) < 1e-5 {
+      return 1.0;
+   }
+   if abs(x) >= RADIUS {
+      return 0.0;
+   }
+   let px = PI * x;
+   return RADIUS * sin(px) * sin(px / RADIUS) / (px * px);
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let size = vec2<f32>(textureDimensions(source));
+   // source pixels per output pixel, along the source's axes which are swapped when turned sideways
+   var ratio = uniforms.source_rect.zw / uniforms.output_size;
+   if (uniforms.orientation & 1u) == 1u {
+      ratio = ratio.yx;
+   }
+   let scale = clamp(size * ratio, vec2<f32>(1.0), vec2<f32>(MAX_SCALE));
+   let centre = in.uv * size - 0.5;
+   let base = floor(centre);
+   let reach = ceil(RADIUS * scale);
+
+   var sum = vec4<f32>(0.0);
+   var total = 0.0;
+   for (var y = 1.0 - reach.y; y <= reach.y; y += 1.0) {
+      for (var x = 1.0 - reach.x; x <= reach.x; x += 1.0) {
+         let texel = base + vec2<f32>(x, y);
+         let distance = (texel - centre) / scale;
+         let weight = lanczos(distance.x) * lanczos(distance.y);
+         let coord = clamp(vec2<i32>(texel), vec2<i32>(0), vec2<i32>(size) - 1);
+         sum += textureLoad(source, coord, 0) * weight;
+         total += weight;
+      }
+   }
+
+   return clamp(sum / total, vec4<f32>(0.0), vec4<f32>(1.0));
+}
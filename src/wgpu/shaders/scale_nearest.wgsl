@@ -0,0 +1,8 @@
+// whole source pixels, for pixel art or checking what the decoder produced
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let size = vec2<i32>(textureDimensions(source));
+   let texel = clamp(vec2<i32>(in.uv * vec2<f32>(size)), vec2<i32>(0), size - 1);
+   return textureLoad(source, texel, 0);
+}
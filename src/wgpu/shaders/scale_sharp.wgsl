@@ -0,0 +1,10 @@
+// catmull-rom pushed further away from bilinear, the difference between them is the detail bicubic adds
+
+const AMOUNT: f32 = 0.8;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let cubic = catmull_rom(in.uv);
+   let linear = tap(in.uv);
+   return clamp(cubic + (cubic - linear) * AMOUNT, vec4<f32>(0.0), vec4<f32>(1.0));
+}
@@ -0,0 +1,16 @@
+// unsharp mask over the four direct neighbours
+
+const STRENGTH: f32 = 0.6;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let texel = 1.0 / vec2<f32>(textureDimensions(source));
+   let centre = tap(in.uv);
+   let around = tap(in.uv + vec2<f32>(texel.x, 0.0))
+      + tap(in.uv - vec2<f32>(texel.x, 0.0))
+      + tap(in.uv + vec2<f32>(0.0, texel.y))
+      + tap(in.uv - vec2<f32>(0.0, texel.y));
+
+   let sharpened = centre.rgb + (centre.rgb * 4.0 - around.rgb) * STRENGTH;
+   return vec4<f32>(clamp(sharpened, vec3<f32>(0.0), vec3<f32>(1.0)), centre.a);
+}
@@ -0,0 +1,208 @@
+// decodes a deep colour frame, pq and hlg are linearised to nits, mapped from bt.2020 into bt.709
+// and tone mapped so sdr white ends at 1.0, everything is sRGB encoded again on the way out
+// so the passes after this see the same kind of values as an 8 bit upload,
+// one of the ``fetch_*.wgsl`` files goes in front to read the planes
+
+struct Params {
+   // 0 sdr, 1 pq, 2 hlg
+   transfer: u32,
+   // 0 reinhard, 1 hable, 2 bt.2390
+   mapper: u32,
+   bt2020: u32,
+   // yuv matrix, 0 for rgb, 1 bt.709, 2 bt.601, 3 bt.2020
+   matrix: u32,
+   // brightest the content gets in cd/m²
+   peak_nits: f32,
+   // cd/m² that ends up as 1.0
+   sdr_white: f32,
+   // yuv in 16-235 rather than 0-255
+   limited_range: u32,
+   _pad: f32,
+}
+
+@group(0) @binding(1) var frame_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: Params;
+
+struct VertexOut {
+   @builtin(position) position: vec4<f32>,
+   @location(0) uv: vec2<f32>,
+}
+
+// one triangle that covers the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+   let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+   var out: VertexOut;
+   out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+   out.uv = uv;
+   return out;
+}
+
+const PQ_M1: f32 = 0.1593017578125;
+const PQ_M2: f32 = 78.84375;
+const PQ_C1: f32 = 0.8359375;
+const PQ_C2: f32 = 18.8515625;
+const PQ_C3: f32 = 18.6875;
+
+// pq signal to cd/m²
+fn pq_eotf(e: vec3<f32>) -> vec3<f32> {
+   let p = pow(clamp(e, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / PQ_M2));
+   return 10000.0 * pow(max(p - PQ_C1, vec3<f32>(0.0)) / (PQ_C2 - PQ_C3 * p), vec3<f32>(1.0 / PQ_M1));
+}
+
+// cd/m² to pq signal
+fn pq_inverse(nits: f32) -> f32 {
+   let y = pow(clamp(nits / 10000.0, 0.0, 1.0), PQ_M1);
+   return pow((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y), PQ_M2);
+}
+
+const HLG_A: f32 = 0.17883277;
+const HLG_B: f32 = 0.28466892;
+const HLG_C: f32 = 0.55991073;
+
+fn hlg_inverse_oetf(e: f32) -> f32 {
+   if e <= 0.5 {
+      return e * e / 3.0;
+   }
+   return (exp((e - HLG_C) / HLG_A) + HLG_B) / 12.0;
+}
+
+// hlg signal to cd/m² on a display of ``peak`` nits, the bt.2100 ootf with its peak dependent gamma
+fn hlg_eotf(e: vec3<f32>, peak: f32) -> vec3<f32> {
+   let scene = vec3<f32>(hlg_inverse_oetf(e.r), hlg_inverse_oetf(e.g), hlg_inverse_oetf(e.b));
+   let gamma = 1.2 + 0.42 * log(peak / 1000.0) / log(10.0);
+   let ys = dot(scene, vec3<f32>(0.2627, 0.6780, 0.0593));
+   return peak * pow(max(ys, 1e-6), gamma - 1.0) * scene;
+}
+
+// linear bt.2020 to linear bt.709, colours outside bt.709 are pulled toward their luminance
+// instead of clipping each channel, which keeps the hue
+fn bt2020_to_bt709(rgb: vec3<f32>) -> vec3<f32> {
+   let m = mat3x3<f32>(
+      vec3<f32>(1.6605, -0.1246, -0.0182),
+      vec3<f32>(-0.5876, 1.1329, -0.1006),
+      vec3<f32>(-0.0728, -0.0083, 1.1187),
+   );
+   let mapped = m * rgb;
+   let y = dot(mapped, vec3<f32>(0.2126, 0.7152, 0.0722));
+   let low = min(mapped.r, min(mapped.g, mapped.b));
+   if low >= 0.0 || y <= 0.0 {
+      return max(mapped, vec3<f32>(0.0));
+   }
+   return mix(vec3<f32>(y), mapped, y / (y - low));
+}
+
+fn hable_curve(x: f32) -> f32 {
+   let a = 0.15;
+   let b = 0.50;
+   let c = 0.10;
+   let d = 0.20;
+   let e = 0.02;
+   let f = 0.30;
+   return (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f;
+}
+
+// the bt.2390 eetf, a hermite knee in the pq domain from the content peak down to sdr white
+fn bt2390(nits: f32) -> f32 {
+   let source_peak = pq_inverse(params.peak_nits);
+   let e1 = clamp(pq_inverse(nits) / source_peak, 0.0, 1.0);
+   let max_lum = pq_inverse(params.sdr_white) / source_peak;
+   let ks = 1.5 * max_lum - 0.5;
+
+   // a peak at or below sdr white leaves no room for a knee
+   var e2 = e1;
+   if ks < 1.0 && e1 > ks {
+      let t = (e1 - ks) / (1.0 - ks);
+      let t2 = t * t;
+      let t3 = t2 * t;
+      e2 = (2.0 * t3 - 3.0 * t2 + 1.0) * ks
+         + (t3 - 2.0 * t2 + t) * (1.0 - ks)
+         + (-2.0 * t3 + 3.0 * t2) * max_lum;
+   }
+
+   return pq_eotf(vec3<f32>(min(e2, 1.0) * source_peak)).r / params.sdr_white;
+}
+
+// ``x`` in units of sdr white to 0..1
+fn tone_map(x: f32) -> f32 {
+   let peak = params.peak_nits / params.sdr_white;
+   switch params.mapper {
+      case 0u: {
+         return x * (1.0 + x / (peak * peak)) / (1.0 + x);
+      }
+      case 1u: {
+         return hable_curve(x) / hable_curve(peak);
+      }
+      default: {
+         return bt2390(x * params.sdr_white);
+      }
+   }
+}
+
+// normalised yuv to non linear rgb
+fn yuv_to_rgb(yuv: vec3<f32>) -> vec3<f32> {
+   var kr = 0.2126;
+   var kb = 0.0722;
+   if params.matrix == 2u {
+      kr = 0.299;
+      kb = 0.114;
+   } else if params.matrix == 3u {
+      kr = 0.2627;
+      kb = 0.0593;
+   }
+
+   var y = yuv.x;
+   var c = yuv.yz - 0.5;
+   if params.limited_range == 1u {
+      y = (y - 16.0 / 255.0) * 255.0 / 219.0;
+      c = c * 255.0 / 224.0;
+   }
+
+   let r = y + 2.0 * (1.0 - kr) * c.y;
+   let b = y + 2.0 * (1.0 - kb) * c.x;
+   let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+   return clamp(vec3<f32>(r, g, b), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// pixel coordinate of ``uv`` in a texture ``size`` big
+fn texel(uv: vec2<f32>, size: vec2<u32>) -> vec2<i32> {
+   let dims = vec2<i32>(size);
+   return clamp(vec2<i32>(uv * vec2<f32>(size)), vec2<i32>(0), dims - 1);
+}
+
+fn srgb_encode(linear: vec3<f32>) -> vec3<f32> {
+   let c = clamp(linear, vec3<f32>(0.0), vec3<f32>(1.0));
+   let low = c * 12.92;
+   let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+   return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+   let source = fetch(in.uv);
+
+   // deep colour sdr only needed decoding
+   if params.transfer == 0u {
+      return source;
+   }
+
+   var nits: vec3<f32>;
+   if params.transfer == 1u {
+      nits = pq_eotf(source.rgb);
+   } else {
+      nits = hlg_eotf(source.rgb, params.peak_nits);
+   }
+
+   var rgb = nits / params.sdr_white;
+   if params.bt2020 == 1u {
+      rgb = bt2020_to_bt709(rgb);
+   }
+
+   // tone mapping the brightest channel and scaling the rest by the same amount keeps the hue
+   let brightest = max(rgb.r, max(rgb.g, rgb.b));
+   if brightest > 0.0 {
+      rgb *= tone_map(brightest) / brightest;
+   }
+
+   return vec4<f32>(srgb_encode(rgb), source.a);
+}
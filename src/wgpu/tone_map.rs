@@ -0,0 +1,183 @@
+use eframe::wgpu::{BindGroup, CommandEncoder, Device, TextureFormat, TextureSampleType, TextureView};
+use gstreamer_video::{VideoColorMatrix, VideoColorPrimaries, VideoColorRange, VideoColorimetry, VideoTransferFunction};
+use serde::{Deserialize, Serialize};
+use crate::wgpu::pack::WgpuRenderPack;
+use crate::wgpu::post_process::{PassConfig, ShaderPass};
+
+/// what deep colour frames are decoded into, half floats so nothing is lost before the colour pass
+pub const DECODE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// luminance sdr white is mapped to, the bt.2408 reference level
+pub const DEFAULT_SDR_WHITE: f32 = 203.0;
+
+/// assumed when the stream has no mastering or content light level metadata
+pub const DEFAULT_HDR_PEAK: f32 = 1000.0;
+
+/// how highlights above sdr white are squeezed into range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ToneMapper {
+   Reinhard,
+   Hable,
+   #[default]
+   Bt2390,
+}
+
+impl ToneMapper {
+   pub const ALL: &'static [ToneMapper] = &[Self::Reinhard, Self::Hable, Self::Bt2390];
+
+   pub fn label(self) -> &'static str {
+      match self {
+         Self::Reinhard => "Reinhard",
+         Self::Hable => "Hable (filmic)",
+         Self::Bt2390 => "BT.2390",
+      }
+   }
+}
+
+/// transfer function of a 16 bit upload, anything that isn't pq or hlg is passed through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HdrTransfer {
+   Sdr,
+   Pq,
+   Hlg,
+}
+
+impl HdrTransfer {
+   pub fn of(colorimetry: &VideoColorimetry) -> Self {
+      match colorimetry.transfer() {
+         VideoTransferFunction::Smpte2084 => Self::Pq,
+         VideoTransferFunction::AribStdB67 => Self::Hlg,
+         _ => Self::Sdr,
+      }
+   }
+}
+
+/// layout of a deep colour upload, each has its own ``fetch_*.wgsl``
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToneMapSource {
+   /// one ``Rgb10a2Unorm`` texture
+   Rgb10a2,
+   /// one ``Rgba16Uint`` texture
+   Rgba16,
+   /// ``R16Uint`` luma and half size ``Rg16Uint`` chroma
+   YuvPlanes,
+}
+
+impl ToneMapSource {
+   pub const ALL: &'static [ToneMapSource] = &[Self::Rgb10a2, Self::Rgba16, Self::YuvPlanes];
+
+   fn source(self) -> String {
+      let fetch = match self {
+         Self::Rgb10a2 => include_str!("shaders/fetch_rgb10a2.wgsl"),
+         Self::Rgba16 => include_str!("shaders/fetch_rgba16.wgsl"),
+         Self::YuvPlanes => include_str!("shaders/fetch_yuv_planes.wgsl"),
+      };
+      format!("{fetch}{}", include_str!("shaders/tone_map.wgsl"))
+   }
+
+   fn config(self) -> PassConfig {
+      PassConfig {
+         sample_type: match self {
+            Self::Rgb10a2 => TextureSampleType::Float { filterable: true },
+            Self::Rgba16 | Self::YuvPlanes => TextureSampleType::Uint,
+         },
+         planes: if self == Self::YuvPlanes { 2 } else { 1 },
+         target: DECODE_FORMAT,
+      }
+   }
+}
+
+/// what the player wants hdr frames mapped with, ``peak_nits`` comes from the stream metadata
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+   pub mapper: ToneMapper,
+   pub peak_nits: Option<f32>,
+   pub sdr_white: f32,
+}
+
+impl Default for ToneMapping {
+   fn default() -> Self {
+      Self {
+         mapper: ToneMapper::default(),
+         peak_nits: None,
+         sdr_white: DEFAULT_SDR_WHITE,
+      }
+   }
+}
+
+/// matches ``Params`` in ``tone_map.wgsl``
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ToneMapUniforms {
+   transfer: u32,
+   mapper: u32,
+   bt2020: u32,
+   matrix: u32,
+   peak_nits: f32,
+   sdr_white: f32,
+   limited_range: u32,
+   _pad: f32,
+}
+
+impl ToneMapUniforms {
+   fn new(transfer: HdrTransfer, colorimetry: &VideoColorimetry, mapping: &ToneMapping) -> Self {
+      Self {
+         transfer: transfer as u32,
+         mapper: mapping.mapper as u32,
+         bt2020: (colorimetry.primaries() == VideoColorPrimaries::Bt2020) as u32,
+         matrix: match colorimetry.matrix() {
+            VideoColorMatrix::Rgb => 0,
+            VideoColorMatrix::Bt601 | VideoColorMatrix::Fcc | VideoColorMatrix::Smpte240m => 2,
+            VideoColorMatrix::Bt2020 => 3,
+            _ => 1,
+         },
+         peak_nits: mapping.peak_nits.unwrap_or(DEFAULT_HDR_PEAK).max(mapping.sdr_white),
+         sdr_white: mapping.sdr_white.max(1.0),
+         limited_range: (colorimetry.range() != VideoColorRange::Range0_255) as u32,
+         _pad: 0.0,
+      }
+   }
+}
+
+/// first pass for deep colour uploads, reads the planes, linearises pq or hlg,
+/// maps bt.2020 into bt.709 and tone maps down to gamma encoded sdr for the rest of the passes
+pub struct ToneMapPass {
+   pass: ShaderPass,
+}
+
+impl ToneMapPass {
+   pub fn new(device: &Device, source: ToneMapSource) -> Self {
+      Self {
+         pass: ShaderPass::with_config(
+            device,
+            "Tone map",
+            &source.source(),
+            std::mem::size_of::<ToneMapUniforms>() as u64,
+            source.config(),
+         ),
+      }
+   }
+
+   pub fn bind(&self, device: &Device, planes: &[&TextureView]) -> BindGroup {
+      self.pass.bind_planes(device, planes)
+   }
+
+   pub fn run(&self, render_pack: &WgpuRenderPack, encoder: &mut CommandEncoder, bind_group: &BindGroup, target: &TextureView, colorimetry: &VideoColorimetry, mapping: &ToneMapping) {
+      let uniforms = ToneMapUniforms::new(HdrTransfer::of(colorimetry), colorimetry, mapping);
+      self.pass.run(render_pack, encoder, bind_group, target, bytemuck::bytes_of(&uniforms));
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::wgpu::shader_chain::validate;
+
+   #[test]
+   fn tone_map_validates() {
+      for source in ToneMapSource::ALL {
+         let planes = source.config().planes;
+         assert!(validate(&source.source(), planes).is_ok(), "{source:?} {:?}", validate(&source.source(), planes));
+      }
+   }
+}